@@ -10,13 +10,29 @@
 //!
 /// Contains functions that allow CIder to create docker images, parse JSON, and more.
 pub mod utils;
+pub use utils::build_context;
+pub use utils::completions;
+pub use utils::conditions;
 pub use utils::config;
 pub use utils::config_generator;
+pub use utils::docker_cache;
+pub use utils::doctor;
+pub use utils::error;
+pub use utils::events;
 pub use utils::executor;
+pub use utils::ignore;
 pub use utils::parsing;
-// pub use utils::watcher;
+pub use utils::redaction;
+pub use utils::remote;
+pub use utils::reporters;
+pub use utils::resources;
+pub use utils::results;
+pub use utils::sandbox;
+pub use utils::terminal;
+pub use utils::watcher;
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod systests {
     use crate::parsing::json_parser;
     use log::info;
@@ -27,6 +43,11 @@ mod systests {
         assert!(true);
     }
 
+    #[test]
+    fn test_parse_json_from_file_is_not_null() {
+        assert!(!json_parser::parse_json_string("cider_config.json").is_null());
+    }
+
     #[test]
     fn test_parse_top_level_actions() {
         let config = json_parser::new_top_level("cider_config.json");
@@ -36,6 +57,12 @@ mod systests {
         assert!(true);
     }
 
+    #[test]
+    fn test_parse_top_level_actions_are_found() {
+        let config = json_parser::new_top_level("cider_config.json");
+        assert!(!config.get_actions().is_empty());
+    }
+
     #[test]
     fn test_parse_pipeline_actions() {
         let config = json_parser::new_top_level("cider_config.json");
@@ -61,4 +88,17 @@ mod systests {
             info!("{:#?}", pipeline);
         }
     }
+
+    #[test]
+    fn test_parse_from_path() {
+        let path = std::path::PathBuf::from("cider_config.json");
+        assert_eq!(
+            json_parser::new_top_level(&path),
+            json_parser::new_top_level(path.to_str().unwrap())
+        );
+        assert_eq!(
+            json_parser::new_top_level(path.as_path()).get_pipelines().len(),
+            json_parser::new_top_level(path).get_pipelines().len()
+        );
+    }
 }
@@ -1,149 +1,875 @@
 pub mod utils;
 
 //package imports
+use cider::completions;
+use cider::config::{Action, TopLevelConfiguration};
+use cider::config_generator;
+use cider::doctor;
+use cider::events::{Event, EventKind, EventSender};
 use cider::executor::*;
+use cider::ignore::read_gitignore;
 use cider::parsing::*;
+use cider::remote;
+use cider::reporters::{Reporter, Reporters};
+use cider::resources;
+use cider::results::{RunMetadata, RunReport};
+use cider::sandbox::Sandbox;
+use cider::watcher::{WatchMode, Watcher};
 
 //arg parser
-use clap::Parser;
+use clap::{CommandFactory, Parser, ValueEnum};
 
 //logger
-use log::info;
+use log::{error, warn};
 use simplelog::*;
 
 //std library imports
-use std::collections::HashMap;
-use std::ffi::OsStr;
-use std::ffi::OsString;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::Path;
-use std::time::Duration;
-use std::time::UNIX_EPOCH;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::{thread, time};
 
 #[derive(Parser, Default, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Arguments {
+    /// The configuration file to run. By default, cider_config.json is looked for within the current directory,
+    /// then within each of its parents
     #[arg(short, long)]
-    config: Option<String>,
+    config: Option<PathBuf>,
 
+    /// Stop looking for cider_config.json within parent directories once this directory has been searched
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["config", "config_url"])]
+    config_boundary: Option<PathBuf>,
+
+    /// Fetch the configuration to run from this http:// or https:// URL, instead of reading a configuration file. Relative
+    /// paths within it are resolved against the current directory
+    #[arg(long, value_name = "URL", conflicts_with_all = ["config", "init_docker"])]
+    config_url: Option<String>,
+
+    /// Parse the configuration file as this format, instead of the format implied by its extension
+    #[arg(long, value_enum)]
+    config_format: Option<FormatMode>,
+
+    /// Resolve relative paths within the configuration against the current directory, instead of against the directory
+    /// containing the configuration file
+    #[arg(long, default_value_t = false)]
+    paths_relative_to_cwd: bool,
+
+    /// Watch the source directory, running the configuration whenever it changes
     #[arg(short, long, default_value_t = false)]
     watch: bool,
+
+    /// Run the configuration against a copy of its source within a throwaway temporary directory, so that it cannot
+    /// touch the original files. The results of the run are written within the sandbox as well
+    #[arg(long, default_value_t = false, conflicts_with = "watch")]
+    sandbox: bool,
+
+    /// Leave the sandbox in place once the run has finished, instead of removing it
+    #[arg(long, default_value_t = false, requires = "sandbox")]
+    keep_sandbox: bool,
+
+    /// When to color terminal output
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Treat references to unset environment variables in the configuration as errors
+    #[arg(long, default_value_t = false)]
+    strict_env: bool,
+
+    /// Load environment variables from this dotenv file, instead of the env_file of the configuration
+    #[arg(long)]
+    env_file: Option<String>,
+
+    /// Replace ${NAME} references within the configuration with the variables of this JSON file, unless NAME is set in
+    /// the environment
+    #[arg(long, value_name = "FILE")]
+    vars: Option<String>,
+
+    /// Print the fully-resolved configuration as JSON and exit without running any actions
+    #[arg(long, default_value_t = false)]
+    print_config: bool,
+
+    /// Print the Dockerfile that the named docker action, or every docker action if none is named, would build, then
+    /// exit without writing or building anything
+    #[arg(long, value_name = "ACTION", num_args = 0..=1, default_missing_value = "")]
+    print_dockerfile: Option<String>,
+
+    /// Print the steps of the named action in the order they run, with the scripts, interpreter, and working directory
+    /// they would be run with, then exit without running anything
+    #[arg(long, value_name = "ACTION")]
+    list_steps: Option<String>,
+
+    /// Check that the configuration can be run, reporting any problems, and exit without running any actions
+    #[arg(long, default_value_t = false)]
+    check: bool,
+
+    /// Treat a configuration with no actions to run as an error, instead of running nothing and succeeding
+    #[arg(long, default_value_t = false)]
+    fail_on_empty: bool,
+
+    /// Print why each action was run or skipped once the run has finished
+    #[arg(long, default_value_t = false)]
+    explain: bool,
+
+    /// Write logs, results, docker metrics, and the dump of the parsed configuration beneath this directory, instead of
+    /// within dist/ and metrics/
+    #[arg(long)]
+    output_dir: Option<String>,
+
+    /// Write an event to this file, as newline-delimited JSON, whenever an action or step starts, writes output, or
+    /// finishes
+    #[arg(long)]
+    events: Option<String>,
+
+    /// Also write the output of each action to its own file within the output directory, named after the action
+    #[arg(long, default_value_t = false)]
+    split_output: bool,
+
+    /// Write a compact JSON summary of the run to this file once the run has finished: how many actions passed,
+    /// failed, and were skipped, how long the run took, and which actions failed
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<String>,
+
+    /// Print where the time of the run went once the run has finished
+    #[arg(long, default_value_t = false)]
+    stats: bool,
+
+    /// Capture the standard error of every step along with its standard output, in the order they were written
+    #[arg(long, default_value_t = false)]
+    merge_output: bool,
+
+    /// The most pipelines to run at the same time
+    #[arg(short, long, default_value_t = 1, visible_alias = "parallel-pipelines")]
+    jobs: usize,
+
+    /// The most weight the pipelines running at the same time may add up to, where a pipeline weighs as much as its
+    /// heaviest action. Defaults to the number of jobs
+    #[arg(long)]
+    resource_budget: Option<usize>,
+
+    /// Run pipelines and steps one at a time in a stable order, regardless of --jobs and the concurrency of each action,
+    /// so that every run writes its output in the same order
+    #[arg(long, default_value_t = false)]
+    deterministic: bool,
+
+    /// Kill any step that runs for longer than this, e.g. 30s, 5m or 1h, unless its action sets a step_timeout. 0 lets
+    /// steps run for as long as they need
+    #[arg(long, value_parser = parse_duration)]
+    step_timeout: Option<time::Duration>,
+
+    /// Fail any pipeline that waits for longer than this, e.g. 30s, 5m or 1h, for the pipelines and actions it requires,
+    /// unless it sets a requires_timeout. 0 lets pipelines wait for as long as they need
+    #[arg(long, value_parser = parse_duration)]
+    requires_timeout: Option<time::Duration>,
+
+    /// Keep at most this many bytes of each stream of a step's output, truncating the rest. 0 keeps everything
+    #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_BYTES)]
+    max_output_bytes: usize,
+
+    /// When a step fails, log the command it was run with, its working directory, exit code, environment, and standard
+    /// error together, masking environment variables that look like secrets
+    #[arg(long, default_value_t = false)]
+    verbose_errors: bool,
+
+    /// Rebuild the image of every docker action, even if none of its cache_key_files changed
+    #[arg(long, default_value_t = false)]
+    no_cache: bool,
+
+    /// Measure the peak memory and CPU time of every step, listing the heaviest steps with --stats. Only supported on
+    /// unix
+    #[arg(long, default_value_t = false)]
+    profile_resources: bool,
+
+    /// Stop the run as soon as an action fails without being allowed to
+    #[arg(long, default_value_t = false, overrides_with = "no_fail_fast")]
+    fail_fast: bool,
+
+    /// Run every action regardless of failures, and report the aggregate result (default)
+    #[arg(long, default_value_t = false, overrides_with = "fail_fast")]
+    no_fail_fast: bool,
+
+    /// Stop starting new actions once this many actions have failed without being allowed to, counted across every
+    /// pipeline
+    #[arg(long, value_name = "N")]
+    max_failures: Option<usize>,
+
+    /// Format step output for a CI system
+    #[arg(long, value_enum, default_value_t = CiMode::None)]
+    ci: CiMode,
+
+    /// When to pull the base image of docker actions
+    #[arg(long, value_enum, default_value_t = PullMode::Missing)]
+    pull: PullMode,
+
+    /// In watch mode, run the configuration as soon as watching begins (default)
+    #[arg(long, default_value_t = false, overrides_with = "watch_only")]
+    run_on_start: bool,
+
+    /// In watch mode, wait for the first change before running the configuration
+    #[arg(long, default_value_t = false, overrides_with = "run_on_start")]
+    watch_only: bool,
+
+    /// In watch mode, cancel a run that is still in progress when a change is detected, killing its running step, and
+    /// start a new run straight away
+    #[arg(long, default_value_t = false)]
+    abort_previous: bool,
+
+    /// In watch mode, treat files modified within this long before starting as changed, e.g. 30s, 5m or 1h
+    #[arg(long, value_parser = parse_duration)]
+    since: Option<time::Duration>,
+
+    /// In watch mode, how changes to files are detected
+    #[arg(long, value_enum, default_value_t = WatchStrategy::Mtime)]
+    watch_mode: WatchStrategy,
+
+    /// In watch mode, how many milliseconds pass between each check for changes
+    #[arg(long, value_name = "MS", value_parser = parse_millis, default_value = "2000")]
+    watch_interval: time::Duration,
+
+    /// In watch mode, how many milliseconds no file may change for after a change before the configuration runs
+    #[arg(long, value_name = "MS", value_parser = parse_millis, default_value = "500")]
+    watch_debounce: time::Duration,
+
+    /// Print the completion script for this shell and exit
+    #[arg(long, value_enum)]
+    completions: Option<completions::Shell>,
+
+    /// Write a starter configuration that runs its actions with docker to the configuration file, then exit
+    #[arg(long, default_value_t = false)]
+    init_docker: bool,
+
+    /// Used with --init-docker, replace the configuration file if it already exists
+    #[arg(long, default_value_t = false, requires = "init_docker")]
+    force: bool,
+
+    /// Check which backends are usable, whether the configuration file exists, and whether the output directories are
+    /// writable, then exit
+    #[arg(long, default_value_t = false)]
+    doctor: bool,
+}
+
+/// Controls whether terminal output is colored
+#[derive(ValueEnum, Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum ColorMode {
+    /// Color output only when the terminal supports it
+    #[default]
+    Auto,
+    /// Always color output
+    Always,
+    /// Never color output
+    Never,
+}
+
+/// Controls when the base image of a docker action is pulled
+#[derive(ValueEnum, Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum PullMode {
+    /// Pull the image only when it is not already present locally
+    #[default]
+    Missing,
+    /// Pull the image before every build
+    Always,
+    /// Never pull the image, failing docker actions whose image is not already present locally
+    Never,
+}
+
+impl From<PullMode> for PullPolicy {
+    fn from(mode: PullMode) -> Self {
+        match mode {
+            PullMode::Missing => PullPolicy::Missing,
+            PullMode::Always => PullPolicy::Always,
+            PullMode::Never => PullPolicy::Never,
+        }
+    }
+}
+
+/// Controls how watch mode decides that a file has changed
+#[derive(ValueEnum, Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum WatchStrategy {
+    /// Compare the modification times of files
+    #[default]
+    Mtime,
+    /// Compare the contents of files, so that touching a file without changing it does not trigger a run
+    Hash,
+}
+
+impl From<WatchStrategy> for WatchMode {
+    fn from(strategy: WatchStrategy) -> Self {
+        match strategy {
+            WatchStrategy::Mtime => WatchMode::Default,
+            WatchStrategy::Hash => WatchMode::Hash,
+        }
+    }
+}
+
+/// Chooses the format a configuration file is parsed as
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum FormatMode {
+    /// Parse the configuration as JSON
+    Json,
+}
+
+impl From<FormatMode> for json_parser::ConfigFormat {
+    fn from(mode: FormatMode) -> Self {
+        match mode {
+            FormatMode::Json => json_parser::ConfigFormat::Json,
+        }
+    }
+}
+
+/// Controls how step output is formatted for the CI system cider runs within
+#[derive(ValueEnum, Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum CiMode {
+    /// Print step output as-is
+    #[default]
+    None,
+    /// Group step output and annotate failures for GitHub Actions
+    Github,
+}
+
+impl From<CiMode> for CiFormat {
+    fn from(mode: CiMode) -> Self {
+        match mode {
+            CiMode::None => CiFormat::Plain,
+            CiMode::Github => CiFormat::Github,
+        }
+    }
+}
+
+impl From<ColorMode> for ColorChoice {
+    fn from(mode: ColorMode) -> Self {
+        match mode {
+            ColorMode::Auto => ColorChoice::Auto,
+            ColorMode::Always => ColorChoice::Always,
+            ColorMode::Never => ColorChoice::Never,
+        }
+    }
 }
 
 fn main() -> std::io::Result<()> {
-    setup_logger().unwrap_or_else(|err| {
+    let args = Arguments::parse();
+
+    if let Some(shell) = args.completions {
+        print!("{}", completions::generate(shell, Arguments::command()));
+        return Ok(());
+    }
+
+    let mut paths = OutputPaths::new(args.output_dir.as_deref());
+    // Output directories are checked before the logger creates them, so one that cannot be created is reported
+    if args.doctor {
+        let config = config_path(&args)?;
+        let checks = doctor::run_checks(&config, &paths.dirs());
+        for check in &checks {
+            println!("{}", check.line());
+        }
+        if !doctor::passed(&checks) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    setup_logger(args.color.into(), &paths).unwrap_or_else(|err| {
         panic!(
             "Logs could not be properly set up due to the following error:\n{}",
             err
         );
     });
 
-    let args = Arguments::parse();
+    // A new configuration is written where it was asked for, rather than over one found within a parent
+    // directory
+    let filename = if args.init_docker {
+        args.config.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG))
+    } else {
+        config_path(&args)?
+    };
+
+    if args.init_docker {
+        config_generator::write_config(&filename, &config_generator::docker_template(), args.force)
+            .inspect_err(|err| error!("{}", err))?;
+        println!("Wrote a docker configuration to {}", filename.display());
+        return Ok(());
+    }
+
+    let parse_options = json_parser::ParseOptions {
+        strict_env: args.strict_env,
+        env_file: args.env_file,
+        vars_file: args.vars,
+        format: args.config_format.map(Into::into),
+        paths_relative_to_cwd: args.paths_relative_to_cwd,
+    };
+    let mut conf = match &args.config_url {
+        Some(url) => {
+            let contents = remote::fetch_config(url).inspect_err(|err| error!("{}", err))?;
+            json_parser::parse_from_str_with_options(&contents, &parse_options)
+        }
+        None => json_parser::new_top_level_with_options(&filename, &parse_options),
+    };
+    let config_name = args
+        .config_url
+        .clone()
+        .unwrap_or_else(|| filename.display().to_string());
+
+    if args.print_config {
+        println!("{}", conf.to_json().pretty(4));
+        return Ok(());
+    }
+
+    if let Some(name) = &args.print_dockerfile {
+        if !print_dockerfiles(&conf, name) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(name) = &args.list_steps {
+        if !list_steps(&conf, name) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut problems = json_parser::validate_config(&conf);
+    if args.fail_on_empty {
+        problems.extend(json_parser::empty_problems(&conf));
+    }
+    for problem in &problems {
+        error!("{}", problem);
+    }
+    if args.check {
+        if problems.is_empty() {
+            println!("{} is valid.", config_name);
+            return Ok(());
+        }
+        std::process::exit(1);
+    }
+    if !problems.is_empty() {
+        error!(
+            "The configuration in {} cannot be run, see the errors above.",
+            config_name
+        );
+        std::process::exit(1);
+    }
 
-    let filename = if args.config.is_none() {
-        "cider_config.json".to_string()
+    // Steps run within the working directory, so a sandboxed run moves into the copy of it for as long as it runs
+    let working_dir = std::env::current_dir()?;
+    let sandbox = if args.sandbox {
+        let sandbox = Sandbox::create(&mut conf, &working_dir, args.keep_sandbox)?;
+        println!("==> Running in a sandbox at {}", sandbox.root().display());
+        paths.metrics = working_dir.join(&paths.metrics).to_string_lossy().into_owned();
+        std::env::set_current_dir(sandbox.working_dir())?;
+        Some(sandbox)
     } else {
-        args.config.unwrap()
+        None
     };
 
-    let conf = json_parser::new_top_level(&filename);
-    let mut output_file = File::create(curate_filepath(
-        conf.s_config.get_output(),
-        "cider_output.txt",
-    ))?;
+    let output_dir = paths
+        .results
+        .clone()
+        .unwrap_or_else(|| conf.s_config.get_output().to_string());
+    let mut output_file = create_output_file(&output_dir, "cider_output.txt")?;
+    let log_dir = args.split_output.then_some(output_dir.as_str());
+
+    let mut reporters = Reporters::default();
+    reporters.attach(Arc::new(OutputFileReporter(Mutex::new(output_file.try_clone()?))));
+    let event_writer = match &args.events {
+        Some(path) => {
+            let (events, writer) = spawn_event_writer(Path::new(path))?;
+            reporters.attach(Arc::new(events));
+            Some(writer)
+        }
+        None => None,
+    };
+    if args.profile_resources && !resources::supported() {
+        warn!(
+            "--profile-resources is not supported on this platform, so no step records its resources"
+        );
+    }
+    let exec_options = ExecOptions {
+        fail_fast: args.fail_fast,
+        max_failures: args.max_failures,
+        failures: Default::default(),
+        pull: args.pull.into(),
+        ci: args.ci.into(),
+        merge_output: args.merge_output,
+        jobs: args.jobs,
+        resource_budget: args.resource_budget,
+        step_timeout: args.step_timeout,
+        requires_timeout: args.requires_timeout,
+        events: None,
+        reporters,
+        cancel: None,
+        metrics_dir: Some(paths.metrics.clone()),
+        max_output_bytes: Some(args.max_output_bytes),
+        deterministic: args.deterministic,
+        step_outputs: Default::default(),
+        verbose_errors: args.verbose_errors,
+        no_cache: args.no_cache,
+        before_each: vec![],
+        after_each: vec![],
+        profile_resources: args.profile_resources,
+    };
 
     let source_dir = Path::new(conf.s_config.get_source());
 
     if args.watch {
-        let mut elapsed_times = HashMap::<OsString, Duration>::new();
-        let mut recent_file_changed = get_least_time(&elapsed_times);
+        let mut watcher = Watcher::new(Some(args.watch_mode.into()), source_dir.to_path_buf());
+        if conf.s_config.get_use_gitignore() {
+            watcher.set_ignore_patterns(read_gitignore(source_dir)?);
+        }
+        if let Some(since) = args.since {
+            watcher.set_since(since);
+        }
+        watcher.set_interval(args.watch_interval);
+        watcher.set_debounce(args.watch_debounce);
+        if args.abort_previous {
+            return watch_aborting_previous(
+                &conf,
+                &exec_options,
+                &output_file,
+                log_dir,
+                &mut watcher,
+                !args.watch_only,
+            );
+        }
+        if !args.watch_only {
+            run_and_report(&conf, &exec_options, &mut output_file, log_dir)?;
+            watcher.record_run();
+        }
+        println!("{}", watcher.status());
         loop {
-            get_files_time_elapsed_since_changed(&mut elapsed_times, source_dir)?;
-            let checked_time = get_least_time(&elapsed_times);
-            if checked_time < recent_file_changed {
-                recent_file_changed = checked_time;
-                println!("Changes detected in source directory.");
-                output_file
-                    .write_fmt(format_args!("{:#?}", exec_actions(&conf.get_all_actions())))?;
-            } else {
-                recent_file_changed = checked_time;
-                info!(
-                    "File in watched directory most recently changed {:#?} ago.",
-                    recent_file_changed
-                );
-                // println!("Waiting for changes to be made to source directory.");
+            if watcher.poll()? {
+                watcher.settle()?;
+                watcher.announce_change();
+                run_and_report(&conf, &exec_options, &mut output_file, log_dir)?;
+                watcher.record_run();
+                println!("{}", watcher.status());
             }
-            thread::sleep(time::Duration::from_millis(2000));
+            watcher.heartbeat();
+            watcher.wait();
         }
-    } else {
-        output_file.write_fmt(format_args!("{:#?}", exec_actions(&conf.get_all_actions())))?;
     }
 
-    let mut file = File::create("./dist/output/config_output.txt")?;
+    let started = time::Instant::now();
+    let report = run_and_report(&conf, &exec_options, &mut output_file, log_dir)?;
+    std::env::set_current_dir(&working_dir)?;
+    // Every event has been sent once the sender is dropped, so the writer finishes once it has written them all
+    drop(exec_options);
+    if let Some(event_writer) = event_writer {
+        event_writer.join().expect("The event writer panicked.")?;
+    }
+    if args.explain {
+        for line in report.explain() {
+            println!("{}", line);
+        }
+    }
+    if args.stats {
+        for line in report.stats(started.elapsed(), SLOWEST_STEPS) {
+            println!("{}", line);
+        }
+    }
+    if let Some(path) = &args.summary_json {
+        write_summary_json(Path::new(path), &report, started.elapsed())?;
+    }
+
+    let mut file = create_output_file(&paths.config_dump, "config_output.txt")?;
     file.write_fmt(format_args!("{:#?}", conf))?;
 
+    // Exiting skips destructors, so the sandbox is removed before the exit code is decided
+    drop(sandbox);
+    if !report.succeeded() {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-fn get_least_time(elapsed_times: &HashMap<OsString, Duration>) -> Duration {
-    let mut least_time = UNIX_EPOCH.elapsed().unwrap();
-    for entry in elapsed_times {
-        if entry.1 < &least_time {
-            least_time = *entry.1;
-            info!("The file with the newest changes is {:#?} with the last change {:#?} ago",entry.0, entry.1);
+/// Watches for changes like the default watch loop, but runs the configuration in the background so that a change
+/// detected while a run is in progress cancels it and starts a new run
+fn watch_aborting_previous(
+    conf: &TopLevelConfiguration,
+    exec_options: &ExecOptions,
+    output_file: &File,
+    log_dir: Option<&str>,
+    watcher: &mut Watcher,
+    run_on_start: bool,
+) -> std::io::Result<()> {
+    thread::scope(|scope| {
+        let start_run = || -> std::io::Result<_> {
+            let cancel = CancelToken::default();
+            let options = ExecOptions {
+                cancel: Some(cancel.clone()),
+                ..exec_options.clone()
+            };
+            let mut output_file = output_file.try_clone()?;
+            let run = scope.spawn(move || run_and_report(conf, &options, &mut output_file, log_dir));
+            Ok((cancel, run))
+        };
+        let mut current = if run_on_start { Some(start_run()?) } else { None };
+        println!("{}", watcher.status());
+        loop {
+            if current.as_ref().is_some_and(|(_, run)| run.is_finished()) {
+                let (_, run) = current.take().unwrap();
+                // A run that failed has already logged why, so watching carries on regardless
+                run.join().expect("A run panicked.").ok();
+                watcher.record_run();
+                println!("{}", watcher.status());
+            }
+            if watcher.poll()? {
+                watcher.settle()?;
+                if let Some((cancel, run)) = current.take() {
+                    println!("==> Aborting the run in progress");
+                    cancel.cancel();
+                    run.join().expect("A run panicked.").ok();
+                }
+                watcher.announce_change();
+                current = Some(start_run()?);
+            }
+            watcher.heartbeat();
+            watcher.wait();
+        }
+    })
+}
+
+/// Prints the Dockerfile of the docker action titled `name`, or of every docker action if `name` is empty, returning
+/// whether there was a Dockerfile to print
+fn print_dockerfiles(conf: &TopLevelConfiguration, name: &str) -> bool {
+    let actions = conf
+        .get_all_actions()
+        .into_iter()
+        .filter(|action| name.is_empty() || action.shared_config.title() == Some(name))
+        .collect::<Vec<_>>();
+    if actions.is_empty() {
+        error!("There is no action named \"{}\" in the configuration", name);
+        return false;
+    }
+    let docker_actions = actions
+        .iter()
+        .filter(|action| action.shared_config.get_backend().eq_ignore_ascii_case("docker"))
+        .collect::<Vec<_>>();
+    if docker_actions.is_empty() {
+        if name.is_empty() {
+            error!("No action in the configuration uses the docker backend");
+        } else {
+            error!("The action \"{}\" does not use the docker backend", name);
+        }
+        return false;
+    }
+    for action in docker_actions {
+        if name.is_empty() {
+            println!("# {}", action.shared_config.title().unwrap_or_default());
+        }
+        print!("{}", preview_dockerfile(action).replace("\r\n", "\n"));
+    }
+    true
+}
+
+/// Prints how every step of the actions named `name` would be run, see [`plan_steps`], returning false if there is no
+/// such action
+///
+/// Every action of that name is listed, such as when actions of different pipelines share a name.
+fn list_steps(conf: &TopLevelConfiguration, name: &str) -> bool {
+    let actions = conf
+        .get_all_actions()
+        .into_iter()
+        .filter(|action| action.shared_config.title() == Some(name))
+        .collect::<Vec<_>>();
+    if actions.is_empty() {
+        error!("There is no action named \"{}\" in the configuration", name);
+        return false;
+    }
+    for action in actions {
+        for line in describe_steps(&action) {
+            println!("{}", line);
+        }
+    }
+    true
+}
+
+/// Describes the steps of `action`, as printed by `--list-steps`
+fn describe_steps(action: &Action) -> Vec<String> {
+    let name = action.shared_config.title().unwrap_or("<untitled>");
+    let mut lines = vec![match action.get_pipeline() {
+        Some(pipeline) => format!("{} (pipeline {}):", name, pipeline),
+        None => format!("{}:", name),
+    }];
+    let steps = plan_steps(action);
+    if steps.is_empty() {
+        lines.push("  No steps".to_string());
+    }
+    if *action.action_config.get_shared_shell() && steps.len() > 1 {
+        lines.push("  The steps share a single shell, as shared_shell is enabled".to_string());
+    }
+    for (index, step) in steps.iter().enumerate() {
+        lines.push(format!("  {}. {}", index + 1, step.name));
+        lines.push(format!("     script:      {}", step.script));
+        lines.push(format!("     interpreter: {}", step.interpreter));
+        lines.push(format!("     working dir: {}", step.working_dir));
+        if let Some(stdin) = &step.stdin {
+            lines.push(format!("     stdin:       {}", stdin));
+        }
+    }
+    lines
+}
+
+/// The configuration file that is run when `--config` is not given
+const DEFAULT_CONFIG: &str = "cider_config.json";
+
+/// Returns the configuration file to run, which is the one given with `--config`, or else the one found by
+/// [`discover_config`] from the current directory
+///
+/// If no configuration is found, cider_config.json within the current directory is returned, so that the error
+/// reported names where a configuration was expected.
+fn config_path(args: &Arguments) -> std::io::Result<PathBuf> {
+    if let Some(config) = &args.config {
+        return Ok(config.clone());
+    }
+    if Path::new(DEFAULT_CONFIG).is_file() {
+        return Ok(PathBuf::from(DEFAULT_CONFIG));
+    }
+    let cwd = std::env::current_dir()?;
+    let boundary = args.config_boundary.as_ref().map(|boundary| cwd.join(boundary));
+    match discover_config(&cwd, boundary.as_deref()) {
+        Some(config) => {
+            println!("==> Using the configuration at {}", config.display());
+            Ok(config)
+        }
+        None => Ok(PathBuf::from(DEFAULT_CONFIG)),
+    }
+}
+
+/// Looks for cider_config.json within `start`, then within each of its parents in turn, like cargo looks for
+/// Cargo.toml, returning the first one found
+///
+/// The search stops at the root of the filesystem, or once `boundary` has been searched if it is one of the
+/// directories searched. Returns None if none of the directories searched contains a configuration.
+fn discover_config(start: &Path, boundary: Option<&Path>) -> Option<PathBuf> {
+    let boundary =
+        boundary.map(|boundary| boundary.canonicalize().unwrap_or(boundary.to_path_buf()));
+    let start = start.canonicalize().unwrap_or(start.to_path_buf());
+    for dir in start.ancestors() {
+        let config = dir.join(DEFAULT_CONFIG);
+        if config.is_file() {
+            return Some(config);
         }
+        if boundary.as_deref() == Some(dir) {
+            break;
+        }
+    }
+    None
+}
+
+/// How many of the slowest steps `--stats` lists
+const SLOWEST_STEPS: usize = 5;
+
+/// Runs a configuration followed by its post_run actions, then writes and summarizes their results
+///
+/// If `log_dir` is given, the output of each action is also written to its own file within it, or within the output
+/// directory of the action if it sets a different one from the configuration.
+fn run_and_report(
+    conf: &TopLevelConfiguration,
+    exec_options: &ExecOptions,
+    output_file: &mut File,
+    log_dir: Option<&str>,
+) -> std::io::Result<RunReport> {
+    let mut metadata = RunMetadata::capture();
+    send_run_event(exec_options, EventKind::RunStarted { metadata: metadata.clone() });
+    let report = exec_config(conf, exec_options).map_err(|err| {
+        error!("{}", err);
+        std::io::Error::other(err)
+    });
+    // post_run actions run whether or not the run failed, and their failures never change its result
+    let post_run = exec_post_run(conf, exec_options);
+    for action in &post_run.actions {
+        let line = format!("post_run {}: {}", action.name, action.status);
+        println!("{}", line);
+        writeln!(output_file, "{}", line)?;
     }
-    info!(
-        "Most recent time in a which a file was changed: {:#?}",
-        least_time
-    );
-    least_time
+    metadata.finish();
+    for line in metadata.summary() {
+        writeln!(output_file, "{}", line)?;
+    }
+    send_run_event(exec_options, EventKind::RunFinished { metadata });
+    if let Some(log_dir) = log_dir {
+        let mut actions = report
+            .as_ref()
+            .map(|report| report.actions.clone())
+            .unwrap_or_default();
+        actions.extend(post_run.actions);
+        write_action_logs(
+            Path::new(log_dir),
+            conf.s_config.get_output(),
+            &RunReport {
+                actions,
+                ..Default::default()
+            },
+        )?;
+    }
+    report
 }
 
-fn get_files_time_elapsed_since_changed<'a>(
-    elapsed_times: &'a mut HashMap<OsString, Duration>,
-    path: &'a Path,
+/// Writes the summary of a run that took `total` to `path`, see [`RunReport::summary_json`]
+fn write_summary_json(
+    path: &Path,
+    report: &RunReport,
+    total: time::Duration,
 ) -> std::io::Result<()> {
-    info!("Getting elapsed time for files within {:#?}", path);
-    for entry in fs::read_dir(path)? {
-        if Path::new(&entry.as_ref().unwrap().file_name()).extension().and_then(OsStr::to_str) == Some("class") || entry.as_ref().unwrap().file_name() == "package-lock.json" {
-            continue;
-        }
-        if !elapsed_times.contains_key(&entry.as_ref().unwrap().file_name()) {
-            elapsed_times.insert(
-                entry.as_ref().unwrap().file_name().to_os_string().clone(),
-                entry
-                    .as_ref()
-                    .unwrap()
-                    .metadata()?
-                    .modified()?
-                    .elapsed()
-                    .unwrap(),
-            );
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, report.summary_json(total).pretty(4) + "\n")
+}
+
+/// Sends an [`Event`] about the run as a whole to every reporter of the run
+fn send_run_event(exec_options: &ExecOptions, kind: EventKind) {
+    exec_options.all_reporters().event(Event::new(kind, None, None, None));
+}
+
+/// Writes the output of each action within `report` to its own file within `dir`, named by [`RunReport::log_filenames`]
+///
+/// An action whose output directory differs from `inherited_output`, the output directory of the configuration, has
+/// one of its own, either set on the action or inherited from its pipeline, so its file is written there instead.
+fn write_action_logs(
+    dir: &Path,
+    inherited_output: &str,
+    report: &RunReport,
+) -> std::io::Result<()> {
+    for (result, filename) in report.actions.iter().zip(report.log_filenames()) {
+        let dir = if result.output_dir == inherited_output {
+            dir
         } else {
-            elapsed_times.insert(
-                entry.as_ref().unwrap().file_name().clone(),
-                entry
-                    .as_ref()
-                    .unwrap()
-                    .metadata()?
-                    .modified()?
-                    .elapsed()
-                    .unwrap(),
-            );
+            Path::new(&result.output_dir)
+        };
+        fs::create_dir_all(dir)?;
+        let mut file = File::create(dir.join(filename))?;
+        for output in &result.output {
+            write!(file, "{}", output)?;
+            if !output.ends_with('\n') {
+                writeln!(file)?;
+            }
         }
-        if entry.as_ref().unwrap().metadata()?.is_dir() && entry.as_ref().unwrap().file_name() != "target" && entry.as_ref().unwrap().file_name() != "node_modules" && entry.as_ref().unwrap().file_name() != "bin" && entry.as_ref().unwrap().file_name() != "obj" {
-            get_files_time_elapsed_since_changed(
-                elapsed_times,
-                entry.as_ref().unwrap().path().as_path(),
-            )
-            .unwrap();
+    }
+    Ok(())
+}
+
+/// Writes the results of every run to the output file, and prints a summary of each run, see [`write_report`]
+#[derive(Debug)]
+struct OutputFileReporter(Mutex<File>);
+
+impl Reporter for OutputFileReporter {
+    fn on_run_finish(&self, report: &RunReport) {
+        let mut output_file = self.0.lock().unwrap();
+        if let Err(err) = write_report(&mut output_file, report) {
+            error!("The results of the run could not be written to the output file: {}", err);
         }
     }
-    // info!("Recursive directory info: {:#?}", elapsed_times.clone());
+}
+
+/// Writes the results of a run to the output file, and prints a summary of the run
+fn write_report(output_file: &mut File, report: &RunReport) -> std::io::Result<()> {
+    output_file.write_fmt(format_args!("{:#?}\n", report.by_pipeline()))?;
+    for line in report.summary() {
+        println!("{}", line);
+        writeln!(output_file, "{}", line)?;
+    }
     Ok(())
 }
 
@@ -152,12 +878,12 @@ fn get_files_time_elapsed_since_changed<'a>(
  * /*!TODO: Allow multiple verbosity options to be input by users. */
  * /*!TODO: Allow for custom file pathing for logs. */
  */
-fn setup_logger() -> std::io::Result<()> {
-    fs::create_dir_all("dist/logs")?;
-    fs::create_dir_all("dist/cider")?;
-    fs::create_dir_all("dist/output")?;
-    fs::create_dir_all("metrics/win")?;
-    fs::create_dir_all("metrics/combined_reports")?;
+fn setup_logger(color: ColorChoice, paths: &OutputPaths) -> std::io::Result<()> {
+    fs::create_dir_all(&paths.logs)?;
+    fs::create_dir_all(paths.results.as_deref().unwrap_or("dist/cider"))?;
+    fs::create_dir_all(&paths.config_dump)?;
+    fs::create_dir_all(Path::new(&paths.metrics).join("win"))?;
+    fs::create_dir_all(Path::new(&paths.metrics).join("combined_reports"))?;
     // fs::create_dir_all("metrics/deb")?;
     // fs::create_dir_all("metrics/rhel")?;
 
@@ -166,38 +892,134 @@ fn setup_logger() -> std::io::Result<()> {
             LevelFilter::Warn,
             Config::default(),
             TerminalMode::Mixed,
-            ColorChoice::Auto,
+            color,
         ),
         WriteLogger::new(
             LevelFilter::max(),
             Config::default(),
-            File::create(curate_filepath("dist/logs/", "verbose_runtime_log.txt")).unwrap(),
+            File::create(curate_filepath(&paths.logs, "verbose_runtime_log.txt")).unwrap(),
         ),
         WriteLogger::new(
             LevelFilter::Trace,
             Config::default(),
-            File::create(curate_filepath("dist/logs/", "trace_runtime_log.txt")).unwrap(),
+            File::create(curate_filepath(&paths.logs, "trace_runtime_log.txt")).unwrap(),
         ),
         WriteLogger::new(
             LevelFilter::Error,
             Config::default(),
-            File::create(curate_filepath("dist/logs/", "error_runtime_log.txt")).unwrap(),
+            File::create(curate_filepath(&paths.logs, "error_runtime_log.txt")).unwrap(),
         ),
         WriteLogger::new(
             LevelFilter::Warn,
             Config::default(),
-            File::create(curate_filepath("dist/logs/", "warn_runtime_log.txt")).unwrap(),
+            File::create(curate_filepath(&paths.logs, "warn_runtime_log.txt")).unwrap(),
         ),
         WriteLogger::new(
             LevelFilter::Info,
             Config::default(),
-            File::create(curate_filepath("dist/logs/", "info_runtime_log.txt")).unwrap(),
+            File::create(curate_filepath(&paths.logs, "info_runtime_log.txt")).unwrap(),
         ),
     ])
     .unwrap();
     Ok(())
 }
 
+/// Where CIder writes its logs, results, docker metrics, and the dump of the parsed configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OutputPaths {
+    logs: String,
+    /// None writes results to the output directory of the configuration
+    results: Option<String>,
+    config_dump: String,
+    metrics: String,
+}
+
+impl OutputPaths {
+    /// Returns the paths used when everything is written beneath `root`, or the default paths if there is no root
+    fn new(root: Option<&str>) -> Self {
+        match root {
+            Some(root) => {
+                let within_root = |dir: &str| Path::new(root).join(dir).to_string_lossy().into_owned();
+                OutputPaths {
+                    logs: within_root("logs"),
+                    results: Some(within_root("cider")),
+                    config_dump: within_root("output"),
+                    metrics: within_root("metrics"),
+                }
+            }
+            None => OutputPaths {
+                logs: "dist/logs".to_string(),
+                results: None,
+                config_dump: "./dist/output/".to_string(),
+                metrics: "metrics".to_string(),
+            },
+        }
+    }
+
+    /// Returns every directory CIder writes to
+    fn dirs(&self) -> Vec<&Path> {
+        vec![
+            Path::new(&self.logs),
+            Path::new(self.results.as_deref().unwrap_or("dist/cider")),
+            Path::new(&self.config_dump),
+            Path::new(&self.metrics),
+        ]
+    }
+}
+
+/// Creates `filename` within the directory `path`, creating the directory and any of its parents if they are missing
+fn create_output_file(path: &str, filename: &str) -> std::io::Result<File> {
+    fs::create_dir_all(path)?;
+    File::create(curate_filepath(path, filename))
+}
+
+/// Starts writing every event sent through the returned [`EventSender`] to the file at `path`, one JSON object per line
+///
+/// Each event is flushed as soon as it is written, so that the file can be followed while the run progresses. The
+/// returned thread finishes once every [`EventSender`] has been dropped.
+fn spawn_event_writer(
+    path: &Path,
+) -> std::io::Result<(EventSender, thread::JoinHandle<std::io::Result<()>>)> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = File::create(path)?;
+    let (events, receiver) = EventSender::channel();
+    let writer = thread::spawn(move || {
+        for event in receiver {
+            writeln!(file, "{}", event.to_ndjson())?;
+            file.flush()?;
+        }
+        Ok(())
+    });
+    Ok((events, writer))
+}
+
+/// Parses a duration given on the command line as a whole number of milliseconds above zero
+fn parse_millis(value: &str) -> Result<time::Duration, String> {
+    match value.trim().parse::<u64>() {
+        Ok(millis) if millis > 0 => Ok(time::Duration::from_millis(millis)),
+        _ => Err(format!("\"{}\" is not a valid number of milliseconds, expected a whole number above 0", value)),
+    }
+}
+
+/// Parses a duration given on the command line, as a number of seconds with an optional `s`, `m`, or `h` unit
+fn parse_duration(value: &str) -> Result<time::Duration, String> {
+    let value = value.trim();
+    let (amount, unit_seconds) = match value.chars().last() {
+        Some('s') => (&value[..value.len() - 1], 1),
+        Some('m') => (&value[..value.len() - 1], 60),
+        Some('h') => (&value[..value.len() - 1], 60 * 60),
+        _ => (value, 1),
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(unit_seconds))
+        .map(time::Duration::from_secs)
+        .ok_or_else(|| format!("\"{}\" is not a valid duration, expected a value like 30s, 5m or 1h", value))
+}
+
 /**
  * Curates filepaths to properly be able to link to files in a user-friendly way
  * Example: path/nested_dir -> path/nested_dir/
@@ -233,6 +1055,44 @@ fn curate_filepath(path: &str, filename: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_completions_cover_every_flag() {
+        for shell in [
+            completions::Shell::Bash,
+            completions::Shell::Zsh,
+            completions::Shell::Fish,
+            completions::Shell::Powershell,
+        ] {
+            let script = completions::generate(shell, Arguments::command());
+            for arg in Arguments::command().get_arguments() {
+                let long = arg.get_long().unwrap();
+                assert!(script.contains(long), "{:?} completions are missing --{}", shell, long);
+            }
+            assert!(script.contains("auto always never") || script.contains("'auto', 'always', 'never'"));
+        }
+    }
+
+    #[test]
+    fn test_discover_config_from_a_nested_directory() {
+        let root = std::env::temp_dir().join(format!("cider_discover_{}", std::process::id()));
+        let project = root.join("project");
+        let nested = project.join("src/utils");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(DEFAULT_CONFIG), "{}").unwrap();
+        fs::write(project.join(DEFAULT_CONFIG), "{}").unwrap();
+        let project = project.canonicalize().unwrap();
+
+        assert_eq!(discover_config(&nested, None), Some(project.join(DEFAULT_CONFIG)));
+        assert_eq!(discover_config(&project, None), Some(project.join(DEFAULT_CONFIG)));
+        fs::remove_file(project.join(DEFAULT_CONFIG)).unwrap();
+        let found = discover_config(&nested, None);
+        assert_eq!(found, Some(root.canonicalize().unwrap().join(DEFAULT_CONFIG)));
+        // The search stops at the boundary, even though a parent of it holds a configuration
+        assert_eq!(discover_config(&nested, Some(&project)), None);
+        assert_eq!(discover_config(&nested, Some(&nested)), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
     #[test]
     fn test_filepath_curation() {
         if cfg!(windows) {
@@ -248,6 +1108,198 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(time::Duration::from_secs(45)));
+        assert_eq!(parse_duration("30s"), Ok(time::Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(time::Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(time::Duration::from_secs(3600)));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_watch_timing_flags() {
+        let args = Arguments::parse_from(["cider", "--watch"]);
+        assert_eq!(args.watch_interval, time::Duration::from_millis(2000));
+        assert_eq!(args.watch_debounce, time::Duration::from_millis(500));
+        let args =
+            Arguments::parse_from(["cider", "--watch-interval", "250", "--watch-debounce", "50"]);
+        assert_eq!(args.watch_interval, time::Duration::from_millis(250));
+        assert_eq!(args.watch_debounce, time::Duration::from_millis(50));
+        assert!(Arguments::try_parse_from(["cider", "--watch-interval", "0"]).is_err());
+        assert!(Arguments::try_parse_from(["cider", "--watch-debounce", "-5"]).is_err());
+    }
+
+    #[test]
+    fn test_color_flag() {
+        let args = Arguments::parse_from(["cider", "--color", "never"]);
+        assert_eq!(ColorChoice::from(args.color), ColorChoice::Never);
+        let args = Arguments::parse_from(["cider"]);
+        assert_eq!(ColorChoice::from(args.color), ColorChoice::Auto);
+    }
+
+    #[test]
+    fn test_output_file_in_new_nested_directory() {
+        let output_dir = std::env::temp_dir().join(format!("cider_test_{}", std::process::id()));
+        let nested_dir = output_dir.join("nested").join("output");
+        let mut file = create_output_file(nested_dir.to_str().unwrap(), "cider_output.txt").unwrap();
+        writeln!(file, "run passed").unwrap();
+        assert!(nested_dir.join("cider_output.txt").is_file());
+        fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_output_writes_a_file_per_action() {
+        use cider::results::{ActionResult, Status};
+
+        let dir = std::env::temp_dir().join(format!("cider_split_output_{}", std::process::id()));
+        let result = |title: &str, output: &str| {
+            ActionResult::new(&Action::builder(title).build(), vec![output.to_string()], Status::Success)
+        };
+        let report = RunReport {
+            actions: vec![result("Build", "built\n"), result("Build", "built again")],
+            ..Default::default()
+        };
+        let inherited = Action::builder("Build").build().shared_config.get_output().to_string();
+        write_action_logs(&dir, &inherited, &report).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("Build.log")).unwrap(), "built\n");
+        assert_eq!(fs::read_to_string(dir.join("Build-2.log")).unwrap(), "built again\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_output_honors_the_output_of_each_action() {
+        use cider::results::{ActionResult, Status};
+
+        let dir = std::env::temp_dir().join(format!("cider_action_output_{}", std::process::id()));
+        let own = dir.join("own");
+        let conf = json_parser::parse_from_str(&format!(
+            r#"{{
+                "output_directory": "{}",
+                "actions": ["Build", "Package"],
+                "Build": {{ "manual": {{ "build": "echo built" }} }},
+                "Package": {{ "output_directory": "{}", "manual": {{ "package": "echo packaged" }} }}
+            }}"#,
+            dir.join("shared").display(),
+            own.display()
+        ));
+        let result = |action: &Action, output: &str| {
+            ActionResult::new(action, vec![output.to_string()], Status::Success)
+        };
+        let actions = conf.get_actions();
+        let report = RunReport {
+            actions: vec![result(&actions[0], "built\n"), result(&actions[1], "packaged\n")],
+            ..Default::default()
+        };
+        let shared = dir.join("shared");
+        write_action_logs(&shared, conf.s_config.get_output(), &report).unwrap();
+        assert_eq!(fs::read_to_string(shared.join("Build.log")).unwrap(), "built\n");
+        assert_eq!(fs::read_to_string(own.join("Package.log")).unwrap(), "packaged\n");
+        assert!(!shared.join("Package.log").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_output_honors_the_output_of_each_pipeline() {
+        use cider::results::{ActionResult, Status};
+
+        let dir = std::env::temp_dir().join(format!("cider_pipeline_logs_{}", std::process::id()));
+        let release = dir.join("release");
+        let conf = json_parser::parse_from_str(&format!(
+            r#"{{
+                "output_directory": "{}",
+                "pipelines": ["Release"],
+                "Release": {{
+                    "output_directory": "{}",
+                    "actions": ["Package"],
+                    "Package": {{ "manual": {{ "package": "echo packaged" }} }}
+                }}
+            }}"#,
+            dir.join("shared").display(),
+            release.display()
+        ));
+        let package = &conf.get_pipelines()[0].pipeline_config.get_actions()[0];
+        let report = RunReport {
+            actions: vec![ActionResult::new(
+                package,
+                vec!["packaged\n".to_string()],
+                Status::Success,
+            )],
+            ..Default::default()
+        };
+        let shared = dir.join("shared");
+        write_action_logs(&shared, conf.s_config.get_output(), &report).unwrap();
+        assert_eq!(fs::read_to_string(release.join("Package.log")).unwrap(), "packaged\n");
+        assert!(!shared.join("Package.log").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_steps() {
+        let conf = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "image": "rust:latest",
+                "actions": ["Build"],
+                "pipelines": ["Release"],
+                "Build": {
+                    "docker_workdir": "/workspace",
+                    "manual": { "build": "cargo build", "test": { "script": "cargo test", "stdin": "y" } }
+                },
+                "Release": { "actions": ["Build"], "Build": { "manual": { "zip": "zip out" } } }
+            }"#,
+        );
+        assert!(list_steps(&conf, "Build"));
+        assert!(!list_steps(&conf, "Deploy"));
+        assert_eq!(
+            describe_steps(conf.get_action("Build").unwrap()),
+            [
+                "Build:",
+                "  1. build",
+                "     script:      cargo build",
+                "     interpreter: /bin/sh -c, within rust:latest",
+                "     working dir: /workspace",
+                "  2. test",
+                "     script:      cargo test",
+                "     interpreter: /bin/sh -c, within rust:latest",
+                "     working dir: /workspace",
+                "     stdin:       y",
+            ]
+        );
+        let packaged = conf.get_all_actions().pop().unwrap();
+        assert_eq!(describe_steps(&packaged)[0], "Build (pipeline Release):");
+    }
+
+    #[test]
+    fn test_print_dockerfiles_needs_a_docker_action() {
+        let conf = json_parser::parse_from_str(
+            r#"{
+                "actions": ["Build", "Image"],
+                "Build": { "manual": { "build": "cargo build" } },
+                "Image": {
+                    "backend": "docker",
+                    "image": "rust:latest",
+                    "manual": { "build": "cargo build" }
+                }
+            }"#,
+        );
+        assert!(print_dockerfiles(&conf, ""));
+        assert!(print_dockerfiles(&conf, "Image"));
+        assert!(!print_dockerfiles(&conf, "Build"));
+        assert!(!print_dockerfiles(&conf, "Deploy"));
+    }
+
+    #[test]
+    fn test_output_dir_holds_every_output() {
+        let paths = OutputPaths::new(Some("out"));
+        let root = Path::new("out");
+        assert_eq!(Path::new(&paths.logs), root.join("logs"));
+        assert_eq!(paths.results.as_deref().map(Path::new), Some(root.join("cider").as_path()));
+        assert_eq!(Path::new(&paths.config_dump), root.join("output"));
+        assert_eq!(Path::new(&paths.metrics), root.join("metrics"));
+        assert_eq!(OutputPaths::new(None).results, None);
+    }
+
     ///This test intends to ensure that proper filepath endings are implemented on the proper operating systems.
     /**For example, test/ on linux should not become test*/
     #[test]
@@ -0,0 +1,237 @@
+use crate::utils::ignore::{self, IgnorePattern};
+use log::warn;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// The size of each block of a tar archive
+const BLOCK_SIZE: usize = 512;
+
+/// Files that docker always sends with the build context, even if the `.dockerignore` excludes them
+const ALWAYS_SENT: &[&str] = &["Dockerfile", ".dockerignore"];
+
+/// Writes the build context within `source` as a tar archive to the file at `path`, leaving out anything the
+/// `.dockerignore` of `source` excludes
+///
+/// See [`write_context_tar`] for how the archive is written.
+pub fn create_context_tar(source: &Path, path: &Path) -> io::Result<()> {
+    let patterns = ignore::read_dockerignore(source)?;
+    let mut file = BufWriter::new(File::create(path)?);
+    write_context_tar(source, &patterns, &mut file)?;
+    file.flush()
+}
+
+/// Writes the build context within `source` as a tar archive to `out`, leaving out anything matching `patterns`
+///
+/// Entries are written in the order of their names, so the same context always produces the same archive. The
+/// `Dockerfile` and `.dockerignore` at the root of the context are always written. Paths that do not fit within a tar
+/// header are written with GNU long name records, which docker understands.
+pub fn write_context_tar(
+    source: &Path,
+    patterns: &[IgnorePattern],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    write_dir(source, "", patterns, out)?;
+    out.write_all(&[0; BLOCK_SIZE * 2])
+}
+
+/// Writes every entry within the directory `dir`, whose path within the context is `relative`
+fn write_dir(
+    dir: &Path,
+    relative: &str,
+    patterns: &[IgnorePattern],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let file_type = entry.file_type()?;
+        let always_sent = relative.is_empty() && ALWAYS_SENT.contains(&name.as_str());
+        if !always_sent && ignore::is_ignored(patterns, &path, file_type.is_dir()) {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+        if file_type.is_dir() {
+            write_header(out, &format!("{}/", path), b'5', 0, mode(&metadata, 0o755), mtime, "")?;
+            write_dir(&entry.path(), &path, patterns, out)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            let target = target.to_string_lossy();
+            write_header(out, &path, b'2', 0, 0o777, mtime, &target)?;
+        } else if file_type.is_file() {
+            let size = metadata.len();
+            write_header(out, &path, b'0', size, mode(&metadata, 0o644), mtime, "")?;
+            io::copy(&mut File::open(entry.path())?, out)?;
+            write_padding(out, size)?;
+        } else {
+            warn!(
+                "Leaving {} out of the docker build context, as it is not a file or directory",
+                path
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the permissions of an entry, or `default` on platforms that do not have them
+fn mode(metadata: &fs::Metadata, default: u32) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = default;
+        metadata.permissions().mode() & 0o7777
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        default
+    }
+}
+
+/// Writes the header of an entry, preceded by GNU long name records for a `name` or `link` that does not fit within it
+fn write_header(
+    out: &mut impl Write,
+    name: &str,
+    kind: u8,
+    size: u64,
+    mode: u32,
+    mtime: u64,
+    link: &str,
+) -> io::Result<()> {
+    if name.len() > 100 {
+        write_long_name(out, b'L', name)?;
+    }
+    if link.len() > 100 {
+        write_long_name(out, b'K', link)?;
+    }
+    let mut header = [0u8; BLOCK_SIZE];
+    put(&mut header[0..100], name.as_bytes());
+    put_octal(&mut header[100..108], mode as u64)?;
+    put_octal(&mut header[108..116], 0)?;
+    put_octal(&mut header[116..124], 0)?;
+    put_octal(&mut header[124..136], size)?;
+    put_octal(&mut header[136..148], mtime)?;
+    header[156] = kind;
+    put(&mut header[157..257], link.as_bytes());
+    put(&mut header[257..265], b"ustar  \0");
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|byte| *byte as u64).sum::<u64>();
+    put(&mut header[148..156], format!("{:06o}\0 ", checksum).as_bytes());
+    out.write_all(&header)
+}
+
+/// Writes a GNU long name record of `kind`, which holds the full `name` of the entry that follows it
+fn write_long_name(out: &mut impl Write, kind: u8, name: &str) -> io::Result<()> {
+    let size = name.len() as u64 + 1;
+    write_header(out, "././@LongLink", kind, size, 0o644, 0, "")?;
+    out.write_all(name.as_bytes())?;
+    out.write_all(&[0])?;
+    write_padding(out, size)
+}
+
+/// Pads an entry of `size` bytes to the end of its last block
+fn write_padding(out: &mut impl Write, size: u64) -> io::Result<()> {
+    let remainder = (size % BLOCK_SIZE as u64) as usize;
+    if remainder > 0 {
+        out.write_all(&[0; BLOCK_SIZE][remainder..])?;
+    }
+    Ok(())
+}
+
+/// Copies as much of `value` into `field` as fits, leaving the rest of the field zeroed
+fn put(field: &mut [u8], value: &[u8]) {
+    let len = value.len().min(field.len());
+    field[..len].copy_from_slice(&value[..len]);
+}
+
+/// Writes `value` into `field` as a zero-padded octal number followed by a NUL
+fn put_octal(field: &mut [u8], value: u64) -> io::Result<()> {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    if digits.len() >= field.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is too large for a tar header", value),
+        ));
+    }
+    put(field, digits.as_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    /// Returns the name of every entry within a tar archive, resolving GNU long name records
+    fn entry_names(archive: &[u8]) -> Vec<String> {
+        let mut names = vec![];
+        let mut long_name = None;
+        let mut offset = 0;
+        while offset + BLOCK_SIZE <= archive.len() && archive[offset] != 0 {
+            let header = &archive[offset..offset + BLOCK_SIZE];
+            let field = |range: std::ops::Range<usize>| {
+                let field = &header[range];
+                let end = field.iter().position(|byte| *byte == 0).unwrap_or(field.len());
+                String::from_utf8_lossy(&field[..end]).into_owned()
+            };
+            let size = u64::from_str_radix(&field(124..135), 8).unwrap() as usize;
+            let data = &archive[offset + BLOCK_SIZE..offset + BLOCK_SIZE + size];
+            if header[156] == b'L' {
+                long_name = Some(String::from_utf8_lossy(&data[..size - 1]).into_owned());
+            } else {
+                names.push(long_name.take().unwrap_or_else(|| field(0..100)));
+            }
+            offset += BLOCK_SIZE + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+        }
+        names
+    }
+
+    #[test]
+    fn test_context_tar_excludes_ignored_entries() {
+        let dir = env::temp_dir().join(format!("cider_context_{}", process::id()));
+        let long = "a".repeat(120);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("target/debug")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/left-pad")).unwrap();
+        fs::write(dir.join("Dockerfile"), "FROM rust:latest\r\n").unwrap();
+        let dockerignore = "target\n**/node_modules\nDockerfile\n*.log\n";
+        fs::write(dir.join(".dockerignore"), dockerignore).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("src").join(&long), "").unwrap();
+        fs::write(dir.join("src/build.log"), "").unwrap();
+        fs::write(dir.join("build.log"), "").unwrap();
+        fs::write(dir.join("target/debug/cider"), "").unwrap();
+        fs::write(dir.join("node_modules/left-pad/index.js"), "").unwrap();
+
+        let tar = env::temp_dir().join(format!("cider_context_{}.tar", process::id()));
+        create_context_tar(&dir, &tar).unwrap();
+        let archive = fs::read(&tar).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&tar).unwrap();
+
+        assert_eq!(archive.len() % BLOCK_SIZE, 0);
+        assert_eq!(
+            entry_names(&archive),
+            [
+                ".dockerignore".to_string(),
+                "Dockerfile".to_string(),
+                "src/".to_string(),
+                format!("src/{}", long),
+                "src/build.log".to_string(),
+                "src/main.rs".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use clap::{Arg, Command, ValueEnum};
+
+/// A shell that completion scripts can be generated for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// Bash, to be sourced from `.bashrc` or placed within `bash-completion/completions`
+    Bash,
+    /// Zsh, to be placed within a directory on the `fpath` as `_cider`
+    Zsh,
+    /// Fish, to be placed within `~/.config/fish/completions`
+    Fish,
+    /// PowerShell, to be sourced from the PowerShell profile
+    Powershell,
+}
+
+/// A flag as it is offered for completion
+struct Flag {
+    short: Option<char>,
+    long: String,
+    help: String,
+    takes_value: bool,
+    values: Vec<String>,
+}
+
+impl Flag {
+    /// Returns every spelling of the flag, such as `-c` and `--config`
+    fn spellings(&self) -> Vec<String> {
+        let mut spellings = vec![];
+        if let Some(short) = self.short {
+            spellings.push(format!("-{}", short));
+        }
+        spellings.push(format!("--{}", self.long));
+        spellings
+    }
+}
+
+/// Returns the completion script for `shell`, covering every visible flag of `command`
+pub fn generate(shell: Shell, mut command: Command) -> String {
+    command.build();
+    let name = command
+        .get_bin_name()
+        .unwrap_or(command.get_name())
+        .to_string();
+    let flags = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .filter_map(flag)
+        .collect::<Vec<_>>();
+    match shell {
+        Shell::Bash => bash(&name, &flags),
+        Shell::Zsh => zsh(&name, &flags),
+        Shell::Fish => fish(&name, &flags),
+        Shell::Powershell => powershell(&name, &flags),
+    }
+}
+
+/// Describes `arg` for completion, if it can be given by name
+fn flag(arg: &Arg) -> Option<Flag> {
+    Some(Flag {
+        short: arg.get_short(),
+        long: arg.get_long()?.to_string(),
+        help: arg
+            .get_help()
+            .map(|help| help.to_string().lines().next().unwrap_or_default().to_string())
+            .unwrap_or_default(),
+        takes_value: arg.get_action().takes_values(),
+        values: arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect(),
+    })
+}
+
+/// Returns the Bash completion script for the command `name`
+fn bash(name: &str, flags: &[Flag]) -> String {
+    let function = format!("_{}", name.replace('-', "_"));
+    let mut script = format!(
+        "{}() {{\n    local cur prev\n    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    case \"${{prev}}\" in\n",
+        function
+    );
+    for flag in flags.iter().filter(|flag| flag.takes_value) {
+        let words = if flag.values.is_empty() {
+            "-f".to_string()
+        } else {
+            format!("-W \"{}\"", flag.values.join(" "))
+        };
+        script += &format!(
+            "        {})\n            COMPREPLY=($(compgen {} -- \"${{cur}}\"))\n            return 0\n            ;;\n",
+            flag.spellings().join("|"),
+            words
+        );
+    }
+    let all = flags
+        .iter()
+        .flat_map(Flag::spellings)
+        .collect::<Vec<_>>()
+        .join(" ");
+    script += &format!(
+        "    esac\n    COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))\n}}\ncomplete -F {} -o bashdefault -o default {}\n",
+        all, function, name
+    );
+    script
+}
+
+/// Returns the Zsh completion script for the command `name`
+fn zsh(name: &str, flags: &[Flag]) -> String {
+    let escape = |help: &str| {
+        help.replace('\'', "'\\''")
+            .replace('[', "\\[")
+            .replace(']', "\\]")
+            .replace(':', "\\:")
+    };
+    let mut script = format!("#compdef {}\n\n_{}() {{\n    _arguments -s \\\n", name, name);
+    for flag in flags {
+        let value = match (flag.takes_value, flag.values.is_empty()) {
+            (false, _) => String::new(),
+            (true, true) => format!(":{}:_files", flag.long),
+            (true, false) => format!(":{}:({})", flag.long, flag.values.join(" ")),
+        };
+        for spelling in flag.spellings() {
+            script += &format!("        '{}[{}]{}' \\\n", spelling, escape(&flag.help), value);
+        }
+    }
+    script += &format!("        && return 0\n}}\n\n_{} \"$@\"\n", name);
+    script
+}
+
+/// Returns the Fish completion script for the command `name`
+fn fish(name: &str, flags: &[Flag]) -> String {
+    let mut script = String::new();
+    for flag in flags {
+        script += &format!("complete -c {}", name);
+        if let Some(short) = flag.short {
+            script += &format!(" -s {}", short);
+        }
+        script += &format!(" -l {} -d '{}'", flag.long, flag.help.replace('\'', "\\'"));
+        if flag.takes_value {
+            if flag.values.is_empty() {
+                script += " -r -F";
+            } else {
+                script += &format!(" -r -f -a \"{}\"", flag.values.join(" "));
+            }
+        }
+        script += "\n";
+    }
+    script
+}
+
+/// Returns the PowerShell completion script for the command `name`
+fn powershell(name: &str, flags: &[Flag]) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let mut script = format!(
+        "using namespace System.Management.Automation\n\nRegister-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{\n    param($wordToComplete, $commandAst, $cursorPosition)\n    $previous = $commandAst.CommandElements | Where-Object {{ $_.Extent.EndOffset -lt $cursorPosition }} | Select-Object -Last 1\n    $values = switch ($previous.ToString()) {{\n",
+        quote(name)
+    );
+    for flag in flags.iter().filter(|flag| !flag.values.is_empty()) {
+        let values = flag
+            .values
+            .iter()
+            .map(|value| quote(value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        for spelling in flag.spellings() {
+            script += &format!("        {} {{ @({}) }}\n", quote(&spelling), values);
+        }
+    }
+    script += "    }\n    if ($values) {\n        $completions = $values | ForEach-Object { [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }\n    } else {\n        $completions = @(\n";
+    for flag in flags {
+        let help = if flag.help.is_empty() { &flag.long } else { &flag.help };
+        for spelling in flag.spellings() {
+            script += &format!(
+                "            [CompletionResult]::new({}, {}, [CompletionResultType]::ParameterName, {})\n",
+                quote(&spelling),
+                quote(&flag.long),
+                quote(help)
+            );
+        }
+    }
+    script += "        )\n    }\n    $completions.Where{ $_.CompletionText -like \"$wordToComplete*\" }\n}\n";
+    script
+}
@@ -0,0 +1,345 @@
+use crate::utils::config::{Condition, ConditionMode};
+use crate::utils::results::StepOutputs;
+use log::{info, warn};
+use std::env;
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A check that a [`Condition`] performs in order to resolve to either true or false
+///
+/// Conditions are written as `kind:argument`, where `kind` is one of the built-in predicates below. Any condition that
+/// does not start with a built-in predicate is run as a shell command instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// `true` or `false`, regardless of case
+    Literal(bool),
+    /// `os:<name>`, true when CIder is running on the named operating system or operating system family, such as
+    /// `linux`, `macos`, `windows`, or `unix`
+    Os(String),
+    /// `env:<VAR>` is true when the environment variable `VAR` is set and not empty, while `env:<VAR>=<value>` is true
+    /// when `VAR` is set to exactly `value`
+    Env(String, Option<String>),
+    /// `file_exists:<path>`, true when something exists at `path`, resolved against the directory CIder is run from
+    FileExists(String),
+    /// `cmd:<program>`, true when `program` can be found on the `PATH`
+    Cmd(String),
+    /// `output_contains:<step>:<text>`, true when the standard output of the step named `step`, which must already have
+    /// run, contains `text`. The text may be wrapped in double quotes
+    OutputContains(String, String),
+    /// Any other condition, true when it runs as a shell command that exits successfully
+    Shell(String),
+}
+
+impl Predicate {
+    /// Parses the [`Predicate`] described by a condition
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::conditions::Predicate;
+    ///
+    /// assert_eq!(Predicate::parse("os:linux"), Predicate::Os("linux".to_string()));
+    /// assert_eq!(Predicate::parse("test -f Cargo.toml"), Predicate::Shell("test -f Cargo.toml".to_string()));
+    /// ```
+    pub fn parse(condition: &str) -> Self {
+        let condition = condition.trim();
+        if condition.eq_ignore_ascii_case("true") {
+            return Predicate::Literal(true);
+        }
+        if condition.eq_ignore_ascii_case("false") {
+            return Predicate::Literal(false);
+        }
+        match condition.split_once(':') {
+            Some(("os", name)) => Predicate::Os(name.trim().to_lowercase()),
+            Some(("env", variable)) => match variable.split_once('=') {
+                Some((name, value)) => {
+                    Predicate::Env(name.trim().to_string(), Some(value.to_string()))
+                }
+                None => Predicate::Env(variable.trim().to_string(), None),
+            },
+            Some(("file_exists", path)) => Predicate::FileExists(path.trim().to_string()),
+            Some(("cmd", program)) => Predicate::Cmd(program.trim().to_string()),
+            Some(("output_contains", query)) => {
+                let (step, text) = query.split_once(':').unwrap_or((query, ""));
+                let text = text.trim();
+                let text = text
+                    .strip_prefix('"')
+                    .and_then(|text| text.strip_suffix('"'))
+                    .unwrap_or(text);
+                Predicate::OutputContains(step.trim().to_string(), text.to_string())
+            }
+            _ => Predicate::Shell(condition.to_string()),
+        }
+    }
+
+    /// Resolves the [`Predicate`] against the device CIder is running on
+    ///
+    /// No step has run as far as this is concerned, see [`Predicate::evaluate_with`] to check the output of steps.
+    pub fn evaluate(&self) -> bool {
+        self.evaluate_with(&StepOutputs::default())
+    }
+
+    /// Resolves the [`Predicate`] against the device CIder is running on and the steps that have run so far
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::conditions::Predicate;
+    /// use cider::results::StepOutputs;
+    ///
+    /// let outputs = StepOutputs::default();
+    /// outputs.record("test", "test result: ok. 12 passed");
+    ///
+    /// assert!(Predicate::parse("output_contains:test:\"test result: ok\"").evaluate_with(&outputs));
+    /// ```
+    pub fn evaluate_with(&self, outputs: &StepOutputs) -> bool {
+        match self {
+            Predicate::Literal(value) => *value,
+            Predicate::Os(name) => name == env::consts::OS || name == env::consts::FAMILY,
+            Predicate::Env(name, None) => env::var_os(name).is_some_and(|value| !value.is_empty()),
+            Predicate::Env(name, Some(expected)) => {
+                env::var(name).is_ok_and(|value| &value == expected)
+            }
+            Predicate::FileExists(path) => Path::new(path).exists(),
+            Predicate::Cmd(program) => find_on_path(program),
+            Predicate::OutputContains(step, text) => match outputs.get(step) {
+                Some(output) => output.contains(text.as_str()),
+                None => {
+                    warn!(
+                        "The condition \"{}\" is false, as no step named \"{}\" has run",
+                        self, step
+                    );
+                    false
+                }
+            },
+            Predicate::Shell(script) => {
+                let mut command = if cfg!(windows) {
+                    let mut command = Command::new("cmd");
+                    command.arg("/C");
+                    command
+                } else {
+                    let mut command = Command::new("sh");
+                    command.arg("-c");
+                    command
+                };
+                command
+                    .arg(script)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .map(|status| status.success())
+                    .unwrap_or_else(|err| {
+                        warn!("The condition \"{}\" could not be run: {}", script, err);
+                        false
+                    })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Predicate::Literal(value) => write!(f, "{}", value),
+            Predicate::Os(name) => write!(f, "os:{}", name),
+            Predicate::Env(name, None) => write!(f, "env:{}", name),
+            Predicate::Env(name, Some(value)) => write!(f, "env:{}={}", name, value),
+            Predicate::FileExists(path) => write!(f, "file_exists:{}", path),
+            Predicate::Cmd(program) => write!(f, "cmd:{}", program),
+            Predicate::OutputContains(step, text) => {
+                write!(f, "output_contains:{}:\"{}\"", step, text)
+            }
+            Predicate::Shell(script) => write!(f, "{}", script),
+        }
+    }
+}
+
+/// The result of resolving a single [`Condition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionOutcome {
+    /// The name of the condition
+    pub name: String,
+    /// The check the condition performed
+    pub predicate: Predicate,
+    /// Whether the condition was met
+    pub met: bool,
+    /// What happens when the condition is not met
+    pub mode: ConditionMode,
+}
+
+impl ConditionOutcome {
+    /// Returns whether the outcome skips the action, which is the case when a condition that blocks was not met
+    pub fn blocks(&self) -> bool {
+        !self.met && self.mode == ConditionMode::Block
+    }
+}
+
+impl fmt::Display for ConditionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "condition \"{}\" ({}) was {}",
+            self.name,
+            self.predicate,
+            if self.met { "met" } else { "not met" }
+        )?;
+        if !self.met && self.mode == ConditionMode::Warn {
+            write!(f, ", which only warns")?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolves a [`Condition`] against the steps that have run so far, returning why it was or was not met
+pub fn evaluate(condition: &Condition, outputs: &StepOutputs) -> ConditionOutcome {
+    let predicate = Predicate::parse(condition.get_condition());
+    let met = predicate.evaluate_with(outputs);
+    info!(
+        "Condition {} ({}) evaluated to {}",
+        condition.get_name(),
+        predicate,
+        met
+    );
+    let outcome = ConditionOutcome {
+        name: condition.get_name().to_string(),
+        predicate,
+        met,
+        mode: condition.get_mode(),
+    };
+    if !met && outcome.mode == ConditionMode::Warn {
+        warn!("The {}, running anyway", outcome);
+    }
+    outcome
+}
+
+/// Resolves `conditions` in order, stopping at the first that is not met and blocks
+///
+/// The action should be skipped if the last outcome returned [`ConditionOutcome::blocks`]. Conditions in the
+/// [`ConditionMode::Warn`] mode only log a warning when they are not met.
+pub fn evaluate_all(conditions: &[Condition], outputs: &StepOutputs) -> Vec<ConditionOutcome> {
+    let mut outcomes = vec![];
+    for condition in conditions {
+        let outcome = evaluate(condition, outputs);
+        let blocks = outcome.blocks();
+        outcomes.push(outcome);
+        if blocks {
+            break;
+        }
+    }
+    outcomes
+}
+
+/// Returns whether `program` can be found within one of the directories on the `PATH`
+pub(crate) fn find_on_path(program: &str) -> bool {
+    let candidates = if cfg!(windows) {
+        vec![program.to_string(), format!("{}.exe", program)]
+    } else {
+        vec![program.to_string()]
+    };
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| {
+            candidates
+                .iter()
+                .any(|candidate| dir.join(candidate).is_file())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_predicates() {
+        assert_eq!(Predicate::parse("False"), Predicate::Literal(false));
+        assert_eq!(
+            Predicate::parse("env:CI=true"),
+            Predicate::Env("CI".to_string(), Some("true".to_string()))
+        );
+        assert_eq!(
+            Predicate::parse("file_exists: Cargo.toml"),
+            Predicate::FileExists("Cargo.toml".to_string())
+        );
+        assert_eq!(Predicate::parse("cmd:sh"), Predicate::Cmd("sh".to_string()));
+        assert_eq!(
+            Predicate::parse("echo a:b"),
+            Predicate::Shell("echo a:b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_evaluate_all_stops_at_first_unmet() {
+        let conditions = vec![
+            Condition::new("Always".to_string(), "true".to_string()),
+            Condition::new("Never".to_string(), "false".to_string()),
+            Condition::new("Unreached".to_string(), "true".to_string()),
+        ];
+        let outcomes = evaluate_all(&conditions, &StepOutputs::default());
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].to_string(), "condition \"Never\" (false) was not met");
+    }
+
+    #[test]
+    fn test_condition_modes() {
+        let mut warning = Condition::new("Warns".to_string(), "false".to_string());
+        warning.set_mode(ConditionMode::Warn);
+        let conditions = vec![
+            warning,
+            Condition::new("Always".to_string(), "true".to_string()),
+            Condition::new("Blocks".to_string(), "false".to_string()),
+        ];
+        let outcomes = evaluate_all(&conditions, &StepOutputs::default());
+        assert_eq!(
+            outcomes.iter().map(ConditionOutcome::blocks).collect::<Vec<_>>(),
+            [false, false, true]
+        );
+        assert_eq!(
+            outcomes[0].to_string(),
+            "condition \"Warns\" (false) was not met, which only warns"
+        );
+        assert_eq!(outcomes[2].to_string(), "condition \"Blocks\" (false) was not met");
+        assert!(!evaluate_all(&conditions[..2], &StepOutputs::default()).last().unwrap().blocks());
+    }
+
+    #[test]
+    fn test_evaluate_builtin_predicates() {
+        assert!(Predicate::parse(&format!("os:{}", env::consts::OS)).evaluate());
+        assert!(!Predicate::parse("os:plan9").evaluate());
+
+        env::set_var("CIDER_TEST_CONDITION", "yes");
+        assert!(Predicate::parse("env:CIDER_TEST_CONDITION").evaluate());
+        assert!(Predicate::parse("env:CIDER_TEST_CONDITION=yes").evaluate());
+        assert!(!Predicate::parse("env:CIDER_TEST_CONDITION=no").evaluate());
+        assert!(!Predicate::parse("env:CIDER_TEST_UNSET_CONDITION").evaluate());
+
+        assert!(Predicate::parse("file_exists:Cargo.toml").evaluate());
+        assert!(!Predicate::parse("file_exists:does_not_exist.toml").evaluate());
+    }
+
+    #[test]
+    fn test_output_contains() {
+        assert_eq!(
+            Predicate::parse("output_contains:build:\"Compiling\""),
+            Predicate::OutputContains("build".to_string(), "Compiling".to_string())
+        );
+        assert_eq!(
+            Predicate::parse("output_contains: test :test result: ok").to_string(),
+            "output_contains:test:\"test result: ok\""
+        );
+
+        let outputs = StepOutputs::default();
+        outputs.record("build", "   Compiling cider v0.1.0\n    Finished dev");
+        assert!(Predicate::parse("output_contains:build:\"Compiling\"").evaluate_with(&outputs));
+        assert!(!Predicate::parse("output_contains:build:error").evaluate_with(&outputs));
+        assert!(!Predicate::parse("output_contains:deploy:Compiling").evaluate_with(&outputs));
+        assert!(!Predicate::parse("output_contains:build:Compiling").evaluate());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_evaluate_commands() {
+        assert!(Predicate::parse("cmd:sh").evaluate());
+        assert!(!Predicate::parse("cmd:cider-test-missing-program").evaluate());
+        assert!(Predicate::parse("test -f Cargo.toml").evaluate());
+        assert!(!Predicate::parse("exit 1").evaluate());
+    }
+}
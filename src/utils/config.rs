@@ -1,5 +1,9 @@
-use log::{info, warn};
+use crate::utils::parsing::json_parser;
+use json::JsonValue;
+use log::{debug, info, warn};
 use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
 
 /// Contains information that can be shared between levels of a configuration
 ///
@@ -13,7 +17,6 @@ use std::collections::HashMap;
 /// Data held within [`ShareableConfiguration`]s become the default data held within more granular configurations, like [`Pipeline`]s and [`Action`]s.
 /// Inversely, the more granular configurations override any configurations set at higher levels. Therefore, a backend belonging to an [`Action`] has priority
 /// over one belonging to a [`Pipeline`] and likewise over a backend belonging to a [`ShareableConfiguration`].
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ShareableConfiguration {
     /// metadata not required
@@ -53,6 +56,34 @@ pub struct ShareableConfiguration {
     /// Source directory required
     /// defaulted to ./src
     source: String,
+
+    /// platform not required
+    /// defaulted to None, which builds images for the native platform
+    /// PLATFORM IS A DOCKER-SPECIFIC FEATURE. IF BACKEND IS NOT DOCKER, PLATFORM SHOULD NOT BE DEFINED
+    platform: Option<String>,
+
+    /// use_gitignore not required
+    /// defaulted to false
+    /// when true, the patterns of the source directory's .gitignore are left out of docker images and are not watched
+    use_gitignore: bool,
+
+    /// clean_paths not required
+    /// defaulted to true
+    /// when false, the scripts of steps are passed to the shell verbatim, without resolving their relative paths
+    clean_paths: bool,
+
+    /// metrics_dir not required
+    /// defaulted to None, which writes docker metrics to the metrics directory given on the command line
+    metrics_dir: Option<String>,
+
+    /// metrics_filename not required
+    /// defaulted to None, which names docker metrics files after the time the action started
+    metrics_filename: Option<String>,
+
+    /// redact not required
+    /// defaulted to empty
+    /// the patterns whose matches are replaced with `***` in the output of steps, including those set above this level
+    redact: Vec<String>,
 }
 
 impl ShareableConfiguration {
@@ -70,6 +101,7 @@ impl ShareableConfiguration {
     /// let s = ShareableConfiguration::new(None, None, None, "Rust".to_string(), None, "bash".to_string(), "./dist/cider".to_string(), "./src".to_string());
     /// ```
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         metadata: Option<HashMap<String, String>>,
         title: Option<String>,
@@ -96,17 +128,50 @@ impl ShareableConfiguration {
             backend,
             output,
             source,
+            platform: None,
+            use_gitignore: false,
+            clean_paths: true,
+            metrics_dir: None,
+            metrics_filename: None,
+            redact: vec![],
         }
     }
 
+    /// Returns the metadata without logging anything, see [`ShareableConfiguration::get_metadata`] for a logging version
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "metadata": { "owner": "cider" }, "actions": [] }"#);
+    ///
+    /// assert_eq!(s.s_config.metadata().unwrap()["owner"], "cider");
+    /// assert!(s.s_config.tags().is_none());
+    /// ```
+    pub fn metadata(&self) -> Option<&HashMap<String, String>> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the title without logging anything, see [`ShareableConfiguration::get_title`] for a logging version
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the tags without logging anything, see [`ShareableConfiguration::get_tags`] for a logging version
+    pub fn tags(&self) -> Option<&HashMap<String, String>> {
+        self.tags.as_ref()
+    }
+
+    /// Returns the image without logging anything, see [`ShareableConfiguration::get_image`] for a logging version
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
     /// Returns metadata
     ///
     /// Returns the metadata associated with a [`ShareableConfiguration`], and logs whether the retrieval was successful
     /// or of a None type.
     ///
-    /// # Warnings
-    /// Will provide the user with a warning if metadata obtained returns a None type.
-    ///
     /// # Examples:
     /// ```
     /// use cider::parsing::json_parser;
@@ -123,8 +188,7 @@ impl ShareableConfiguration {
                 Some(metadata.to_owned())
             }
             None => {
-                let res_str = "No metadata value found or no metadata value configured.";
-                warn!("{}", res_str);
+                debug!("No metadata value found or no metadata value configured.");
                 None
             }
         }
@@ -156,9 +220,6 @@ impl ShareableConfiguration {
     /// Returns the title associated with a [`ShareableConfiguration`], and logs whether the retrieval was successful
     /// or of a None type.
     ///
-    /// # Warnings
-    /// Will provide the user with a warning if metadata obtained returns a None type.
-    ///
     /// # Examples:
     /// ```
     /// use cider::parsing::json_parser;
@@ -175,8 +236,7 @@ impl ShareableConfiguration {
                 Some(title.to_string())
             }
             None => {
-                let res_str = "No title value found or no title value configured.";
-                warn!("{}", res_str);
+                debug!("No title value found or no title value configured.");
                 None
             }
         }
@@ -206,9 +266,6 @@ impl ShareableConfiguration {
     /// Returns the tags associated with a [`ShareableConfiguration`], and logs whether the retrieval was successful
     /// or of a None type.
     ///
-    /// # Warnings
-    /// Will provide the user with a warning if metadata obtained returns a None type.
-    ///
     /// # Examples:
     /// ```
     /// use cider::parsing::json_parser;
@@ -225,8 +282,7 @@ impl ShareableConfiguration {
                 Some(tags.to_owned())
             }
             None => {
-                let res_str = "No tags found or no tags configured.";
-                warn!("{}", res_str);
+                debug!("No tags found or no tags configured.");
                 None
             }
         }
@@ -297,9 +353,6 @@ impl ShareableConfiguration {
     /// Returns the image associated with a [`ShareableConfiguration`], and logs whether the retrieval was successful
     /// or of a None type.
     ///
-    /// # Warnings
-    /// Will provide the user with a warning if image obtained returns a None type.
-    ///
     /// # Examples:
     /// ```
     /// use cider::parsing::json_parser;
@@ -316,10 +369,7 @@ impl ShareableConfiguration {
                 Some(image.to_string())
             }
             None => {
-                if Self::get_backend(self) == "docker" {
-                    let res_str = "No image found or no image configured.";
-                    warn!("{}", res_str);
-                }
+                debug!("No image found or no image configured.");
                 None
             }
         }
@@ -459,11 +509,315 @@ impl ShareableConfiguration {
     ///
     /// s.s_config.set_source(src.clone());
     ///
-    /// //assert_eq!(s.s_config.get_source(), &src);
+    /// assert_eq!(s.s_config.get_source(), src);
     /// ```
     pub fn set_source(&mut self, new_source: String) {
         info!("New source directory set: {}", new_source);
-        self.backend = new_source;
+        self.source = new_source;
+    }
+
+    /// Returns the platform docker images are built for, or None if they are built for the native platform
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "backend": "docker", "platform": "linux/amd64", "actions": [] }"#);
+    ///
+    /// assert_eq!(s.s_config.get_platform(), Some("linux/amd64"));
+    /// ```
+    pub fn get_platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+
+    /// Allows the platform docker images are built for to be changed
+    pub fn set_platform(&mut self, new_platform: Option<String>) {
+        info!("New platform set: {:?}", new_platform);
+        self.platform = new_platform;
+    }
+
+    /// Returns whether the patterns of the source directory's `.gitignore` are ignored by docker and while watching
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "use_gitignore": true, "actions": [] }"#);
+    ///
+    /// assert!(s.s_config.get_use_gitignore());
+    /// ```
+    pub fn get_use_gitignore(&self) -> bool {
+        self.use_gitignore
+    }
+
+    /// Allows whether the patterns of the source directory's `.gitignore` are ignored to be changed
+    pub fn set_use_gitignore(&mut self, new_use_gitignore: bool) {
+        info!("New use gitignore setting set: {:?}", new_use_gitignore);
+        self.use_gitignore = new_use_gitignore;
+    }
+
+    /// Returns whether the relative paths within the scripts of steps are resolved before the scripts are run
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "clean_paths": false, "actions": [] }"#);
+    ///
+    /// assert!(!s.s_config.get_clean_paths());
+    /// ```
+    pub fn get_clean_paths(&self) -> bool {
+        self.clean_paths
+    }
+
+    /// Allows whether the relative paths within the scripts of steps are resolved to be changed
+    pub fn set_clean_paths(&mut self, new_clean_paths: bool) {
+        info!("New clean paths setting set: {:?}", new_clean_paths);
+        self.clean_paths = new_clean_paths;
+    }
+
+    /// Returns the directory docker metrics are written to, if one is configured
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "metrics_dir": "/tmp/metrics", "actions": [] }"#);
+    ///
+    /// assert_eq!(s.s_config.get_metrics_dir(), Some("/tmp/metrics"));
+    /// ```
+    pub fn get_metrics_dir(&self) -> Option<&str> {
+        self.metrics_dir.as_deref()
+    }
+
+    /// Allows the directory docker metrics are written to to be changed
+    pub fn set_metrics_dir(&mut self, new_metrics_dir: Option<String>) {
+        info!("New metrics directory set: {:?}", &new_metrics_dir);
+        self.metrics_dir = new_metrics_dir;
+    }
+
+    /// Returns the pattern docker metrics files are named with, if one is configured
+    pub fn get_metrics_filename(&self) -> Option<&str> {
+        self.metrics_filename.as_deref()
+    }
+
+    /// Allows the pattern docker metrics files are named with to be changed
+    pub fn set_metrics_filename(&mut self, new_metrics_filename: Option<String>) {
+        info!("New metrics filename set: {:?}", &new_metrics_filename);
+        self.metrics_filename = new_metrics_filename;
+    }
+
+    /// Returns the patterns whose matches are redacted from the output of steps, see [`crate::redaction::RedactPattern`]
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let s = json_parser::parse_from_str(r#"{ "redact": ["ghp_\\w+"], "actions": [] }"#);
+    ///
+    /// assert_eq!(s.s_config.get_redact(), [r"ghp_\w+"]);
+    /// ```
+    pub fn get_redact(&self) -> &[String] {
+        &self.redact
+    }
+
+    /// Allows the patterns whose matches are redacted from the output of steps to be changed
+    pub fn set_redact(&mut self, new_redact: Vec<String>) {
+        info!("New redaction patterns set: {:?}", &new_redact);
+        self.redact = new_redact;
+    }
+
+    /// Creates a [`ShareableConfigurationBuilder`], which starts from the same defaults a configuration file does
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::ShareableConfiguration;
+    ///
+    /// let s = ShareableConfiguration::builder().backend("docker").image("rust").build();
+    ///
+    /// assert_eq!(s.get_backend(), "docker");
+    /// assert_eq!(s.get_image().unwrap(), "rust");
+    /// assert_eq!(s.get_language(), "Python");
+    /// ```
+    pub fn builder() -> ShareableConfigurationBuilder {
+        ShareableConfigurationBuilder::default()
+    }
+
+    /// Returns the [`ShareableConfiguration`] as a JSON object
+    ///
+    /// Keys match those used within a configuration file, so the object can be merged into the JSON representation of any
+    /// level of configuration.
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            "title": self.title.clone(),
+            "metadata": sorted_map(self.metadata.as_ref()),
+            "tags": sorted_map(self.tags.as_ref()),
+            "language": self.language.clone(),
+            "image": self.image.clone(),
+            "backend": self.backend.clone(),
+            "output_directory": self.output.clone(),
+            "source_directory": self.source.clone(),
+            "platform": self.platform.clone(),
+            "use_gitignore": self.use_gitignore,
+            "clean_paths": self.clean_paths,
+            "metrics_dir": self.metrics_dir.clone(),
+            "metrics_filename": self.metrics_filename.clone(),
+            "redact": self.redact.clone(),
+        }
+    }
+}
+
+/// Builds a [`ShareableConfiguration`] without listing every value, see [`ShareableConfiguration::builder`]
+///
+/// Anything that is not set is defaulted the same way it is when it is left out of a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareableConfigurationBuilder {
+    metadata: Option<HashMap<String, String>>,
+    title: Option<String>,
+    tags: Option<HashMap<String, String>>,
+    language: String,
+    image: Option<String>,
+    backend: String,
+    output: String,
+    source: String,
+    platform: Option<String>,
+    use_gitignore: bool,
+    clean_paths: bool,
+    metrics_dir: Option<String>,
+    metrics_filename: Option<String>,
+    redact: Vec<String>,
+}
+
+impl Default for ShareableConfigurationBuilder {
+    fn default() -> Self {
+        ShareableConfigurationBuilder {
+            metadata: None,
+            title: None,
+            tags: None,
+            language: "Python".to_string(),
+            image: None,
+            backend: "bash".to_string(),
+            output: json_parser::resolve_path("./dist/cider/"),
+            source: json_parser::resolve_path("./src"),
+            platform: None,
+            use_gitignore: false,
+            clean_paths: true,
+            metrics_dir: None,
+            metrics_filename: None,
+            redact: vec![],
+        }
+    }
+}
+
+impl ShareableConfigurationBuilder {
+    /// Sets the metadata
+    pub fn metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets the title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the tags
+    pub fn tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Sets the language
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// Sets the docker image, which is only kept if the backend is docker
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.image = Some(image.into());
+        self
+    }
+
+    /// Sets the backend
+    pub fn backend(mut self, backend: impl Into<String>) -> Self {
+        self.backend = backend.into();
+        self
+    }
+
+    /// Sets the output directory, resolved against the current working directory unless it is absolute
+    pub fn output(mut self, output: &str) -> Self {
+        self.output = json_parser::resolve_path(output);
+        self
+    }
+
+    /// Sets the source directory, resolved against the current working directory unless it is absolute
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = json_parser::resolve_path(source);
+        self
+    }
+
+    /// Sets the platform docker images are built for, which is only kept if the backend is docker
+    pub fn platform(mut self, platform: impl Into<String>) -> Self {
+        self.platform = Some(platform.into());
+        self
+    }
+
+    /// Sets whether the patterns of the source directory's `.gitignore` are ignored by docker and while watching
+    pub fn use_gitignore(mut self, use_gitignore: bool) -> Self {
+        self.use_gitignore = use_gitignore;
+        self
+    }
+
+    /// Sets whether the relative paths within the scripts of steps are resolved before the scripts are run
+    pub fn clean_paths(mut self, clean_paths: bool) -> Self {
+        self.clean_paths = clean_paths;
+        self
+    }
+
+    /// Sets the directory docker metrics are written to, resolved against the current working directory unless it is
+    /// absolute
+    pub fn metrics_dir(mut self, metrics_dir: &str) -> Self {
+        self.metrics_dir = Some(json_parser::resolve_path(metrics_dir));
+        self
+    }
+
+    /// Sets the pattern docker metrics files are named with
+    pub fn metrics_filename(mut self, metrics_filename: impl Into<String>) -> Self {
+        self.metrics_filename = Some(metrics_filename.into());
+        self
+    }
+
+    /// Adds a pattern whose matches are redacted from the output of steps, see [`crate::redaction::RedactPattern`]
+    pub fn redact(mut self, pattern: impl Into<String>) -> Self {
+        self.redact.push(pattern.into());
+        self
+    }
+
+    /// Builds the [`ShareableConfiguration`]
+    pub fn build(self) -> ShareableConfiguration {
+        let mut config = ShareableConfiguration::new(
+            self.metadata,
+            self.title,
+            self.tags,
+            self.language,
+            self.image,
+            self.backend,
+            self.output,
+            self.source,
+        );
+        if self.platform.is_some() && !config.get_backend().eq_ignore_ascii_case("docker") {
+            warn!("Platform cannot be set if docker is not the backend.");
+        } else {
+            config.set_platform(self.platform);
+        }
+        config.set_use_gitignore(self.use_gitignore);
+        config.set_clean_paths(self.clean_paths);
+        config.set_metrics_dir(self.metrics_dir);
+        config.set_metrics_filename(self.metrics_filename);
+        config.set_redact(self.redact);
+        config
     }
 }
 
@@ -497,6 +851,23 @@ pub struct TopLevelConfiguration {
 
     ///Top-level actions not required for a TopLevelConfiguration implementation to be valid
     actions: Vec<Action>,
+
+    /// Actions that always run once everything else has run, whether the run succeeded or not, such as cleanup
+    /// defaulted to an empty Vector
+    post_run: Vec<Action>,
+
+    /// Steps that run before every [`Action`] of the configuration, such as printing a separator
+    /// defaulted to an empty Vector
+    before_each: Vec<Step>,
+
+    /// Steps that run after every [`Action`] of the configuration, even if the action failed, such as resetting state
+    /// defaulted to an empty Vector
+    after_each: Vec<Step>,
+
+    /// The conditions which are required to be true in order for the program to run any [`Action`] of the
+    /// configuration
+    /// defaulted to an empty Vector
+    conditions: Vec<Condition>,
 }
 
 impl TopLevelConfiguration {
@@ -519,6 +890,10 @@ impl TopLevelConfiguration {
             pipelines,
             action_defs,
             actions,
+            post_run: vec![],
+            before_each: vec![],
+            after_each: vec![],
+            conditions: vec![],
         }
     }
 
@@ -600,6 +975,30 @@ impl TopLevelConfiguration {
         self.pipelines = new_pipelines;
     }
 
+    /// Returns the [`Pipeline`] titled `name`, if there is one
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let t = json_parser::new_top_level("./cider_config.json");
+    ///
+    /// assert!(t.get_pipeline("Build_Pipeline").is_some());
+    /// assert!(t.get_pipeline("Deploy_Pipeline").is_none());
+    /// ```
+    pub fn get_pipeline(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines
+            .iter()
+            .find(|pipeline| pipeline.shared_config.title() == Some(name))
+    }
+
+    /// Returns the [`Pipeline`] titled `name` so that it can be changed, if there is one
+    pub fn get_pipeline_mut(&mut self, name: &str) -> Option<&mut Pipeline> {
+        self.pipelines
+            .iter_mut()
+            .find(|pipeline| pipeline.shared_config.title() == Some(name))
+    }
+
     /// Returns action definitions
     ///
     /// Returns the a reference to the [`Action`] definitions associated with a [`TopLevelConfiguration`] in a vector form
@@ -664,9 +1063,108 @@ impl TopLevelConfiguration {
         self.actions = new_actions;
     }
 
+    /// Returns the top-level [`Action`] titled `name`, if there is one
+    ///
+    /// Only actions listed in the top-level `actions` are looked up. Use [`TopLevelConfiguration::get_pipeline`] to find
+    /// the actions of a pipeline.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let t = json_parser::new_top_level("./cider_config.json");
+    ///
+    /// assert!(t.get_action("Print_Version").is_some());
+    /// assert!(t.get_action("Build").is_none());
+    /// ```
+    pub fn get_action(&self, name: &str) -> Option<&Action> {
+        self.actions
+            .iter()
+            .find(|action| action.shared_config.title() == Some(name))
+    }
+
+    /// Returns the top-level [`Action`] titled `name` so that it can be changed, if there is one
+    pub fn get_action_mut(&mut self, name: &str) -> Option<&mut Action> {
+        self.actions
+            .iter_mut()
+            .find(|action| action.shared_config.title() == Some(name))
+    }
+
+    /// Returns the [`Action`]s that run once everything else has run, whether the run succeeded or not
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let config = json_parser::parse_from_str(r#"{
+    ///     "actions": [],
+    ///     "post_run": ["Clean"],
+    ///     "Clean": { "manual": { "clean": "rm -rf tmp" } }
+    /// }"#);
+    ///
+    /// assert_eq!(config.get_post_run()[0].shared_config.title(), Some("Clean"));
+    /// ```
+    pub fn get_post_run(&self) -> &Vec<Action> {
+        &self.post_run
+    }
+
+    /// Allows the [`Action`]s that run once everything else has run to be changed
+    pub fn set_post_run(&mut self, new_post_run: Vec<Action>) {
+        info!("New post_run actions set: \n{:#?}", new_post_run);
+        self.post_run = new_post_run;
+    }
+
+    /// Returns the [`Step`]s that run before every [`Action`] of the configuration
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let config = json_parser::parse_from_str(r#"{
+    ///     "before_each": { "separator": "echo ----" },
+    ///     "actions": ["Build"],
+    ///     "Build": { "manual": { "build": "cargo build" } }
+    /// }"#);
+    ///
+    /// assert_eq!(config.get_before_each()[0].get_script(), "echo ----");
+    /// ```
+    pub fn get_before_each(&self) -> &Vec<Step> {
+        &self.before_each
+    }
+
+    /// Allows the [`Step`]s that run before every [`Action`] of the configuration to be changed
+    pub fn set_before_each(&mut self, new_before_each: Vec<Step>) {
+        info!("New before_each steps set: \n{:#?}", new_before_each);
+        self.before_each = new_before_each;
+    }
+
+    /// Returns the [`Step`]s that run after every [`Action`] of the configuration, even if the action failed
+    pub fn get_after_each(&self) -> &Vec<Step> {
+        &self.after_each
+    }
+
+    /// Allows the [`Step`]s that run after every [`Action`] of the configuration to be changed
+    pub fn set_after_each(&mut self, new_after_each: Vec<Step>) {
+        info!("New after_each steps set: \n{:#?}", new_after_each);
+        self.after_each = new_after_each;
+    }
+
+    /// Returns the [`Condition`]s that every [`Action`] of the configuration inherits
+    pub fn get_conditions(&self) -> &Vec<Condition> {
+        &self.conditions
+    }
+
+    /// Allows the [`Condition`]s that every [`Action`] of the configuration inherits to be changed
+    pub fn set_conditions(&mut self, new_conditions: Vec<Condition>) {
+        info!("New conditions set: {:#?}", new_conditions);
+        self.conditions = new_conditions;
+    }
+
     /// Returns every action in the configuration
     ///
-    /// Returns the a reference to the [`Action`] definitions associated with a [`TopLevelConfiguration`] and all underlying [`Pipeline`]s in a vector form
+    /// Returns the a reference to the [`Action`] definitions associated with a [`TopLevelConfiguration`] and all underlying [`Pipeline`]s in a vector form.
+    /// Top-level actions come first, followed by the actions of each pipeline in order. Each action keeps track of the
+    /// pipeline it belongs to, see [`Action::get_pipeline`].
     ///
     /// # Examples:
     /// ```
@@ -688,6 +1186,35 @@ impl TopLevelConfiguration {
         }
         actions
     }
+
+    /// Returns the fully-resolved [`TopLevelConfiguration`] as a JSON object
+    ///
+    /// Every [`Pipeline`] and [`Action`] is included with the configuration it inherited from higher levels, rather than
+    /// only the configuration that was written in the configuration file.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// //returns a TopLevelConfiguration
+    /// let t = json_parser::new_top_level("./cider_config.json");
+    ///
+    /// println!("{}", t.to_json().pretty(4));
+    /// ```
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = self.s_config.to_json();
+        json["pipeline_defs"] = self.pipeline_defs.clone().into();
+        json["pipelines"] = self.pipelines.iter().map(Pipeline::to_json).collect::<Vec<_>>().into();
+        json["action_defs"] = self.action_defs.clone().into();
+        json["actions"] = self.actions.iter().map(Action::to_json).collect::<Vec<_>>().into();
+        json["post_run"] = self.post_run.iter().map(Action::to_json).collect::<Vec<_>>().into();
+        json["before_each"] = steps_to_json(&self.before_each);
+        json["after_each"] = steps_to_json(&self.after_each);
+        if !self.conditions.is_empty() {
+            json["conditions"] = conditions_to_json(&self.conditions);
+        }
+        json
+    }
 }
 
 ///holds action-specific configuration information
@@ -695,13 +1222,21 @@ impl TopLevelConfiguration {
 /// Actions are designed to hold the necessary information to run scripts, as well as any specific configuration pieces that may be necessary.
 ///
 /// It is important to note that action-specific configuration overrides [`ShareableConfiguration`] information provided from any other level.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Action {
     /// ShareableConfiguration data required to perform bottom-level tasks. See [`ShareableConfiguration`]
     pub shared_config: ShareableConfiguration,
 
     /// Contains configuration information relevant only to [`Action`]s
     pub action_config: ActionConfig,
+
+    /// The title of the [`Pipeline`] this action belongs to
+    /// defaulted to None, which means the action was defined at the top level
+    pipeline: Option<String>,
+
+    /// Where the action was defined within its configuration
+    /// defaulted to None, which means the action was not parsed from a configuration
+    location: Option<SourceLocation>,
 }
 
 impl Action {
@@ -710,35 +1245,400 @@ impl Action {
         Action {
             shared_config,
             action_config,
+            pipeline: None,
+            location: None,
         }
     }
-}
-
-/// Contains information required to run defined [`Action`]s
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ActionConfig {
-    ///The conditions which are required to be true in order for the program to run an action
-    /// Currently not implemented.
-    conditions: Option<Vec<Condition>>,
-
-    /// Specifies how many times the program will a given action in the event that the result is a failure.
-    /// Currently not implemented.
-    retries: i8,
-
-    ///Specifies whether the action is allowed to fail and the result is still able to be considered a success
-    allowed_failure: bool,
 
-    ///The actual steps required for program execution
+    /// Returns the title of the [`Pipeline`] this [`Action`] belongs to, or None if it was defined at the top level
+    ///
     /// # Examples:
     /// ```
-    /// use cider::config::*;
-    /// let step_1 = Step::new("step1".to_string(), "echo \"hello world!\"".to_string());
-    /// let step_2 = Step::new("step1".to_string(), "echo \"hello world!\"".to_string());
-    /// let manual = vec![step_1, step_2];
+    /// use cider::parsing::json_parser;
+    ///
+    /// //returns a TopLevelConfiguration
+    /// let t = json_parser::new_top_level("./cider_config.json");
+    ///
+    /// for action in t.get_all_actions() {
+    ///     println!("{:?}", action.get_pipeline());
+    /// }
     /// ```
-    manual: Vec<Step>,
+    pub fn get_pipeline(&self) -> Option<&str> {
+        self.pipeline.as_deref()
+    }
+
+    /// Creates an [`ActionBuilder`] for an [`Action`] titled `title`
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::{Action, ShareableConfiguration};
+    ///
+    /// let action = Action::builder("Build")
+    ///     .shared_config(ShareableConfiguration::builder().backend("bash"))
+    ///     .step("build", "cargo build")
+    ///     .step("test", "cargo test")
+    ///     .allowed_failure(true)
+    ///     .build();
+    ///
+    /// assert_eq!(action.shared_config.get_title().unwrap(), "Build");
+    /// assert_eq!(action.action_config.get_manual().len(), 2);
+    /// ```
+    pub fn builder(title: impl Into<String>) -> ActionBuilder {
+        ActionBuilder::new(title)
+    }
+
+    /// Changes the [`Pipeline`] this [`Action`] belongs to
+    pub fn set_pipeline(&mut self, new_pipeline: Option<String>) {
+        info!("New pipeline set: {:?}", new_pipeline);
+        self.pipeline = new_pipeline;
+    }
+
+    /// Returns where the [`Action`] was defined within its configuration, or None if it was not parsed from one
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    /// Changes where the [`Action`] was defined within its configuration
+    pub fn set_location(&mut self, new_location: Option<SourceLocation>) {
+        debug!("New location set: {:?}", new_location);
+        self.location = new_location;
+    }
+
+    /// Returns the [`Action`] as a JSON object containing both its shared and action-specific configuration
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = self.shared_config.to_json();
+        for (key, value) in self.action_config.to_json().entries() {
+            json[key] = value.clone();
+        }
+        json
+    }
+}
+
+/// Actions are equal when their configurations are, regardless of where they were defined
+impl PartialEq for Action {
+    fn eq(&self, other: &Self) -> bool {
+        self.shared_config == other.shared_config
+            && self.action_config == other.action_config
+            && self.pipeline == other.pipeline
+    }
+}
+
+impl Eq for Action {}
+
+/// Builds an [`Action`] step by step, see [`Action::builder`]
+///
+/// Anything that is not set is defaulted the same way it is when it is left out of a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionBuilder {
+    title: String,
+    shared_config: ShareableConfigurationBuilder,
+    conditions: Vec<Condition>,
+    retries: i8,
+    allowed_failure: bool,
+    manual: Vec<Step>,
+    shared_shell: bool,
+    merge_output: bool,
+    fail_on_stderr: bool,
+    tty: bool,
+    docker_workdir: Option<String>,
+    docker_user: Option<String>,
+    step_timeout: Option<Duration>,
+    pull_retries: u32,
+    pull_retry_delay: Duration,
+    weight: usize,
+    concurrency: usize,
+    cache_key_files: Vec<String>,
+    skip: bool,
+    docker_context_tar: bool,
+    pull_policy: Option<PullPolicy>,
+}
+
+impl ActionBuilder {
+    /// Creates an [`ActionBuilder`] for an [`Action`] titled `title`
+    pub fn new(title: impl Into<String>) -> Self {
+        ActionBuilder {
+            title: title.into(),
+            shared_config: ShareableConfiguration::builder(),
+            conditions: vec![],
+            retries: 0,
+            allowed_failure: false,
+            manual: vec![],
+            shared_shell: false,
+            merge_output: false,
+            fail_on_stderr: false,
+            tty: false,
+            docker_workdir: None,
+            docker_user: None,
+            step_timeout: None,
+            pull_retries: 0,
+            pull_retry_delay: DEFAULT_PULL_RETRY_DELAY,
+            weight: 1,
+            concurrency: 1,
+            cache_key_files: vec![],
+            skip: false,
+            docker_context_tar: false,
+            pull_policy: None,
+        }
+    }
+
+    /// Sets the shared configuration of the [`Action`], whose title is replaced by the title of the [`Action`]
+    pub fn shared_config(mut self, shared_config: ShareableConfigurationBuilder) -> Self {
+        self.shared_config = shared_config;
+        self
+    }
+
+    /// Adds a [`Condition`] that has to be met for the [`Action`] to run
+    pub fn condition(mut self, name: impl Into<String>, condition: impl Into<String>) -> Self {
+        self.conditions.push(Condition::new(name.into(), condition.into()));
+        self
+    }
+
+    /// Sets how many times the [`Action`] is retried
+    pub fn retries(mut self, retries: i8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets whether the [`Action`] is allowed to fail
+    pub fn allowed_failure(mut self, allowed_failure: bool) -> Self {
+        self.allowed_failure = allowed_failure;
+        self
+    }
+
+    /// Adds a [`Step`] to the end of the manual
+    pub fn step(mut self, name: impl Into<String>, script: impl Into<String>) -> Self {
+        self.manual.push(Step::from_command(name, script));
+        self
+    }
+
+    /// Sets whether every [`Step`] runs within a single shell invocation
+    pub fn shared_shell(mut self, shared_shell: bool) -> Self {
+        self.shared_shell = shared_shell;
+        self
+    }
+
+    /// Sets whether the standard error of every [`Step`] is captured along with its standard output
+    pub fn merge_output(mut self, merge_output: bool) -> Self {
+        self.merge_output = merge_output;
+        self
+    }
+
+    /// Sets whether a [`Step`] that writes to its standard error fails, even if it exits successfully
+    pub fn fail_on_stderr(mut self, fail_on_stderr: bool) -> Self {
+        self.fail_on_stderr = fail_on_stderr;
+        self
+    }
+
+    /// Sets whether every [`Step`] runs with a pseudo-terminal as its standard output and error
+    pub fn tty(mut self, tty: bool) -> Self {
+        self.tty = tty;
+        self
+    }
+
+    /// Sets the directory the steps of a docker [`Action`] run within inside its image
+    pub fn docker_workdir(mut self, docker_workdir: impl Into<String>) -> Self {
+        self.docker_workdir = Some(docker_workdir.into());
+        self
+    }
+
+    /// Sets the user the steps of a docker [`Action`] run as inside its image
+    pub fn docker_user(mut self, docker_user: impl Into<String>) -> Self {
+        self.docker_user = Some(docker_user.into());
+        self
+    }
+
+    /// Sets how long each [`Step`] may run before it is killed
+    pub fn step_timeout(mut self, step_timeout: Duration) -> Self {
+        self.step_timeout = Some(step_timeout);
+        self
+    }
+
+    /// Sets how many times a failed pull of the image of a docker [`Action`] is retried
+    pub fn pull_retries(mut self, pull_retries: u32) -> Self {
+        self.pull_retries = pull_retries;
+        self
+    }
+
+    /// Sets how long to wait before retrying a failed pull of the image of a docker [`Action`]
+    pub fn pull_retry_delay(mut self, pull_retry_delay: Duration) -> Self {
+        self.pull_retry_delay = pull_retry_delay;
+        self
+    }
+
+    /// Sets how much of the resource budget the [`Action`] uses while it runs
+    pub fn weight(mut self, weight: usize) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets how many steps of the [`Action`] may run at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Adds a file whose contents decide whether the image of the docker [`Action`] is rebuilt
+    pub fn cache_key_file(mut self, file: impl Into<String>) -> Self {
+        self.cache_key_files.push(file.into());
+        self
+    }
+
+    /// Sets whether the [`Action`] is disabled, so that it is skipped instead of run
+    pub fn skip(mut self, skip: bool) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// Sets whether the build context of the [`Action`] is sent to docker as a tarball
+    pub fn docker_context_tar(mut self, docker_context_tar: bool) -> Self {
+        self.docker_context_tar = docker_context_tar;
+        self
+    }
+
+    /// Sets when the image of a docker [`Action`] is pulled
+    pub fn pull_policy(mut self, pull_policy: PullPolicy) -> Self {
+        self.pull_policy = Some(pull_policy);
+        self
+    }
+
+    /// Builds the [`Action`]
+    pub fn build(self) -> Action {
+        let conditions = if self.conditions.is_empty() {
+            None
+        } else {
+            Some(self.conditions)
+        };
+        let mut action_config = ActionConfig::new(
+            conditions,
+            Some(self.retries),
+            Some(self.allowed_failure),
+            self.manual,
+        );
+        action_config.set_shared_shell(self.shared_shell);
+        action_config.set_merge_output(self.merge_output);
+        action_config.set_fail_on_stderr(self.fail_on_stderr);
+        action_config.set_tty(self.tty);
+        action_config.set_docker_workdir(self.docker_workdir);
+        action_config.set_docker_user(self.docker_user);
+        action_config.set_step_timeout(self.step_timeout);
+        action_config.set_pull_retries(self.pull_retries);
+        action_config.set_pull_retry_delay(self.pull_retry_delay);
+        action_config.set_weight(self.weight);
+        action_config.set_concurrency(self.concurrency);
+        action_config.set_cache_key_files(self.cache_key_files);
+        action_config.set_skip(self.skip);
+        action_config.set_docker_context_tar(self.docker_context_tar);
+        action_config.set_pull_policy(self.pull_policy);
+        Action::new(self.shared_config.title(self.title).build(), action_config)
+    }
 }
 
+/// Contains information required to run defined [`Action`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionConfig {
+    ///The conditions which are required to be true in order for the program to run an action
+    /// Currently not implemented.
+    conditions: Option<Vec<Condition>>,
+
+    /// Specifies how many times the program will a given action in the event that the result is a failure.
+    /// Currently not implemented.
+    retries: i8,
+
+    ///Specifies whether the action is allowed to fail and the result is still able to be considered a success
+    allowed_failure: bool,
+
+    ///The actual steps required for program execution
+    /// # Examples:
+    /// ```
+    /// use cider::config::*;
+    /// let step_1 = Step::new("step1".to_string(), "echo \"hello world!\"".to_string());
+    /// let step_2 = Step::new("step1".to_string(), "echo \"hello world!\"".to_string());
+    /// let manual = vec![step_1, step_2];
+    /// ```
+    manual: Vec<Step>,
+
+    /// Specifies whether every [`Step`] of the manual runs within a single shell invocation, so that state like the
+    /// working directory or variables carries over from one step to the next.
+    /// defaulted to false
+    shared_shell: bool,
+
+    /// Specifies whether the standard error of every [`Step`] is captured along with its standard output, in the order
+    /// the two were written.
+    /// defaulted to false
+    merge_output: bool,
+
+    /// Specifies whether a [`Step`] that writes anything to its standard error fails, even if it exits successfully.
+    /// defaulted to false
+    fail_on_stderr: bool,
+
+    /// Specifies whether every [`Step`] runs with a pseudo-terminal as its standard output and error, for tools that
+    /// behave differently when they are not writing to a terminal. Only supported on unix.
+    /// defaulted to false
+    tty: bool,
+
+    /// Specifies the absolute path the source of a docker [`Action`] is copied to within its image, which its steps run
+    /// within.
+    /// defaulted to None, in which case [`DEFAULT_DOCKER_WORKDIR`] is used
+    docker_workdir: Option<String>,
+
+    /// Specifies the user, optionally followed by `:` and a group, that the steps of a docker [`Action`] run as inside
+    /// its image. The copied source is owned by this user.
+    /// defaulted to None, in which case steps run as the user the image runs as, which is usually root
+    docker_user: Option<String>,
+
+    /// Specifies how long each [`Step`] may run before it is killed.
+    /// defaulted to None, in which case the default given on the command line applies
+    step_timeout: Option<Duration>,
+
+    /// Specifies how many times a failed pull of the image of a docker [`Action`] is retried.
+    /// defaulted to 0
+    pull_retries: u32,
+
+    /// Specifies how long to wait before retrying a failed pull of the image of a docker [`Action`].
+    /// defaulted to [`DEFAULT_PULL_RETRY_DELAY`]
+    pull_retry_delay: Duration,
+
+    /// Specifies how much of the resource budget the [`Action`] uses while it runs, so that heavy actions in different
+    /// [`Pipeline`]s are not run at the same time.
+    /// defaulted to 1
+    weight: usize,
+
+    /// Specifies how many steps of the [`Action`] may run at the same time. Steps still report their output in the order
+    /// they are defined.
+    /// defaulted to 1
+    concurrency: usize,
+
+    /// Specifies the files whose contents decide whether the image of a docker [`Action`] is rebuilt. When none of them
+    /// changed since the image was last built, the image is reused instead.
+    /// defaulted to an empty list, in which case the image is rebuilt on every run
+    cache_key_files: Vec<String>,
+
+    /// Specifies whether the [`Action`] is disabled, in which case it is skipped without evaluating its conditions.
+    /// defaulted to false
+    skip: bool,
+
+    /// Specifies whether the build context of a docker [`Action`] is packed into a tarball, honoring its
+    /// `.dockerignore`, and sent to `docker build` through its standard input instead of as a directory. This lets
+    /// the image be built by a remote docker daemon that cannot see the source directory.
+    /// defaulted to false
+    docker_context_tar: bool,
+
+    /// Specifies when the image of a docker [`Action`] is pulled.
+    /// defaulted to None, in which case the policy given on the command line applies
+    pull_policy: Option<PullPolicy>,
+
+    /// The conditions of the top level and of the [`Pipeline`] the [`Action`] belongs to, which are required to be true
+    /// along with its own conditions.
+    /// defaulted to an empty Vector
+    inherited_conditions: Vec<Condition>,
+}
+
+/// The directory the steps of a docker [`Action`] run within inside its image, unless it sets a `docker_workdir`
+pub const DEFAULT_DOCKER_WORKDIR: &str = "/cider/app";
+
+/// How long to wait before retrying a failed pull of a docker image, unless an [`Action`] sets a `pull_retry_delay`
+pub const DEFAULT_PULL_RETRY_DELAY: Duration = Duration::from_secs(2);
+
 impl ActionConfig {
     /// Creates a new [`ActionConfig`]
     pub fn new(
@@ -756,6 +1656,22 @@ impl ActionConfig {
             retries,
             allowed_failure,
             manual,
+            shared_shell: false,
+            merge_output: false,
+            fail_on_stderr: false,
+            tty: false,
+            docker_workdir: None,
+            docker_user: None,
+            step_timeout: None,
+            pull_retries: 0,
+            pull_retry_delay: DEFAULT_PULL_RETRY_DELAY,
+            weight: 1,
+            concurrency: 1,
+            cache_key_files: vec![],
+            skip: false,
+            docker_context_tar: false,
+            pull_policy: None,
+            inherited_conditions: vec![],
         }
     }
 
@@ -770,6 +1686,33 @@ impl ActionConfig {
         self.conditions = Some(new_conditions);
     }
 
+    /// Gets the [`Condition`]s the [`Action`] inherited from the top level and from its [`Pipeline`]
+    pub fn get_inherited_conditions(&self) -> &Vec<Condition> {
+        &self.inherited_conditions
+    }
+
+    /// Changes the [`Condition`]s the [`Action`] inherited from the levels above it
+    pub fn set_inherited_conditions(&mut self, new_inherited_conditions: Vec<Condition>) {
+        info!("New inherited conditions set: {:#?}", new_inherited_conditions);
+        self.inherited_conditions = new_inherited_conditions;
+    }
+
+    /// Returns every [`Condition`] that has to be met for the [`Action`] to run, those inherited from the levels above
+    /// it first, or None if there are none
+    pub fn get_all_conditions(&self) -> Option<Vec<Condition>> {
+        let mut conditions = self.inherited_conditions.clone();
+        for condition in self.conditions.iter().flatten() {
+            if !conditions.contains(condition) {
+                conditions.push(condition.clone());
+            }
+        }
+        if conditions.is_empty() {
+            None
+        } else {
+            Some(conditions)
+        }
+    }
+
     /// Gets the retries within an [`ActionConfig`]
     pub fn get_retries(&self) -> &i8 {
         info!("Retry count successfully acquired: {} ", &self.retries);
@@ -808,18 +1751,216 @@ impl ActionConfig {
         info!("New manual set: {:#?}", new_manual);
         self.manual = new_manual;
     }
+
+    /// Returns whether the [`Step`]s of the [`Action`] share a single shell invocation.
+    pub fn get_shared_shell(&self) -> &bool {
+        &self.shared_shell
+    }
+
+    /// Changes whether the [`Step`]s of the [`Action`] share a single shell invocation.
+    pub fn set_shared_shell(&mut self, new_shared_shell: bool) {
+        info!("New shared shell setting set: {:?}", &new_shared_shell);
+        self.shared_shell = new_shared_shell;
+    }
+
+    /// Returns whether the standard error of the [`Action`]'s [`Step`]s is captured along with their standard output.
+    pub fn get_merge_output(&self) -> &bool {
+        &self.merge_output
+    }
+
+    /// Changes whether the standard error of the [`Action`]'s [`Step`]s is captured along with their standard output.
+    pub fn set_merge_output(&mut self, new_merge_output: bool) {
+        info!("New merge output setting set: {:?}", &new_merge_output);
+        self.merge_output = new_merge_output;
+    }
+
+    /// Returns whether a [`Step`] of the [`Action`] that writes to its standard error fails.
+    pub fn get_fail_on_stderr(&self) -> bool {
+        self.fail_on_stderr
+    }
+
+    /// Changes whether a [`Step`] of the [`Action`] that writes to its standard error fails.
+    pub fn set_fail_on_stderr(&mut self, new_fail_on_stderr: bool) {
+        info!("New fail on stderr setting set: {:?}", &new_fail_on_stderr);
+        self.fail_on_stderr = new_fail_on_stderr;
+    }
+
+    /// Returns whether the [`Step`]s of the [`Action`] run with a pseudo-terminal as their standard output and error.
+    pub fn get_tty(&self) -> bool {
+        self.tty
+    }
+
+    /// Changes whether the [`Step`]s of the [`Action`] run with a pseudo-terminal as their standard output and error.
+    pub fn set_tty(&mut self, new_tty: bool) {
+        info!("New tty setting set: {:?}", &new_tty);
+        self.tty = new_tty;
+    }
+
+    /// Returns the directory the steps of the [`Action`] run within inside its image, or None if
+    /// [`DEFAULT_DOCKER_WORKDIR`] is used.
+    pub fn get_docker_workdir(&self) -> Option<&str> {
+        self.docker_workdir.as_deref()
+    }
+
+    /// Changes the directory the steps of the [`Action`] run within inside its image.
+    pub fn set_docker_workdir(&mut self, new_docker_workdir: Option<String>) {
+        info!("New docker workdir set: {:?}", &new_docker_workdir);
+        self.docker_workdir = new_docker_workdir;
+    }
+
+    /// Returns the user the steps of the [`Action`] run as inside its image, or None if they run as the user of the
+    /// image.
+    pub fn get_docker_user(&self) -> Option<&str> {
+        self.docker_user.as_deref()
+    }
+
+    /// Changes the user the steps of the [`Action`] run as inside its image.
+    pub fn set_docker_user(&mut self, new_docker_user: Option<String>) {
+        info!("New docker user set: {:?}", &new_docker_user);
+        self.docker_user = new_docker_user;
+    }
+
+    /// Returns how long each [`Step`] of the [`Action`] may run before it is killed, if the [`Action`] sets a limit.
+    pub fn get_step_timeout(&self) -> Option<Duration> {
+        self.step_timeout
+    }
+
+    /// Changes how long each [`Step`] of the [`Action`] may run before it is killed.
+    pub fn set_step_timeout(&mut self, new_step_timeout: Option<Duration>) {
+        info!("New step timeout set: {:?}", &new_step_timeout);
+        self.step_timeout = new_step_timeout;
+    }
+
+    /// Returns how many times a failed pull of the image of the [`Action`] is retried.
+    pub fn get_pull_retries(&self) -> u32 {
+        self.pull_retries
+    }
+
+    /// Changes how many times a failed pull of the image of the [`Action`] is retried.
+    pub fn set_pull_retries(&mut self, new_pull_retries: u32) {
+        info!("New pull retry count set: {:?}", &new_pull_retries);
+        self.pull_retries = new_pull_retries;
+    }
+
+    /// Returns how long to wait before retrying a failed pull of the image of the [`Action`].
+    pub fn get_pull_retry_delay(&self) -> Duration {
+        self.pull_retry_delay
+    }
+
+    /// Changes how long to wait before retrying a failed pull of the image of the [`Action`].
+    pub fn set_pull_retry_delay(&mut self, new_pull_retry_delay: Duration) {
+        info!("New pull retry delay set: {:?}", &new_pull_retry_delay);
+        self.pull_retry_delay = new_pull_retry_delay;
+    }
+
+    /// Returns how much of the resource budget the [`Action`] uses while it runs.
+    pub fn get_weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Changes how much of the resource budget the [`Action`] uses while it runs.
+    pub fn set_weight(&mut self, new_weight: usize) {
+        info!("New weight set: {:?}", &new_weight);
+        self.weight = new_weight;
+    }
+
+    /// Returns how many steps of the [`Action`] may run at the same time.
+    pub fn get_concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Changes how many steps of the [`Action`] may run at the same time.
+    pub fn set_concurrency(&mut self, new_concurrency: usize) {
+        info!("New concurrency set: {:?}", &new_concurrency);
+        self.concurrency = new_concurrency;
+    }
+
+    /// Returns the files whose contents decide whether the image of the [`Action`] is rebuilt.
+    pub fn get_cache_key_files(&self) -> &[String] {
+        &self.cache_key_files
+    }
+
+    /// Changes the files whose contents decide whether the image of the [`Action`] is rebuilt.
+    pub fn set_cache_key_files(&mut self, new_cache_key_files: Vec<String>) {
+        info!("New cache key files set: {:?}", &new_cache_key_files);
+        self.cache_key_files = new_cache_key_files;
+    }
+
+    /// Returns whether the [`Action`] is disabled.
+    pub fn get_skip(&self) -> bool {
+        self.skip
+    }
+
+    /// Changes whether the [`Action`] is disabled.
+    pub fn set_skip(&mut self, new_skip: bool) {
+        info!("New skip setting set: {:?}", &new_skip);
+        self.skip = new_skip;
+    }
+
+    /// Returns whether the build context of the [`Action`] is sent to docker as a tarball.
+    pub fn get_docker_context_tar(&self) -> bool {
+        self.docker_context_tar
+    }
+
+    /// Changes whether the build context of the [`Action`] is sent to docker as a tarball.
+    pub fn set_docker_context_tar(&mut self, new_docker_context_tar: bool) {
+        info!("New docker_context_tar setting set: {:?}", &new_docker_context_tar);
+        self.docker_context_tar = new_docker_context_tar;
+    }
+
+    /// Returns when the image of the [`Action`] is pulled, if the [`Action`] decides it.
+    pub fn get_pull_policy(&self) -> Option<PullPolicy> {
+        self.pull_policy
+    }
+
+    /// Changes when the image of the [`Action`] is pulled.
+    pub fn set_pull_policy(&mut self, new_pull_policy: Option<PullPolicy>) {
+        info!("New pull policy set: {:?}", &new_pull_policy);
+        self.pull_policy = new_pull_policy;
+    }
+
+    /// Returns the [`ActionConfig`] as a JSON object
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            "conditions": self.conditions.as_ref().map(|conditions| conditions_to_json(conditions)),
+            "retries": self.retries,
+            "allowed_failure": self.allowed_failure,
+            "manual": steps_to_json(&self.manual),
+            "shared_shell": self.shared_shell,
+            "merge_output": self.merge_output,
+            "fail_on_stderr": self.fail_on_stderr,
+            "tty": self.tty,
+            "docker_workdir": self.docker_workdir.clone(),
+            "docker_user": self.docker_user.clone(),
+            "step_timeout": self.step_timeout.map(|timeout| timeout.as_secs()),
+            "pull_retries": self.pull_retries,
+            "pull_retry_delay": self.pull_retry_delay.as_secs(),
+            "weight": self.weight,
+            "concurrency": self.concurrency,
+            "cache_key_files": self.cache_key_files.clone(),
+            "skip": self.skip,
+            "docker_context_tar": self.docker_context_tar,
+            "pull_policy": self.pull_policy.map(|policy| policy.to_string()),
+            "inherited_conditions": (!self.inherited_conditions.is_empty())
+                .then(|| conditions_to_json(&self.inherited_conditions)),
+        }
+    }
 }
 
 /// Contains information relevant to pipelines
 ///
 /// Pipelines are meant to "own" multiple [`Action`]s.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Pipeline {
     /// ShareableConfiguration data required to perform bottom-level tasks. See [`ShareableConfiguration`]
     pub shared_config: ShareableConfiguration,
 
     /// Contains configuration information relevant only to [`Action`]s
     pub pipeline_config: PipelineConfig,
+
+    /// Where the pipeline was defined within its configuration
+    /// defaulted to None, which means the pipeline was not parsed from a configuration
+    location: Option<SourceLocation>,
 }
 impl Pipeline {
     /// Creates a nwe [`Pipeline`]
@@ -827,10 +1968,40 @@ impl Pipeline {
         Pipeline {
             shared_config,
             pipeline_config,
+            location: None,
         }
     }
+
+    /// Returns where the [`Pipeline`] was defined within its configuration, or None if it was not parsed from one
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    /// Changes where the [`Pipeline`] was defined within its configuration
+    pub fn set_location(&mut self, new_location: Option<SourceLocation>) {
+        debug!("New location set: {:?}", new_location);
+        self.location = new_location;
+    }
+
+    /// Returns the [`Pipeline`] as a JSON object containing both its shared and pipeline-specific configuration
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = self.shared_config.to_json();
+        for (key, value) in self.pipeline_config.to_json().entries() {
+            json[key] = value.clone();
+        }
+        json
+    }
+}
+
+/// Pipelines are equal when their configurations are, regardless of where they were defined
+impl PartialEq for Pipeline {
+    fn eq(&self, other: &Self) -> bool {
+        self.shared_config == other.shared_config && self.pipeline_config == other.pipeline_config
+    }
 }
 
+impl Eq for Pipeline {}
+
 /// Holds information that is specific to the functionality of [`Pipeline`]s
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PipelineConfig {
@@ -855,6 +2026,16 @@ pub struct PipelineConfig {
     //not required at runtime
     //default = empty Vector
     requires: Vec<String>,
+
+    //Specifies whether the pipeline is allowed to fail without the run as a whole being considered a failure
+    //not required at runtime
+    //default = false
+    allowed_failure: bool,
+
+    //Specifies how long the pipeline waits for its requires to be met before it fails instead of running
+    //not required at runtime
+    //default = None, in which case the default given on the command line applies
+    requires_timeout: Option<Duration>,
 }
 
 impl PipelineConfig {
@@ -866,18 +2047,15 @@ impl PipelineConfig {
         requires: Option<Vec<String>>,
     ) -> Self {
         let has_run = false;
-        let requires = match requires {
-            Some(requires) => requires,
-            None => {
-                vec![]
-            }
-        };
+        let requires = requires.unwrap_or_default();
         Self {
             conditions,
             action_defs,
             actions,
             has_run,
             requires,
+            allowed_failure: false,
+            requires_timeout: None,
         }
     }
 
@@ -896,6 +2074,11 @@ impl PipelineConfig {
         }
     }
 
+    /// Returns the [`Condition`]s of a [`PipelineConfig`], which are empty if none were configured
+    pub fn conditions(&self) -> &[Condition] {
+        self.conditions.as_deref().unwrap_or_default()
+    }
+
     /// Allows the [`Condition`]s for a [`PipelineConfig`] to be changed.
     pub fn set_conditions(&mut self, new_conditions: Vec<Condition>) {
         info!("New conditions set: {:#?}", new_conditions);
@@ -911,6 +2094,170 @@ impl PipelineConfig {
     pub fn get_actions(&self) -> &Vec<Action> {
         &self.actions
     }
+
+    /// Allows the [`Action`]s of a [`Pipeline`] to be changed.
+    pub fn set_actions(&mut self, new_actions: Vec<Action>) {
+        info!("New actions set: \n{:#?}", new_actions);
+        self.actions = new_actions;
+    }
+
+    /// Returns the titles of the pipelines that must run before the [`Pipeline`] can run.
+    pub fn get_requires(&self) -> &Vec<String> {
+        &self.requires
+    }
+
+    /// Returns whether or not the [`Pipeline`] is allowed to fail.
+    pub fn get_allowed_failure(&self) -> &bool {
+        &self.allowed_failure
+    }
+
+    /// Changes whether or not the [`Pipeline`] is allowed to fail.
+    pub fn set_allowed_failure(&mut self, new_allowed_failure: bool) {
+        info!("New failure allowance set: {:?}", &new_allowed_failure);
+        self.allowed_failure = new_allowed_failure;
+    }
+
+    /// Returns how long the [`Pipeline`] waits for its requires to be met, if it sets its own limit.
+    pub fn get_requires_timeout(&self) -> Option<Duration> {
+        self.requires_timeout
+    }
+
+    /// Changes how long the [`Pipeline`] waits for its requires to be met.
+    pub fn set_requires_timeout(&mut self, new_requires_timeout: Option<Duration>) {
+        info!("New requires timeout set: {:?}", &new_requires_timeout);
+        self.requires_timeout = new_requires_timeout;
+    }
+
+    /// Returns the [`PipelineConfig`] as a JSON object
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            "conditions": self.conditions.as_ref().map(|conditions| conditions_to_json(conditions)),
+            "action_defs": self.action_defs.clone(),
+            "actions": self.actions.iter().map(Action::to_json).collect::<Vec<_>>(),
+            "requires": self.requires.clone(),
+            "allowed_failure": self.allowed_failure,
+            "requires_timeout": self.requires_timeout.map(|timeout| timeout.as_secs()),
+        }
+    }
+}
+
+/// Decides when the base image of a docker [`Action`] is pulled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PullPolicy {
+    /// Pull the image only when it is not already present locally
+    #[default]
+    Missing,
+    /// Pull the image before every build, even if it is already present locally
+    Always,
+    /// Never pull the image, failing the [`Action`] if it is not already present locally
+    Never,
+}
+
+impl PullPolicy {
+    /// Parses a [`PullPolicy`] from the name it is configured with, regardless of case
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::PullPolicy;
+    ///
+    /// assert_eq!(PullPolicy::parse("if-not-present"), Some(PullPolicy::Missing));
+    /// assert_eq!(PullPolicy::parse("Never"), Some(PullPolicy::Never));
+    /// assert_eq!(PullPolicy::parse("sometimes"), None);
+    /// ```
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy.to_lowercase().as_str() {
+            "always" => Some(PullPolicy::Always),
+            "if-not-present" => Some(PullPolicy::Missing),
+            "never" => Some(PullPolicy::Never),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PullPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PullPolicy::Always => write!(f, "always"),
+            PullPolicy::Missing => write!(f, "if-not-present"),
+            PullPolicy::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Decides what happens when a [`Condition`] is not met
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConditionMode {
+    /// Skip the [`Action`]
+    #[default]
+    Block,
+    /// Log a warning, but run the [`Action`] anyway
+    Warn,
+}
+
+impl ConditionMode {
+    /// Parses a [`ConditionMode`] from the name it is configured with, regardless of case
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::ConditionMode;
+    ///
+    /// assert_eq!(ConditionMode::parse("Warn"), Some(ConditionMode::Warn));
+    /// assert_eq!(ConditionMode::parse("ignore"), None);
+    /// ```
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_lowercase().as_str() {
+            "block" => Some(ConditionMode::Block),
+            "warn" => Some(ConditionMode::Warn),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConditionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionMode::Block => write!(f, "block"),
+            ConditionMode::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+/// Where an [`Action`] or [`Pipeline`] was defined within a configuration
+///
+/// # Examples:
+/// ```
+/// use cider::config::SourceLocation;
+///
+/// let location = SourceLocation {
+///     file: Some("cider_config.json".to_string()),
+///     position: Some((12, 5)),
+/// };
+///
+/// assert_eq!(location.to_string(), "cider_config.json:12:5");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The configuration file the definition was read from, or None if the configuration was not read from a file
+    pub file: Option<String>,
+    /// The line and column the definition starts at, both counted from 1, if it could be found
+    pub position: Option<(usize, usize)>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.position) {
+            (Some(file), Some((line, column))) => write!(f, "{}:{}:{}", file, line, column),
+            (Some(file), None) => write!(f, "{}", file),
+            (None, Some((line, column))) => write!(f, "line {}, column {}", line, column),
+            (None, None) => write!(f, "an unknown location"),
+        }
+    }
+}
+
+/// Returns ` (defined at <location>)` if a definition has a location, to follow its name within a message, or an empty
+/// string otherwise
+pub(crate) fn defined_at(location: Option<&SourceLocation>) -> String {
+    location.map_or_else(String::new, |location| format!(" (defined at {})", location))
 }
 
 /// Holds information with conditions that will resolve to either true or false
@@ -922,12 +2269,30 @@ pub struct Condition {
     //A condition is also necessary for a condition to exist.
     //There cannot be a default condition for a condition struct, as it would be forced to default to true
     condition: String,
+    /// What happens when the condition is not met.
+    /// defaulted to [`ConditionMode::Block`]
+    mode: ConditionMode,
 }
 
 impl Condition {
-    /// Creates a new [`Condition`]
+    /// Creates a new [`Condition`], which skips its [`Action`] when it is not met
     pub fn new(name: String, condition: String) -> Condition {
-        Condition { name, condition }
+        Condition {
+            name,
+            condition,
+            mode: ConditionMode::Block,
+        }
+    }
+
+    /// Returns what happens when the [`Condition`] is not met
+    pub fn get_mode(&self) -> ConditionMode {
+        self.mode
+    }
+
+    /// Changes what happens when the [`Condition`] is not met
+    pub fn set_mode(&mut self, new_mode: ConditionMode) {
+        info!("New condition mode set: {}", new_mode);
+        self.mode = new_mode;
     }
 
     /// Returns the [`Condition`] name
@@ -952,12 +2317,37 @@ impl Condition {
 pub struct Step {
     name: String,
     script: String,
+    /// What is written to the standard input of the step, defaulted to nothing
+    ///
+    /// A value starting with `@` names a file whose contents are written instead.
+    stdin: Option<String>,
+    /// Whether the action carries on as if the step succeeded when it fails, defaulted to the `allowed_failure` of its
+    /// action
+    allowed_failure: Option<bool>,
 }
 
 impl Step {
     /// Creates a new [`Step`]
     pub fn new(name: String, script: String) -> Self {
-        Self { name, script }
+        Self {
+            name,
+            script,
+            stdin: None,
+            allowed_failure: None,
+        }
+    }
+
+    /// Creates a new [`Step`] named `name` that runs `script`, accepting anything that converts into a [`String`]
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::Step;
+    ///
+    /// let step = Step::from_command("build", "cargo build");
+    /// assert_eq!(step, Step::new("build".to_string(), "cargo build".to_string()));
+    /// ```
+    pub fn from_command(name: impl Into<String>, script: impl Into<String>) -> Self {
+        Self::new(name.into(), script.into())
     }
 
     /// Returns the name of the [`Step`]
@@ -970,9 +2360,198 @@ impl Step {
         &self.script
     }
 
+    /// Returns what is written to the standard input of this [`Step`], if anything
+    pub fn get_stdin(&self) -> Option<&str> {
+        self.stdin.as_deref()
+    }
+
+    /// Sets what is written to the standard input of this [`Step`]: a literal string, or `@` followed by the path of a
+    /// file to read it from
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::Step;
+    ///
+    /// let mut step = Step::from_command("count", "wc -l");
+    /// step.set_stdin(Some("one\ntwo\n".to_string()));
+    /// assert_eq!(step.get_stdin(), Some("one\ntwo\n"));
+    /// ```
+    pub fn set_stdin(&mut self, stdin: Option<String>) {
+        info!("New stdin set: {:?}", stdin);
+        self.stdin = stdin;
+    }
+
+    /// Returns whether this [`Step`] is allowed to fail, or None if it follows the `allowed_failure` of its action
+    pub fn get_allowed_failure(&self) -> Option<bool> {
+        self.allowed_failure
+    }
+
+    /// Sets whether this [`Step`] is allowed to fail, where None follows the `allowed_failure` of its action
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::config::Step;
+    ///
+    /// let mut step = Step::from_command("lint", "cargo clippy");
+    /// step.set_allowed_failure(Some(true));
+    /// assert_eq!(step.get_allowed_failure(), Some(true));
+    /// ```
+    pub fn set_allowed_failure(&mut self, allowed_failure: Option<bool>) {
+        info!("New allowed_failure set: {:?}", allowed_failure);
+        self.allowed_failure = allowed_failure;
+    }
+
     /// Changes the information held by the [`Step`]
     pub fn update_script(&mut self, name: String, script: String) {
         self.name = name;
         self.script = script;
     }
 }
+
+/// Returns [`Condition`]s as a JSON object in the same form they are configured in, `{ "name": "condition" }`
+fn conditions_to_json(conditions: &[Condition]) -> JsonValue {
+    let mut json = JsonValue::new_object();
+    for condition in conditions {
+        json[condition.get_name()] = match condition.get_mode() {
+            ConditionMode::Block => condition.get_condition().into(),
+            mode => json::object! {
+                "condition": condition.get_condition(),
+                "mode": mode.to_string(),
+            },
+        };
+    }
+    json
+}
+
+/// Returns [`Step`]s as a JSON object in the same form they are configured in, `{ "name": "script" }`
+///
+/// Steps that set anything besides their script are written as `{ "name": { "script": "script", ... } }` instead.
+fn steps_to_json(steps: &[Step]) -> JsonValue {
+    let mut json = JsonValue::new_object();
+    for step in steps {
+        if step.get_stdin().is_none() && step.get_allowed_failure().is_none() {
+            json[step.get_name()] = step.get_script().into();
+            continue;
+        }
+        let mut object = json::object! { "script": step.get_script() };
+        if let Some(stdin) = step.get_stdin() {
+            object["stdin"] = stdin.into();
+        }
+        if let Some(allowed_failure) = step.get_allowed_failure() {
+            object["allowed_failure"] = allowed_failure.into();
+        }
+        json[step.get_name()] = object;
+    }
+    json
+}
+
+/// Returns a map as a JSON object with its keys sorted, so that it is written the same way every time, or null if there
+/// is no map
+fn sorted_map(map: Option<&HashMap<String, String>>) -> JsonValue {
+    let Some(map) = map else {
+        return JsonValue::Null;
+    };
+    let mut keys = map.keys().collect::<Vec<_>>();
+    keys.sort();
+    let mut json = JsonValue::new_object();
+    for key in keys {
+        json[key.as_str()] = map[key].as_str().into();
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders_match_parsed_configuration() {
+        let parsed = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "image": "rust:latest",
+                "actions": ["Build"],
+                "Build": {
+                    "conditions": { "linux": "os:linux" },
+                    "allowed_failure": true,
+                    "fail_on_stderr": true,
+                    "tty": true,
+                    "step_timeout": 60,
+                    "pull_retries": 3,
+                    "pull_retry_delay": 5,
+                    "concurrency": 2,
+                    "cache_key_files": ["Cargo.toml", "Cargo.lock"],
+                    "docker_workdir": "/workspace",
+                    "docker_user": "builder",
+                    "manual": { "build": "cargo build", "test": "cargo test" }
+                }
+            }"#,
+        );
+        let built = Action::builder("Build")
+            .shared_config(
+                ShareableConfiguration::builder()
+                    .backend("docker")
+                    .image("rust:latest"),
+            )
+            .condition("linux", "os:linux")
+            .allowed_failure(true)
+            .fail_on_stderr(true)
+            .tty(true)
+            .step_timeout(Duration::from_secs(60))
+            .pull_retries(3)
+            .pull_retry_delay(Duration::from_secs(5))
+            .concurrency(2)
+            .cache_key_file("Cargo.toml")
+            .cache_key_file("Cargo.lock")
+            .docker_workdir("/workspace")
+            .docker_user("builder")
+            .step("build", "cargo build")
+            .step("test", "cargo test")
+            .build();
+        assert_eq!(parsed.get_actions()[0], built);
+    }
+
+    #[test]
+    fn test_lookups_match_sample_config() {
+        let mut config = json_parser::new_top_level("./cider_config.json");
+        let pipeline = config.get_pipeline("Build_Pipeline").unwrap();
+        assert_eq!(pipeline, &config.get_pipelines()[0]);
+        assert_eq!(pipeline.pipeline_config.get_actions().len(), 2);
+        assert!(config.get_pipeline("Print_Version").is_none());
+        assert_eq!(
+            config.get_action("Print_Version"),
+            Some(&config.get_actions()[0])
+        );
+        assert!(config.get_action("Build_Pipeline").is_none());
+
+        config
+            .get_pipeline_mut("Build_Pipeline")
+            .unwrap()
+            .pipeline_config
+            .set_actions(vec![]);
+        assert!(config.get_pipelines()[0].pipeline_config.get_actions().is_empty());
+        config
+            .get_action_mut("Print_Version")
+            .unwrap()
+            .shared_config
+            .set_title("Version".to_string());
+        assert!(config.get_action("Print_Version").is_none());
+        assert!(config.get_action("Version").is_some());
+    }
+
+    #[test]
+    fn test_maps_are_written_in_sorted_order() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "tags": { "zone": "eu", "arch": "x86_64", "os": "linux", "cpu": "4", "gpu": "none" },
+                "actions": []
+            }"#,
+        );
+        let tags = config.s_config.to_json()["tags"].dump();
+        assert_eq!(
+            tags,
+            r#"{"arch":"x86_64","cpu":"4","gpu":"none","os":"linux","zone":"eu"}"#
+        );
+        assert!(sorted_map(None).is_null());
+    }
+}
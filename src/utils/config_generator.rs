@@ -1,23 +1,87 @@
-// use crate::utils::config::TopLevelConfiguration;
-// use std::fs::File;
+use json::JsonValue;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
 
-// struct OutputConfig {
-//     pub configuration: TopLevelConfiguration,
-// }
+/// The base image of the configuration returned by [`docker_template`]
+pub const DEFAULT_DOCKER_IMAGE: &str = "rust:latest";
 
-// // struct json_output_config {
+/// Returns a starter configuration that runs its actions with the docker backend
+///
+/// The configuration builds from the directory it is written to, leaves out anything ignored by its `.gitignore`, and
+/// holds a single action that installs dependencies and then runs the tests.
+///
+/// # Examples:
+/// ```
+/// use cider::config_generator;
+/// use cider::parsing::json_parser;
+///
+/// let config = json_parser::parse_from_str(&config_generator::docker_template().dump());
+///
+/// assert_eq!(config.s_config.get_backend(), "docker");
+/// assert_eq!(config.get_actions()[0].shared_config.image(), Some(config_generator::DEFAULT_DOCKER_IMAGE));
+/// ```
+pub fn docker_template() -> JsonValue {
+    json::object! {
+        "title": "CIder Docker Config",
+        "backend": "docker",
+        "image": DEFAULT_DOCKER_IMAGE,
+        "language": "Rust",
+        "source_directory": "./",
+        "use_gitignore": true,
+        "actions": ["Test"],
+        "Test": {
+            "manual": {
+                "install": "cargo fetch",
+                "test": "cargo test"
+            }
+        }
+    }
+}
 
-// // }
+/// Writes `config` to the file at `path` as indented JSON
+///
+/// An existing file is only replaced if `force` is set, otherwise an error of the [`io::ErrorKind::AlreadyExists`] kind
+/// is returned and the file is left untouched.
+pub fn write_config(path: &Path, config: &JsonValue, force: bool) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    let mut file = options.open(path).map_err(|err| {
+        if err.kind() == io::ErrorKind::AlreadyExists {
+            io::Error::new(
+                err.kind(),
+                format!("{} already exists. Use --force to replace it.", path.display()),
+            )
+        } else {
+            err
+        }
+    })?;
+    writeln!(file, "{}", config.pretty(4))
+}
 
-// trait OutputFile {
-//     fn default(&self) -> File;
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, process};
 
-// impl OutputFile for OutputConfig {
-//     fn default(&self) -> File {
-//         File::create(self.configuration.s_config.get_output()).unwrap_or_else(|err| {
-//             eprintln!("No directory found at that location. {}", err);
-//             panic!("No directory found at that location. {}", err);
-//         })
-//     }
-// }
+    #[test]
+    fn test_write_config_refuses_to_overwrite() {
+        let path = env::temp_dir().join(format!("cider_generated_{}.json", process::id()));
+        write_config(&path, &docker_template(), false).unwrap();
+        fs::write(&path, "{}").unwrap();
+
+        let err = write_config(&path, &docker_template(), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        write_config(&path, &docker_template(), true).unwrap();
+        let written = json::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(written, docker_template());
+    }
+}
@@ -0,0 +1,229 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::Path;
+
+/// The image a docker action was last built into, along with the cache key it was built with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// The cache key the image was built with, see [`cache_key`]
+    pub key: String,
+    /// The tag the image was built with
+    pub tag: String,
+    /// The ID of the image that was built, so that an image since rebuilt by another action is not reused
+    pub image_id: String,
+}
+
+/// Returns the cache key of a docker action from the contents of each of `files`, relative to `root`, along with
+/// everything else the image depends on given in `inputs`, such as its Dockerfile
+///
+/// A file that does not exist is hashed as missing, so creating it changes the key. A directory is hashed along with
+/// the names and contents of everything within it, so changing, adding or removing any file within it changes the key.
+///
+/// The key is an FNV-1a hash, rather than one from [`std::hash`], whose output may change between releases of Rust,
+/// so that keys stored by one build of CIder still match those computed by another.
+///
+/// # Examples:
+/// ```
+/// use cider::docker_cache;
+/// use std::path::Path;
+///
+/// let files = vec!["Cargo.toml".to_string()];
+/// let key = docker_cache::cache_key(&files, Path::new("."), &["FROM rust:latest"]);
+///
+/// assert_eq!(key, docker_cache::cache_key(&files, Path::new("."), &["FROM rust:latest"]));
+/// assert_ne!(key, docker_cache::cache_key(&files, Path::new("."), &["FROM rust:slim"]));
+/// ```
+pub fn cache_key(files: &[String], root: &Path, inputs: &[&str]) -> String {
+    let mut hasher = Fnv1a::new();
+    hasher.write_len(inputs.len());
+    for input in inputs {
+        hasher.write_field(input.as_bytes());
+    }
+    for file in files {
+        hasher.write_field(file.as_bytes());
+        let path = root.join(file);
+        match fs::metadata(&path) {
+            Ok(metadata) => hash_path(&mut hasher, &path, &metadata),
+            Err(_) => hasher.write(&[MISSING]),
+        }
+    }
+    format!("{:016x}", hasher.0)
+}
+
+/// Marks a path that does not exist, or could not be read, within a cache key
+const MISSING: u8 = 0;
+/// Marks a file within a cache key
+const FILE: u8 = 1;
+/// Marks a directory within a cache key
+const DIRECTORY: u8 = 2;
+/// Marks a symbolic link within a directory, which is hashed by its target rather than followed
+const SYMLINK: u8 = 3;
+
+/// Hashes the file or directory at `path`, whose metadata is `metadata`, into `hasher`
+fn hash_path(hasher: &mut Fnv1a, path: &Path, metadata: &Metadata) {
+    if metadata.is_file() {
+        match fs::read(path) {
+            Ok(contents) => {
+                hasher.write(&[FILE]);
+                hasher.write_field(&contents);
+            }
+            Err(_) => hasher.write(&[MISSING]),
+        }
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        hasher.write(&[MISSING]);
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    hasher.write(&[DIRECTORY]);
+    hasher.write_len(entries.len());
+    for entry in entries {
+        hasher.write_field(entry.file_name().to_string_lossy().as_bytes());
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_symlink() => {
+                let target = fs::read_link(entry.path()).unwrap_or_default();
+                hasher.write(&[SYMLINK]);
+                hasher.write_field(target.to_string_lossy().as_bytes());
+            }
+            Ok(metadata) => hash_path(hasher, &entry.path(), &metadata),
+            Err(_) => hasher.write(&[MISSING]),
+        }
+    }
+}
+
+/// A 64-bit FNV-1a hasher, whose output is the same on every platform and release of Rust
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Writes `len` as eight little-endian bytes
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    /// Writes `bytes` preceded by their length, so that neighbouring fields cannot run into each other
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write_len(bytes.len());
+        self.write(bytes);
+    }
+}
+
+/// Returns the [`CacheEntry`] of the action titled `action` within the state file at `state`, if there is one
+pub fn read_entry(state: &Path, action: &str) -> Option<CacheEntry> {
+    let contents = fs::read_to_string(state).ok()?;
+    contents.lines().find_map(|line| {
+        let mut fields = line.split('\t');
+        if fields.next()? != action {
+            return None;
+        }
+        let entry = CacheEntry {
+            key: fields.next()?.to_string(),
+            tag: fields.next()?.to_string(),
+            image_id: fields.next()?.to_string(),
+        };
+        Some(entry)
+    })
+}
+
+/// Records `entry` as the [`CacheEntry`] of the action titled `action` within the state file at `state`, replacing
+/// any entry it had before and creating the file if it does not exist
+///
+/// Each line of the file holds the title of an action followed by the key, tag and image ID of its entry, separated
+/// by tabs.
+pub fn write_entry(state: &Path, action: &str, entry: &CacheEntry) -> io::Result<()> {
+    let contents = fs::read_to_string(state).unwrap_or_default();
+    let mut lines: Vec<String> = contents
+        .lines()
+        .filter(|line| line.split('\t').next() != Some(action))
+        .map(str::to_string)
+        .collect();
+    lines.push(format!(
+        "{}\t{}\t{}\t{}",
+        action, entry.key, entry.tag, entry.image_id
+    ));
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(state, lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn test_fnv1a() {
+        // Published FNV-1a 64 test vectors, which must keep matching for stored keys to stay valid
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv1a::new();
+            hasher.write(bytes);
+            hasher.0
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_cache_key_of_directory() {
+        let dir = env::temp_dir().join(format!("cider_docker_cache_dir_{}", process::id()));
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        let files = vec!["src".to_string()];
+
+        let original = cache_key(&files, &dir, &[]);
+        assert_eq!(original, cache_key(&files, &dir, &[]));
+        fs::write(dir.join("src/nested/lib.rs"), "").unwrap();
+        let added = cache_key(&files, &dir, &[]);
+        fs::write(dir.join("src/main.rs"), "fn main() { }").unwrap();
+        let changed = cache_key(&files, &dir, &[]);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_ne!(original, added);
+        assert_ne!(added, changed);
+        assert_ne!(changed, cache_key(&files, &dir, &[]));
+    }
+
+    #[test]
+    fn test_cache_key_and_entries() {
+        let dir = env::temp_dir().join(format!("cider_docker_cache_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files = vec!["Cargo.lock".to_string()];
+
+        let missing = cache_key(&files, &dir, &[]);
+        fs::write(dir.join("Cargo.lock"), "a").unwrap();
+        let written = cache_key(&files, &dir, &[]);
+        assert_ne!(missing, written);
+        assert_eq!(written, cache_key(&files, &dir, &[]));
+        fs::write(dir.join("Cargo.lock"), "b").unwrap();
+        assert_ne!(written, cache_key(&files, &dir, &[]));
+
+        let state = dir.join("metrics/docker_cache");
+        assert_eq!(read_entry(&state, "Build"), None);
+        let entry = |key: &str| CacheEntry {
+            key: key.to_string(),
+            tag: "cider-image".to_string(),
+            image_id: "sha256:abc".to_string(),
+        };
+        write_entry(&state, "Build", &entry("1")).unwrap();
+        write_entry(&state, "Test", &entry("2")).unwrap();
+        write_entry(&state, "Build", &entry("3")).unwrap();
+        let build = read_entry(&state, "Build");
+        let test = read_entry(&state, "Test");
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(build, Some(entry("3")));
+        assert_eq!(test, Some(entry("2")));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::utils::conditions::find_on_path;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+use std::process;
+
+/// Describes how a [`Check`] turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Everything that was checked is usable
+    Ok,
+    /// Something that was checked is missing, but configurations that do not need it can still be run
+    Warn,
+    /// Something that was checked is missing, and no configuration can be run without it
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Ok => write!(f, "ok"),
+            CheckStatus::Warn => write!(f, "warn"),
+            CheckStatus::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// The outcome of checking one part of the environment CIder runs in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, such as `backend docker`
+    pub name: String,
+    /// How the check turned out
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+}
+
+impl Check {
+    /// Creates a [`Check`] named `name`
+    pub fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    /// Returns the [`Check`] as a line of a checklist, such as `[ok]   backend bash: sh was found on the PATH`
+    pub fn line(&self) -> String {
+        format!("{:<6} {}: {}", format!("[{}]", self.status), self.name, self.detail)
+    }
+}
+
+/// Checks whether the environment can run configurations: which backends are usable, whether the configuration file
+/// at `config` exists, and whether each of `output_dirs` can be written to
+///
+/// The shell used by the default backend of the current operating system is critical, as is every output directory, so
+/// they fail their checks when they are unusable. Any other backend only warns, as does a missing configuration file.
+pub fn run_checks(config: &Path, output_dirs: &[&Path]) -> Vec<Check> {
+    let mut checks = vec![
+        check_program("backend bash", "sh", cfg!(unix)),
+        check_program("backend batch", "cmd", cfg!(windows)),
+        check_program("backend docker", "docker", false),
+        check_program("podman", "podman", false),
+    ];
+    checks.push(if find_on_path("pwsh") {
+        Check::new("powershell", CheckStatus::Ok, "pwsh was found on the PATH")
+    } else {
+        check_program("powershell", "powershell", false)
+    });
+    checks.push(if config.is_file() {
+        Check::new(
+            "configuration",
+            CheckStatus::Ok,
+            format!("{} exists", config.display()),
+        )
+    } else {
+        Check::new(
+            "configuration",
+            CheckStatus::Warn,
+            format!(
+                "{} does not exist, so --config must name a configuration to run",
+                config.display()
+            ),
+        )
+    });
+    for dir in output_dirs {
+        let name = format!("output {}", dir.display());
+        checks.push(match check_writable(dir) {
+            Ok(()) => Check::new(name, CheckStatus::Ok, "is writable"),
+            Err(err) => Check::new(name, CheckStatus::Fail, format!("is not writable: {}", err)),
+        });
+    }
+    checks
+}
+
+/// Returns whether none of `checks` failed
+///
+/// # Examples:
+/// ```
+/// use cider::doctor::{self, Check, CheckStatus};
+///
+/// let warned = vec![Check::new("podman", CheckStatus::Warn, "podman was not found on the PATH")];
+/// let failed = vec![Check::new("backend bash", CheckStatus::Fail, "sh was not found on the PATH")];
+///
+/// assert!(doctor::passed(&warned));
+/// assert!(!doctor::passed(&failed));
+/// ```
+pub fn passed(checks: &[Check]) -> bool {
+    checks.iter().all(|check| check.status != CheckStatus::Fail)
+}
+
+/// Checks whether `program` can be found on the `PATH`, failing if it is `critical` and warning otherwise
+fn check_program(name: &str, program: &str, critical: bool) -> Check {
+    if find_on_path(program) {
+        Check::new(name, CheckStatus::Ok, format!("{} was found on the PATH", program))
+    } else {
+        let status = if critical {
+            CheckStatus::Fail
+        } else {
+            CheckStatus::Warn
+        };
+        Check::new(name, status, format!("{} was not found on the PATH", program))
+    }
+}
+
+/// Checks whether a file can be created within `dir`, or within the closest of its parents that exists if it does not
+/// exist yet, without leaving anything behind
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    let absolute = std::path::absolute(dir)?;
+    let existing = absolute
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(&absolute);
+    if !existing.is_dir() {
+        return Err(std::io::Error::other(format!(
+            "{} is not a directory",
+            existing.display()
+        )));
+    }
+    let probe = existing.join(format!(".cider-doctor-{}", process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)?;
+    fs::remove_file(probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_run_checks() {
+        let dir = env::temp_dir().join(format!("cider_doctor_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("cider_config.json");
+        let not_a_dir = dir.join("file");
+        fs::write(&not_a_dir, "").unwrap();
+
+        let nested = dir.join("missing/logs");
+        let checks = run_checks(&config, &[&dir, &nested, &not_a_dir.join("logs")]);
+        let status = |name: &str| {
+            checks
+                .iter()
+                .find(|check| check.name == name)
+                .map(|check| check.status)
+        };
+        assert_eq!(status("configuration"), Some(CheckStatus::Warn));
+        assert_eq!(status(&format!("output {}", dir.display())), Some(CheckStatus::Ok));
+        assert_eq!(status(&format!("output {}", nested.display())), Some(CheckStatus::Ok));
+        assert!(!nested.exists());
+        assert!(!passed(&checks));
+
+        fs::write(&config, "{}").unwrap();
+        let checks = run_checks(&config, &[&dir]);
+        fs::remove_dir_all(&dir).unwrap();
+        let configuration = checks.iter().find(|check| check.name == "configuration").unwrap();
+        assert_eq!(configuration.status, CheckStatus::Ok);
+        assert_eq!(
+            configuration.line(),
+            format!("[ok]   configuration: {} exists", config.display())
+        );
+    }
+}
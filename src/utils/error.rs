@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Describes why CIder was unable to run an action
+///
+/// A failing step is not a [`CustomError`]: it is recorded as the [`crate::results::Status`] of the action instead.
+/// A [`CustomError`] means CIder itself could not do what the configuration asked of it.
+#[derive(Debug)]
+pub enum CustomError {
+    /// The backend configured for an action is not supported
+    UnsupportedBackend(String),
+    /// A command could not be started, or could not be waited on
+    Spawn {
+        /// The command that was being run
+        command: String,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// A file needed to run an action could not be written
+    Io {
+        /// The path of the file
+        path: String,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// A file needed to run an action could not be read
+    Read {
+        /// The path of the file
+        path: String,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// Docker metrics could not be recorded
+    Metrics(csv::Error),
+    /// A pipeline requires a pipeline or action that is not part of the configuration
+    UnknownRequirement {
+        /// The title of the pipeline
+        pipeline: String,
+        /// The requirement that could not be found
+        requirement: String,
+    },
+    /// Pipelines require each other, so none of them could ever run
+    CyclicRequirement(Vec<String>),
+    /// The run was cancelled before it finished
+    Cancelled,
+}
+
+impl CustomError {
+    /// Creates a [`CustomError::Spawn`] for `command`
+    pub fn spawn(command: impl Into<String>, source: io::Error) -> Self {
+        CustomError::Spawn {
+            command: command.into(),
+            source,
+        }
+    }
+
+    /// Creates a [`CustomError::Io`] for the file at `path`
+    pub fn io(path: impl Into<String>, source: io::Error) -> Self {
+        CustomError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomError::UnsupportedBackend(backend) => {
+                write!(f, "The backend \"{}\" is not supported", backend)
+            }
+            CustomError::Spawn { command, source } => {
+                write!(f, "Failed to execute \"{}\": {}", command, source)
+            }
+            CustomError::Io { path, source } => write!(f, "Failed to write {}: {}", path, source),
+            CustomError::Read { path, source } => write!(f, "Failed to read {}: {}", path, source),
+            CustomError::Metrics(source) => write!(f, "Failed to record docker metrics: {}", source),
+            CustomError::UnknownRequirement {
+                pipeline,
+                requirement,
+            } => write!(
+                f,
+                "Pipeline \"{}\" requires \"{}\", which is not a pipeline or action in the configuration",
+                pipeline, requirement
+            ),
+            CustomError::CyclicRequirement(cycle) => {
+                write!(f, "Pipelines require each other: {}", cycle.join(" -> "))
+            }
+            CustomError::Cancelled => write!(f, "The run was cancelled before it finished"),
+        }
+    }
+}
+
+impl Error for CustomError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CustomError::UnsupportedBackend(_)
+            | CustomError::UnknownRequirement { .. }
+            | CustomError::CyclicRequirement(_)
+            | CustomError::Cancelled => None,
+            CustomError::Spawn { source, .. }
+            | CustomError::Io { source, .. }
+            | CustomError::Read { source, .. } => Some(source),
+            CustomError::Metrics(source) => Some(source),
+        }
+    }
+}
+
+impl From<csv::Error> for CustomError {
+    fn from(err: csv::Error) -> Self {
+        CustomError::Metrics(err)
+    }
+}
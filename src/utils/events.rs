@@ -0,0 +1,164 @@
+use crate::utils::results::{CommandLine, RunMetadata};
+use chrono::{SecondsFormat, Utc};
+use json::JsonValue;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Describes what happened when an [`Event`] was sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    /// A run started
+    RunStarted {
+        /// What is known about the run as it starts
+        metadata: RunMetadata,
+    },
+    /// A run finished, along with its post_run actions
+    RunFinished {
+        /// What is known about the run once it finished
+        metadata: RunMetadata,
+    },
+    /// An action started running
+    ActionStarted,
+    /// A step of an action started running
+    StepStarted,
+    /// A step of an action wrote to its standard output or standard error
+    StepOutput {
+        /// Either `stdout` or `stderr`
+        stream: &'static str,
+        /// What the step wrote
+        text: String,
+    },
+    /// A step of an action finished running
+    StepFinished {
+        /// Whether the step succeeded
+        success: bool,
+        /// How long the step took to run
+        duration: Duration,
+        /// The command the step was run with
+        command: CommandLine,
+    },
+    /// An action finished running, or was skipped
+    ActionFinished {
+        /// The [`crate::results::Status`] of the action, as it is displayed
+        status: String,
+        /// How long the action took to run
+        duration: Duration,
+    },
+}
+
+impl EventKind {
+    /// Returns the name the [`EventKind`] is written with, such as `action_started`
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::RunStarted { .. } => "run_started",
+            EventKind::RunFinished { .. } => "run_finished",
+            EventKind::ActionStarted => "action_started",
+            EventKind::StepStarted => "step_started",
+            EventKind::StepOutput { .. } => "step_output",
+            EventKind::StepFinished { .. } => "step_finished",
+            EventKind::ActionFinished { .. } => "action_finished",
+        }
+    }
+}
+
+/// Something that happened while running actions, sent as it happens so that progress can be followed live
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// What happened
+    pub kind: EventKind,
+    /// When it happened, as an RFC 3339 timestamp
+    pub timestamp: String,
+    /// The title of the pipeline the action belongs to, if any
+    pub pipeline: Option<String>,
+    /// The title of the action
+    pub action: Option<String>,
+    /// The name of the step, for events about a step
+    pub step: Option<String>,
+}
+
+impl Event {
+    /// Creates a new [`Event`] that happened now
+    pub fn new(
+        kind: EventKind,
+        pipeline: Option<String>,
+        action: Option<String>,
+        step: Option<String>,
+    ) -> Self {
+        Event {
+            kind,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            pipeline,
+            action,
+            step,
+        }
+    }
+
+    /// Returns the [`Event`] as a JSON object
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::events::{Event, EventKind};
+    ///
+    /// let event = Event::new(EventKind::StepStarted, None, Some("Build".to_string()), Some("build".to_string()));
+    /// let json = event.to_json();
+    ///
+    /// assert_eq!(json["event"], "step_started");
+    /// assert_eq!(json["action"], "Build");
+    /// assert!(json["pipeline"].is_null());
+    /// ```
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = json::object! {
+            "event": self.kind.name(),
+            "timestamp": self.timestamp.clone(),
+            "pipeline": self.pipeline.clone(),
+            "action": self.action.clone(),
+            "step": self.step.clone(),
+        };
+        match &self.kind {
+            EventKind::RunStarted { metadata } | EventKind::RunFinished { metadata } => {
+                json["run"] = metadata.to_json();
+            }
+            EventKind::ActionStarted | EventKind::StepStarted => {}
+            EventKind::StepOutput { stream, text } => {
+                json["stream"] = (*stream).into();
+                json["text"] = text.clone().into();
+            }
+            EventKind::StepFinished {
+                success,
+                duration,
+                command,
+            } => {
+                json["success"] = (*success).into();
+                json["duration_ms"] = (duration.as_millis() as u64).into();
+                json["command"] = command.to_json();
+            }
+            EventKind::ActionFinished { status, duration } => {
+                json["status"] = status.clone().into();
+                json["duration_ms"] = (duration.as_millis() as u64).into();
+            }
+        }
+        json
+    }
+
+    /// Returns the [`Event`] as a single line of newline-delimited JSON, without the newline
+    pub fn to_ndjson(&self) -> String {
+        self.to_json().dump()
+    }
+}
+
+/// Sends [`Event`]s to whoever is following a run, such as a writer draining the receiving end of the channel
+#[derive(Debug, Clone)]
+pub struct EventSender(mpsc::Sender<Event>);
+
+impl EventSender {
+    /// Creates an [`EventSender`], along with the receiver its [`Event`]s arrive at
+    pub fn channel() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel();
+        (EventSender(sender), receiver)
+    }
+
+    /// Sends an [`Event`], doing nothing if nobody is receiving events anymore
+    pub fn send(&self, event: Event) {
+        self.0.send(event).ok();
+    }
+}
@@ -1,4 +1,20 @@
-use crate::utils::config::{Action, Condition, Step};
+use crate::utils::build_context;
+use crate::utils::conditions;
+use crate::utils::docker_cache::{self, CacheEntry};
+use crate::utils::error::CustomError;
+use crate::utils::events::{Event, EventKind, EventSender};
+use crate::utils::ignore;
+use crate::utils::redaction::{self, RedactPattern};
+use crate::utils::reporters::Reporters;
+use crate::utils::resources::{self, ResourceUsage};
+use crate::utils::terminal;
+use crate::utils::config::{
+    defined_at, Action, Condition, Pipeline, Step, TopLevelConfiguration, DEFAULT_DOCKER_WORKDIR,
+};
+use crate::utils::results::{
+    sanitize_filename, ActionResult, CommandLine, RunReport, Status, StepOutputs, StepResult,
+    Timing, TimingKind,
+};
 use chrono::Utc;
 use csv::Writer;
 use log::{error, info, warn};
@@ -8,427 +24,4378 @@ use relative_path::RelativePath;
  * Eventually, this module will also be used to separate pipeline executions and handle conditional logic
  * May also be split into modules on an action/pipeline level in the future
  */
-use std::fs::File;
-use std::io::Write;
-use std::process::{Command, Output, Stdio};
-use std::time::SystemTime;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, env::current_dir};
 
-/// Small wrapper used to gather output of multiple actions and run actions programatically
-pub fn exec_actions(action_vec: &Vec<Action>) -> Vec<Vec<String>> {
-    let mut all_output = vec![];
-    for action in action_vec {
-        all_output.push(exec_action(action))
-    }
-    // println!("All output: {:#?}", &all_output);
-    all_output
+/// Options that change how actions are run
+///
+/// The default options match the behavior of [`exec_actions`].
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    /// Whether the run stops as soon as an action fails in a way that fails the run as a whole.
+    ///
+    /// When false, every action runs regardless of the failures before it, unless [`ExecOptions::max_failures`] is set.
+    pub fail_fast: bool,
+    /// How many actions may fail in a way that fails the run as a whole before no more actions are started. Failures
+    /// are counted across every pipeline, including those running at the same time, and actions that are already
+    /// running still finish. None, or 0, lets every action run.
+    pub max_failures: Option<usize>,
+    /// How many actions have failed so far, which [`ExecOptions::max_failures`] is checked against. It is cleared
+    /// whenever [`exec_config`] starts a run
+    pub failures: FailureCount,
+    /// When the base image of a docker action is pulled before the action is built, unless the action sets its own
+    /// `pull_policy`
+    pub pull: PullPolicy,
+    /// How the output of each step is printed
+    pub ci: CiFormat,
+    /// Whether the standard error of every step is captured along with its standard output, as if every action set
+    /// `merge_output`
+    pub merge_output: bool,
+    /// The most pipelines that may run at the same time. Values of 0 and 1 both run one pipeline at a time.
+    ///
+    /// The actions within a pipeline always run one at a time, in order.
+    pub jobs: usize,
+    /// The most weight that the pipelines running at the same time may add up to, where the weight of a pipeline is
+    /// the largest `weight` of its actions. None uses [`ExecOptions::jobs`] as the budget, which with the default weight
+    /// of 1 limits only the number of pipelines.
+    ///
+    /// A pipeline that is heavier than the whole budget still runs, but only once no other pipeline is running.
+    pub resource_budget: Option<usize>,
+    /// How long a step may run before it is killed, unless its action sets its own `step_timeout`. None, or a duration
+    /// of zero, lets steps run for as long as they need.
+    pub step_timeout: Option<Duration>,
+    /// How long a pipeline waits for the pipelines and actions it requires to run before it fails instead, unless it
+    /// sets its own `requires_timeout`. None, or a duration of zero, lets pipelines wait for as long as they need.
+    pub requires_timeout: Option<Duration>,
+    /// Where an [`Event`] is sent whenever an action or step starts, writes output, or finishes, if anywhere. It is
+    /// called after every reporter within [`ExecOptions::reporters`]
+    pub events: Option<EventSender>,
+    /// The [`crate::reporters::Reporter`]s that are called as actions and steps start and finish, and once the run
+    /// has finished
+    pub reporters: Reporters,
+    /// Cancels the run when cancelled, killing any running step and returning [`CustomError::Cancelled`]
+    pub cancel: Option<CancelToken>,
+    /// The directory docker metrics are written to, unless an action sets its own `metrics_dir`. None writes them to
+    /// `./metrics`
+    pub metrics_dir: Option<String>,
+    /// The most bytes of each stream of a step's output that are kept. Anything past it is read and dropped, so the step
+    /// still runs to completion. None keeps [`DEFAULT_MAX_OUTPUT_BYTES`], and 0 keeps everything.
+    pub max_output_bytes: Option<usize>,
+    /// Whether pipelines and steps run one at a time, regardless of [`ExecOptions::jobs`] and the `concurrency` of each
+    /// action, so that a run writes its output and events in the same order every time it is run
+    pub deterministic: bool,
+    /// The standard output of every step that has run, which `output_contains` conditions look up. It is cleared
+    /// whenever [`exec_config`] starts a run
+    pub step_outputs: StepOutputs,
+    /// Whether a failing step logs the command it was run with, its working directory, exit code, environment, and
+    /// standard error together as an error. Environment variables whose names suggest they hold secrets are masked
+    pub verbose_errors: bool,
+    /// Whether docker actions are rebuilt even if none of their `cache_key_files` changed since their image was last
+    /// built
+    pub no_cache: bool,
+    /// Steps that run before every action that is not skipped. [`exec_config`] uses the `before_each` of the
+    /// configuration instead, if it sets any
+    pub before_each: Vec<Step>,
+    /// Steps that run after every action that is not skipped, even if the action or its `before_each` failed.
+    /// [`exec_config`] uses the `after_each` of the configuration instead, if it sets any
+    pub after_each: Vec<Step>,
+    /// Whether the peak memory and CPU time of every step is measured and recorded within its [`StepResult`]. This is
+    /// only supported on unix, see [`resources::supported`]; elsewhere, steps record no resources
+    pub profile_resources: bool,
 }
 
-/// Determines how to perform steps defined by an Action
-fn exec_action(action: &Action) -> Vec<String> {
-    let exec_info = ExecInfo::new(action);
-    match exec_info.backend.to_lowercase().as_str() {
-        "bash" => run_bash_scripts(&exec_info),
-        "batch" => run_batch_script(&exec_info),
-        "bat" => run_batch_script(&exec_info),
-        "docker" => run_with_docker(exec_info),
-        &_ => {
-            panic!("Specified backend not supported");
+impl ExecOptions {
+    /// Returns [`ExecOptions::reporters`], followed by [`ExecOptions::events`] if anyone is following the events
+    pub fn all_reporters(&self) -> Reporters {
+        let mut reporters = self.reporters.clone();
+        if let Some(events) = &self.events {
+            reporters.attach(Arc::new(events.clone()));
         }
+        reporters
     }
 }
 
-fn generate_dockerfile(info: &ExecInfo) -> File {
-    let mut file = File::create(format!("{}/Dockerfile", info.source)).unwrap_or_else(|_| {
-            error!("There was an issue creating a dockerfile for your docker backend.\nMake sure there are no files in your project named \"DOCKERFILE\".");
-            panic!("There was an issue regarding your dockerfile. Please check your logs for more information.");
-        }
-    );
-    let mut str = format_args!("FROM {}\r\n", info.image.as_ref().unwrap()).to_string();
-    str += "WORKDIR /cider/app\r\n";
-    str += "COPY . ./\r\n";
-    for step in info.manual.iter() {
-        str += format_args!("RUN {}\r\n", step.get_script())
-            .to_string()
-            .as_ref();
-    }
+/// Cancels a run that is in progress, from any thread
+///
+/// Clones of a [`CancelToken`] share the same state, so cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
 
-    file.write_fmt(format_args!("{}", str)).unwrap_or_else(|_| {
-        error!("There was an issue creating a dockerfile for your docker backend.\nMake sure there are no files in your project named \"DOCKERFILE\".");
-        panic!("There was an issue regarding your dockerfile. Please check your logs for more information.");
-    });
+impl CancelToken {
+    /// Cancels every run using the [`CancelToken`]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
 
-    file
+    /// Returns whether the [`CancelToken`] has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
 }
 
+/// Counts the actions that failed in a way that fails the run, from any thread, see [`ExecOptions::max_failures`]
+///
+/// Clones of a [`FailureCount`] share the same count.
+#[derive(Debug, Clone, Default)]
+pub struct FailureCount(Arc<AtomicUsize>);
 
-fn run_batch_script(setup: &ExecInfo) -> Vec<String> {
-    let mut outputs = vec![];
-    if cfg!(windows) {
-        for step in &setup.manual {
-            let mut command = Command::new("cmd");
-            let mut script = script_setup(&mut outputs, step);
-            let output = command_setup_windows(&mut command, &mut script, false)
-                .output()
-                .expect(&("Failed to execute: ".to_string() + &script.concat()));
-            collect_piped_output(step, &output, &mut outputs);
-        }
-        return outputs;
-    } else {
-        error!("As of now, running batch scripts is unsupported on non-windows systems.");
-        outputs.push(
-            "A batch script was unable to be processed on Linux and was taken care of accordingly."
-                .to_string(),
-        );
+impl FailureCount {
+    /// Records that another action failed, returning how many actions have failed so far
+    pub fn record(&self) -> usize {
+        self.0.fetch_add(1, Ordering::SeqCst) + 1
     }
-    outputs
-}
 
-fn run_with_docker(setup: ExecInfo) -> Vec<String> {
-    let mut setup = setup;
-    let mut outputs = vec![];
-    image_setup(&mut setup, &mut outputs);
-    generate_dockerfile(&setup);
+    /// Returns how many actions have failed so far
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Forgets every failure, such as before a new run starts
+    pub fn clear(&self) {
+        self.0.store(0, Ordering::SeqCst);
+    }
+}
 
-    let csv_headers = vec!["Image_pull_time", "Image_remove_time", "Image_build_time"];
-    let mut csv_data: Vec<&str> = vec![];
+/// Decides how the output of each step is printed, so that it can be understood by the CI system cider runs within
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CiFormat {
+    /// Print step output as-is
+    #[default]
+    Plain,
+    /// Wrap each step's output in GitHub Actions log groups, and print failures as GitHub Actions error annotations
+    Github,
+}
 
-    if cfg!(windows) {
+pub use crate::utils::config::PullPolicy;
 
-        let log_time = Utc::now().format("%d-%m_%H%M%S");
-        let log_file = "./metrics/win/".to_string() + log_time.to_string().as_str() + ".csv";
-        let mut csv_wtr = Writer::from_path(log_file).unwrap_or_else(|err| {
-            error!("{}", err);
-            panic!("{}", err);
-        });
+/// Small wrapper used to gather output of multiple actions and run actions programatically
+///
+/// Each action's output is recorded alongside the action and pipeline it came from, see [`RunReport`]. A failing step
+/// is recorded within the [`RunReport`], while an error is only returned when CIder itself could not run an action,
+/// such as when a command could not be started.
+pub fn exec_actions(action_vec: &[Action]) -> Result<RunReport, CustomError> {
+    exec_actions_with_options(action_vec, &ExecOptions::default())
+}
 
-        let image_pull_time = SystemTime::now();
-        let mut cmd = Command::new("cmd");
-        let mut process = docker_setup_windows(&mut cmd, &setup, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            error!("{:#?}", err);
-            panic!("{:#?}", err);
-        });
-        info!("{:#?}", &image_pull_time.elapsed().unwrap());
-
-        let image_pull_string = format!("{:?}", image_pull_time.elapsed().unwrap());
-        csv_data.push(&image_pull_string);
-
-        let image_rm_time = SystemTime::now();
-        let mut cmd = Command::new("cmd");
-        let mut process = docker_clean_windows(&mut cmd, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            error!("{:#?}", err);
-            panic!("{:#?}", err);
-        });
-        info!("{:#?}", image_rm_time.elapsed().unwrap());
-
-        let image_rm_string = format!("{:?}", image_rm_time.elapsed().unwrap());
-        csv_data.push(&image_rm_string);
-
-        let image_build_time = SystemTime::now();
-        let mut cmd = Command::new("cmd");
-        let mut process = docker_build_windows(&mut cmd, &setup, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            error!("{:#?}", err);
-            panic!("{:#?}", err);
-        });
-        info!("{:#?}", image_build_time.elapsed().unwrap());
+/// Runs multiple actions according to `options`
+///
+/// See [`exec_actions`] for more information.
+pub fn exec_actions_with_options(
+    action_vec: &[Action],
+    options: &ExecOptions,
+) -> Result<RunReport, CustomError> {
+    run_actions(action_vec, RunReport::default(), options, |_| {})
+}
 
-        let image_build_string = format!("{:?}", image_build_time.elapsed().unwrap());
-        csv_data.push(&image_build_string);
+/// Runs every action within a [`TopLevelConfiguration`], including those belonging to its pipelines
+///
+/// Unlike [`exec_actions`], the returned [`RunReport`] knows which pipelines are allowed to fail, so a failing action
+/// within one of them does not fail the run as a whole.
+///
+/// Top-level actions run first. Pipelines then run once every pipeline and action they require has run, with up to
+/// [`ExecOptions::jobs`] pipelines running at the same time. Results are always reported in the order pipelines are
+/// defined, regardless of the order they finished in. Returns an error before running anything if pipelines require
+/// each other or require a pipeline or action that does not exist.
+pub fn exec_config(
+    config: &TopLevelConfiguration,
+    options: &ExecOptions,
+) -> Result<RunReport, CustomError> {
+    let pipelines = config.get_pipelines();
+    let requirements = resolve_requires(pipelines)?;
+    let options = &with_hooks(config, options);
+    options.step_outputs.clear();
+    options.failures.clear();
+    let mut report = RunReport::default();
+    for pipeline in pipelines {
+        if *pipeline.pipeline_config.get_allowed_failure() {
+            report
+                .allowed_failure_pipelines
+                .push(pipeline.shared_config.title().unwrap_or_default().to_string());
+        }
+    }
+    let mut report = run_actions(config.get_actions(), report, options, |_| {})?;
+    if (!options.fail_fast || report.succeeded()) && !too_many_failures(options) {
+        for pipeline_report in run_pipelines(pipelines, &requirements, &report, options) {
+            report.actions.append(&mut pipeline_report?.actions);
+        }
+    }
+    options.all_reporters().run_finished(&report);
+    Ok(report)
+}
 
-        csv_wtr.write_record(&csv_headers).unwrap();
-        csv_wtr.write_record(&csv_data).unwrap();
-        csv_wtr.flush().unwrap();
+/// Returns `options` with the `before_each` and `after_each` of `config`, for those that the configuration sets
+fn with_hooks(config: &TopLevelConfiguration, options: &ExecOptions) -> ExecOptions {
+    let mut options = options.clone();
+    if !config.get_before_each().is_empty() {
+        options.before_each = config.get_before_each().clone();
+    }
+    if !config.get_after_each().is_empty() {
+        options.after_each = config.get_after_each().clone();
+    }
+    options
+}
 
-    } else {
-        let mut cmd = Command::new("sh");
-        let mut process = docker_setup_unix(&mut cmd, &setup, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            panic!("{:#?}", err);
-        });
-        let mut cmd = Command::new("sh");
-        let mut process = docker_clean_unix(&mut cmd, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            panic!("{:#?}", err);
-        });
-        let mut cmd = Command::new("sh");
-        let mut process = docker_build_unix(&mut cmd, &setup, true)
-            .spawn()
-            .expect("There was an error building your docker environment.");
-        process.wait().unwrap_or_else(|err| {
-            panic!("{:#?}", err);
+/// Runs every `post_run` action of a [`TopLevelConfiguration`], such as cleanup, returning the results of each of them
+///
+/// Meant to be called once [`exec_config`] has returned, whether the run succeeded, failed, or returned an error. Every
+/// action runs regardless of the failures before it, and runs even if the run was cancelled. An action that CIder is
+/// unable to run is logged and recorded as a failure rather than stopping the others. The `before_each` and
+/// `after_each` steps do not run around `post_run` actions.
+pub fn exec_post_run(config: &TopLevelConfiguration, options: &ExecOptions) -> RunReport {
+    let options = ExecOptions {
+        fail_fast: false,
+        cancel: None,
+        before_each: vec![],
+        after_each: vec![],
+        ..options.clone()
+    };
+    let mut report = RunReport::default();
+    for action in config.get_post_run() {
+        let title = action.shared_config.title().unwrap_or_default();
+        info!("Running post_run action {}", title);
+        let result = exec_action(action, &options).unwrap_or_else(|err| {
+            error!("The post_run action {} could not be run: {}", title, err);
+            ActionResult::new(action, vec![err.to_string()], Status::Failure)
         });
+        if result.status.is_fatal() {
+            error!("The post_run action {} failed", title);
+        }
+        report.actions.push(result);
     }
+    report
+}
 
-    outputs
+/// Checks that the `requires` of every pipeline can be satisfied
+///
+/// Returns an error if a pipeline requires a pipeline or action that does not exist, or if pipelines require each
+/// other.
+pub fn check_requires(config: &TopLevelConfiguration) -> Result<(), CustomError> {
+    resolve_requires(config.get_pipelines()).map(|_| ())
 }
 
-///Runs bash scripts defined in an Action's Manual
-fn run_bash_scripts(setup: &ExecInfo) -> Vec<String> {
-    let mut outputs = vec![];
+/// A pipeline or action that a pipeline waits on before it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Requirement {
+    /// Every action of the pipeline at this index has to have run
+    Pipeline(usize),
+    /// The action at index `action` of the pipeline at index `pipeline` has to have run
+    Action { pipeline: usize, action: usize },
+}
 
-    if cfg!(windows) {
-        warn!("In order to avoid unexpected behavior, please consider using \"bat\" or \"batch\" backend for windows operating systems.");
-        for step in &setup.manual {
-            let mut command = Command::new("cmd");
-            let mut script = script_setup(&mut outputs, step);
-            let output = command_setup_windows(&mut command, &mut script, false).current_dir(&setup.source)
-                .output()
-                .expect(&("Failed to execute: ".to_string() + &script.concat()));
-            collect_piped_output(step, &output, &mut outputs);
+impl Requirement {
+    /// Returns the index of the pipeline the requirement belongs to
+    fn pipeline(&self) -> usize {
+        match self {
+            Requirement::Pipeline(pipeline) | Requirement::Action { pipeline, .. } => *pipeline,
         }
-        outputs
-    } else {
-        for step in &setup.manual {
-            let mut command = Command::new("sh");
-            let mut script = script_setup(&mut outputs, step);
-            let output = command_setup_unix(&mut command, &mut script, false)
-                .output()
-                .expect(&("Failed to execute: ".to_string() + &script.concat()));
-            collect_piped_output(step, &output, &mut outputs)
+    }
+
+    /// Returns whether the requirement has been met, given the results and number of finished actions of each pipeline
+    fn is_met(&self, results: &[Option<Result<RunReport, CustomError>>], progress: &[usize]) -> bool {
+        match self {
+            Requirement::Pipeline(pipeline) => results[*pipeline].is_some(),
+            Requirement::Action { pipeline, action } => {
+                results[*pipeline].is_some() || progress[*pipeline] > *action
+            }
         }
-        outputs
     }
 }
 
-/// Cleans paths used within scripts.
-/// TODO: Fix paths being "overcleaned" i.e. directory/"some other directory"/low_dir being split incorrectly
-/// TODO: Fix paths being incorrectly parsed (FIX options: split by OS or split into multiple functions.)
+/// Returns what each pipeline requires
 ///
-fn clean_script_pathing(script: &str) -> Vec<String> {
-    let root = current_dir().unwrap();
-    script
-        .split(' ')
-        .map(|item| {
-            if item.contains("../") || item.contains("./") {
-                RelativePath::new(&item)
-                    .to_path(&root)
-                    .to_str()
-                    .unwrap()
-                    .to_string()
-            } else {
-                item.to_string()
+/// A requirement is either the title of a pipeline, or a pipeline and action title separated by a `.`, such as
+/// `Build_Pipeline.Build`. Titles of pipelines are matched first, so a pipeline title containing a `.` can still be
+/// required as a whole.
+fn resolve_requires(pipelines: &[Pipeline]) -> Result<Vec<Vec<Requirement>>, CustomError> {
+    let titles = pipelines
+        .iter()
+        .map(|pipeline| pipeline.shared_config.title().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    let mut requirements = vec![];
+    for (pipeline, title) in pipelines.iter().zip(&titles) {
+        let mut required = vec![];
+        for requirement in pipeline.pipeline_config.get_requires() {
+            match resolve_requirement(requirement, pipelines, &titles) {
+                Some(resolved) => required.push(resolved),
+                None => {
+                    return Err(CustomError::UnknownRequirement {
+                        pipeline: title.to_string(),
+                        requirement: requirement.to_string(),
+                    })
+                }
             }
-        })
-        .collect()
+        }
+        requirements.push(required);
+    }
+    let graph = requirements
+        .iter()
+        .map(|required| required.iter().map(Requirement::pipeline).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if let Some(cycle) = find_cycle(&graph) {
+        return Err(CustomError::CyclicRequirement(
+            cycle.into_iter().map(|index| titles[index].clone()).collect(),
+        ));
+    }
+    Ok(requirements)
 }
 
-/// Contains data necessary to perform specific actions in a configurable manner
-/// Combines information from both [`crate::utils::config::ShareableConfiguration`] and [`crate::utils::config::ActionConfig`]
-/// See [`crate::utils::config`] for more information.
-#[derive(Debug)]
-pub struct ExecInfo {
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub backend: String,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub image: Option<String>,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub title: Option<String>,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub tags: Option<HashMap<String, String>>,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub metadata: Option<HashMap<String, String>>,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub output: String,
-    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
-    pub source: String,
-    /// See [`crate::utils::config::ActionConfig`] for more information.
-    pub conditions: Option<Vec<Condition>>,
-    /// See [`crate::utils::config::ActionConfig`] for more information.
-    pub manual: Vec<Step>,
-    /// See [`crate::utils::config::ActionConfig`] for more information.
-    pub retries: i8,
-    /// See [`crate::utils::config::ActionConfig`] for more information.
-    pub allowed_failure: bool,
+/// Finds the pipeline, or the action within a pipeline, that `requirement` refers to
+fn resolve_requirement(
+    requirement: &str,
+    pipelines: &[Pipeline],
+    titles: &[String],
+) -> Option<Requirement> {
+    if let Some(index) = titles.iter().position(|title| title == requirement) {
+        return Some(Requirement::Pipeline(index));
+    }
+    let (pipeline_title, action_title) = requirement.rsplit_once('.')?;
+    let pipeline = titles.iter().position(|title| title == pipeline_title)?;
+    let action = pipelines[pipeline]
+        .pipeline_config
+        .get_actions()
+        .iter()
+        .position(|action| action.shared_config.title() == Some(action_title))?;
+    Some(Requirement::Action { pipeline, action })
 }
 
-/**
- * Functions to be used by the ExecInfo struct.
- * Should only contain a constructor and/or cleanup scripts.
- */
-impl ExecInfo {
-    fn new(action: &Action) -> Self {
-        ExecInfo {
-            backend: action.shared_config.get_backend().to_string(),
-            image: action.shared_config.get_image(),
-            title: action.shared_config.get_title(),
-            tags: action.shared_config.get_tags(),
-            metadata: action.shared_config.get_metadata(),
-            output: action.shared_config.get_output().to_string(),
-            source: action.shared_config.get_source().to_string(),
-            conditions: action.action_config.get_conditions(),
-            manual: action.action_config.get_manual().to_vec(),
-            retries: *action.action_config.get_retries(),
-            allowed_failure: *action.action_config.get_allowed_failure(),
+/// Returns a cycle within `requirements`, starting and ending with the same index, if there is one
+fn find_cycle(requirements: &[Vec<usize>]) -> Option<Vec<usize>> {
+    fn visit(
+        index: usize,
+        requirements: &[Vec<usize>],
+        path: &mut Vec<usize>,
+        finished: &mut Vec<bool>,
+    ) -> Option<Vec<usize>> {
+        if let Some(start) = path.iter().position(|visited| *visited == index) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(index);
+            return Some(cycle);
+        }
+        if finished[index] {
+            return None;
         }
+        path.push(index);
+        for required in &requirements[index] {
+            if let Some(cycle) = visit(*required, requirements, path, finished) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        finished[index] = true;
+        None
     }
+
+    let mut finished = vec![false; requirements.len()];
+    (0..requirements.len())
+        .find_map(|index| visit(index, requirements, &mut vec![], &mut finished))
 }
 
-fn command_setup_windows<'a>(
-    cmd: &'a mut Command,
-    args: &mut Vec<String>,
-    inherit: bool,
-) -> &'a mut Command {
-    //pass command first?
+/// Decides when pipelines may start, so that neither the number of running pipelines nor their combined weight goes
+/// over its limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Scheduler {
+    jobs: usize,
+    budget: usize,
+    weights: Vec<usize>,
+    running: Vec<usize>,
+}
 
-    args.insert(0, "/C".to_string());
-    if inherit {
-        return set_output_inherit(cmd.args(args).current_dir(current_dir().unwrap()));
+impl Scheduler {
+    /// Creates a [`Scheduler`] for pipelines of the given weights
+    fn new(jobs: usize, budget: Option<usize>, weights: Vec<usize>) -> Self {
+        let jobs = jobs.max(1);
+        Scheduler {
+            jobs,
+            budget: budget.unwrap_or(jobs),
+            weights,
+            running: vec![],
+        }
     }
-    set_output_piped(cmd.args(args).current_dir(current_dir().unwrap()))
-}
 
-fn image_setup(setup: &mut ExecInfo, outputs: &mut Vec<String>) {
-    if setup.image.is_none() {
-        setup.image = Some("alpine:latest".to_string());
-        warn!("There was no image detected in a configured action.");
-        outputs.push(
-            "There was no docker image found to build off of. Using Alpine Linux by default."
-                .to_string(),
-        );
+    /// Returns the combined weight of the running pipelines
+    fn in_flight(&self) -> usize {
+        self.running.iter().map(|index| self.weights[*index]).sum()
     }
-}
 
-fn docker_setup_unix<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
-    cmd.arg("-c")
-        .arg(format_args!("docker pull {}", &info.image.clone().unwrap()).to_string().as_str()).current_dir(&info.source);
-    if inherit {
-        return set_output_inherit(cmd);
+    /// Returns whether the pipeline at `index` may start now
+    ///
+    /// A pipeline heavier than the whole budget may only start when nothing else is running, so that it is never
+    /// blocked forever.
+    fn can_start(&self, index: usize) -> bool {
+        self.running.len() < self.jobs
+            && (self.running.is_empty() || self.in_flight() + self.weights[index] <= self.budget)
     }
-    set_output_piped(cmd)
-}
 
-fn docker_setup_windows<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
-    cmd.args(vec!["/C", "docker", "pull", &info.image.clone().unwrap()])
-        .current_dir(&info.source);
-    if inherit {
-        return set_output_inherit(cmd);
+    /// Records that the pipeline at `index` started
+    fn start(&mut self, index: usize) {
+        self.running.push(index);
     }
-    set_output_piped(cmd)
-}
 
-fn docker_clean_unix(cmd: &mut Command, inherit: bool) -> &mut Command {
-    cmd.arg("-c").arg("docker image rm -f cider-image");
-    if inherit {
-        return set_output_inherit(cmd);
+    /// Records that the pipeline at `index` finished
+    fn finish(&mut self, index: usize) {
+        self.running.retain(|running| *running != index);
     }
-    set_output_piped(cmd)
-}
 
-fn docker_clean_windows(cmd: &mut Command, inherit: bool) -> &mut Command {
-    cmd.args(vec!["/C", "docker", "image", "rm", "-f", "cider-image"]);
-    if inherit {
-        return set_output_inherit(cmd);
+    /// Returns whether any pipeline is running
+    fn is_idle(&self) -> bool {
+        self.running.is_empty()
     }
-    set_output_piped(cmd)
 }
 
-fn docker_build_unix<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
-    cmd.arg("-c").arg("docker build -t cider-image .").current_dir(&info.source);
-    if inherit {
-        return set_output_inherit(cmd);
-    }
-    set_output_piped(cmd)
+/// Returns the weight of a pipeline, which is the largest weight of its actions, as they run one at a time
+fn pipeline_weight(pipeline: &Pipeline) -> usize {
+    pipeline
+        .pipeline_config
+        .get_actions()
+        .iter()
+        .map(|action| action.action_config.get_weight())
+        .max()
+        .unwrap_or(0)
 }
 
-fn docker_build_windows<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
-    cmd.args(["/C", "docker", "build", "-t", "cider-image", "."]).current_dir(&info.source);
-    if inherit {
-        return set_output_inherit(cmd);
-    }
-    set_output_piped(cmd)
+/// Returns how long a pipeline waits for its requires to be met, if it may not wait for as long as it needs
+fn requires_timeout(pipeline: &Pipeline, options: &ExecOptions) -> Option<Duration> {
+    pipeline
+        .pipeline_config
+        .get_requires_timeout()
+        .or(options.requires_timeout)
+        .filter(|timeout| !timeout.is_zero())
 }
 
-fn command_setup_unix<'a>(
-    cmd: &'a mut Command,
-    args: &mut Vec<String>,
-    inherit: bool,
-) -> &'a mut Command {
-    let mut arg_string = String::new();
-    for arg in args {
-        arg_string += &(arg.to_owned() + " ");
+/// Returns the title of what `requirement` refers to, as it would be written within `requires`
+fn requirement_title(requirement: &Requirement, pipelines: &[Pipeline]) -> String {
+    match requirement {
+        Requirement::Pipeline(pipeline) => pipelines[*pipeline]
+            .shared_config
+            .title()
+            .unwrap_or_default()
+            .to_string(),
+        Requirement::Action { pipeline, action } => format!(
+            "{}.{}",
+            pipelines[*pipeline].shared_config.title().unwrap_or_default(),
+            pipelines[*pipeline].pipeline_config.get_actions()[*action]
+                .shared_config
+                .title()
+                .unwrap_or_default()
+        ),
     }
+}
 
-    arg_string = arg_string.trim().to_string();
-    if inherit {
-        return set_output_inherit(cmd.arg("-c").arg(arg_string));
+/// Returns the results of a pipeline that gave up waiting for its requires, which fail every one of its actions
+fn requires_timed_out(
+    pipeline: &Pipeline,
+    mut report: RunReport,
+    message: &str,
+    options: &ExecOptions,
+) -> RunReport {
+    for action in pipeline.pipeline_config.get_actions() {
+        let result = ActionResult::new(action, vec![message.to_string()], Status::Failure)
+            .with_reasons(vec![message.to_string()]);
+        push_result(&mut report, result, options);
     }
-    return set_output_piped(cmd.arg("-c").arg(arg_string));
+    report
 }
 
-/// Potential issues:
-/// Some success outputs may be read as failures on Linux environments. Look into this more.
-fn collect_piped_output(step: &Step, output: &Output, outputs: &mut Vec<String>) {
-    let stdout = String::from_utf8(output.stdout.clone())
-        .expect("Could not parse command output as a String.");
-    let stderr = String::from_utf8(output.stderr.clone())
-        .expect("Could not parse command output as a String.");
-
-    println!("stdout from {}: {stdout}", step.get_name());
-    println!("stderr from {}: {stderr}", step.get_name());
+/// Reported by a running pipeline to [`run_pipelines`]
+enum PipelineProgress {
+    /// The pipeline at this index finished running one of its actions
+    Action(usize),
+    /// The pipeline at this index finished running
+    Finished(usize, Result<RunReport, CustomError>),
+}
 
-    outputs.push(if stdout.is_empty() {
-        if stderr.is_empty() {
-            "No standard output detected. Check to see if it was piped to another file.".to_string()
-        } else {
-            error!("Standard output from step {}: {}", step.get_name(), stderr);
-            stderr
+/// Runs pipelines once the pipelines and actions they require have run, returning the result of each pipeline in the
+/// order given
+///
+/// Pipelines that were never started, as fail fast or [`ExecOptions::max_failures`] stopped the run, return an empty
+/// report. A pipeline that waits for
+/// its requires for longer than its `requires_timeout` is failed without being started. It only starts waiting once
+/// every pipeline it is waiting for has started, as until then it is queued behind them rather than blocked on them.
+fn run_pipelines(
+    pipelines: &[Pipeline],
+    requirements: &[Vec<Requirement>],
+    report: &RunReport,
+    options: &ExecOptions,
+) -> Vec<Result<RunReport, CustomError>> {
+    let mut scheduler = Scheduler::new(
+        if options.deterministic { 1 } else { options.jobs },
+        options.resource_budget,
+        pipelines.iter().map(pipeline_weight).collect(),
+    );
+    let mut results = (0..pipelines.len()).map(|_| None).collect::<Vec<_>>();
+    let mut progress = vec![0; pipelines.len()];
+    let mut started = vec![false; pipelines.len()];
+    let mut stopped = false;
+    let mut waiting_since: Vec<Option<Instant>> = vec![None; pipelines.len()];
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        loop {
+            let mut timed_out = false;
+            // Another pipeline may have reached the most failures allowed while this one was waiting
+            stopped = stopped || too_many_failures(options);
+            for (index, pipeline) in pipelines.iter().enumerate() {
+                if stopped || is_cancelled(options) || started[index] {
+                    continue;
+                }
+                let unmet = requirements[index]
+                    .iter()
+                    .find(|required| !required.is_met(&results, &progress));
+                if let Some(unmet) = unmet {
+                    let timeout = requires_timeout(pipeline, options);
+                    let waited =
+                        waiting_since[index].map_or(Duration::ZERO, |since| since.elapsed());
+                    if let Some(timeout) = timeout.filter(|timeout| waited >= *timeout) {
+                        let message = format!(
+                            "Pipeline {} failed, as dependency {} did not complete within {:?}",
+                            pipeline.shared_config.title().unwrap_or_default(),
+                            requirement_title(unmet, pipelines),
+                            timeout
+                        );
+                        error!("{}", message);
+                        println!("{}", message);
+                        let pipeline_report = RunReport {
+                            allowed_failure_pipelines: report.allowed_failure_pipelines.clone(),
+                            ..Default::default()
+                        };
+                        started[index] = true;
+                        let failed =
+                            requires_timed_out(pipeline, pipeline_report, &message, options);
+                        results[index] = Some(Ok(failed));
+                        stopped = too_many_failures(options);
+                        timed_out = true;
+                    }
+                    continue;
+                }
+                if !scheduler.can_start(index) {
+                    continue;
+                }
+                started[index] = true;
+                scheduler.start(index);
+                let sender = sender.clone();
+                let pipeline_report = RunReport {
+                    allowed_failure_pipelines: report.allowed_failure_pipelines.clone(),
+                    ..Default::default()
+                };
+                scope.spawn(move || {
+                    let result = run_actions(
+                        pipeline.pipeline_config.get_actions(),
+                        pipeline_report,
+                        options,
+                        |_| {
+                            sender.send(PipelineProgress::Action(index)).ok();
+                        },
+                    );
+                    sender.send(PipelineProgress::Finished(index, result)).ok();
+                });
+            }
+            // The wait of a pipeline starts once everything it is still waiting for has started
+            for (index, since) in waiting_since.iter_mut().enumerate() {
+                let mut unmet = requirements[index]
+                    .iter()
+                    .filter(|required| !required.is_met(&results, &progress))
+                    .peekable();
+                if since.is_none()
+                    && !started[index]
+                    && unmet.peek().is_some()
+                    && unmet.all(|required| started[required.pipeline()])
+                {
+                    *since = Some(Instant::now());
+                }
+            }
+            // A pipeline that gave up waiting counts as finished, so the pipelines requiring it may be ready
+            if timed_out {
+                continue;
+            }
+            if scheduler.is_idle() {
+                break;
+            }
+            let waiting = |index: usize| {
+                !stopped
+                    && !started[index]
+                    && !requirements[index]
+                        .iter()
+                        .all(|required| required.is_met(&results, &progress))
+            };
+            let next_timeout = pipelines
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| waiting(*index))
+                .filter_map(|(index, pipeline)| {
+                    let since = waiting_since[index]?;
+                    Some(requires_timeout(pipeline, options)?.saturating_sub(since.elapsed()))
+                })
+                .min();
+            let progress_report = match next_timeout {
+                Some(timeout) => {
+                    match receiver.recv_timeout(timeout) {
+                        Ok(progress_report) => progress_report,
+                        Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => {
+                            panic!("A pipeline stopped without reporting its result.")
+                        }
+                    }
+                }
+                None => receiver.recv().expect("A pipeline stopped without reporting its result."),
+            };
+            let (index, result) = match progress_report {
+                PipelineProgress::Action(index) => {
+                    progress[index] += 1;
+                    continue;
+                }
+                PipelineProgress::Finished(index, result) => (index, result),
+            };
+            scheduler.finish(index);
+            if options.fail_fast && result.as_ref().map_or(true, |report| !report.succeeded()) {
+                warn!("Stopping the run after a failure, as fail fast is enabled. Pipelines that have not started will not be run.");
+                stopped = true;
+            }
+            results[index] = Some(result);
         }
-    } else {
-        info!("Standard output from step {}: {}", step.get_name(), stdout);
-        stdout
     });
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Ok(RunReport::default())))
+        .collect()
 }
 
-fn set_output_inherit(command: &mut Command) -> &mut Command {
-    command.stdout(Stdio::inherit()).stderr(Stdio::inherit())
+/// Runs actions in order, adding their results to `report`
+///
+/// `on_action` is called with the index of each action once it has run.
+fn run_actions(
+    action_vec: &[Action],
+    mut report: RunReport,
+    options: &ExecOptions,
+    mut on_action: impl FnMut(usize),
+) -> Result<RunReport, CustomError> {
+    for (index, action) in action_vec.iter().enumerate() {
+        if is_cancelled(options) {
+            return Err(CustomError::Cancelled);
+        }
+        if too_many_failures(options) {
+            break;
+        }
+        let result = exec_action(action, options)?;
+        push_result(&mut report, result, options);
+        if is_cancelled(options) {
+            return Err(CustomError::Cancelled);
+        }
+        on_action(index);
+        if options.fail_fast && !report.succeeded() {
+            let skipped = action_vec.len() - report.actions.len();
+            warn!(
+                "Stopping the run after a failure, as fail fast is enabled. {} action(s) will not be run.",
+                skipped
+            );
+            break;
+        }
+    }
+    Ok(report)
 }
 
-fn set_output_piped(command: &mut Command) -> &mut Command {
-    command.stdout(Stdio::piped()).stderr(Stdio::piped())
+/// Adds the result of an action to `report`, recording it as a failure unless its pipeline is allowed to fail
+fn push_result(report: &mut RunReport, result: ActionResult, options: &ExecOptions) {
+    let allowed = result
+        .pipeline
+        .as_ref()
+        .is_some_and(|pipeline| report.allowed_failure_pipelines.contains(pipeline));
+    if result.status.is_fatal() && !allowed {
+        record_failure(options);
+    }
+    report.actions.push(result);
 }
 
-fn script_setup(outputs: &mut Vec<String>, step: &Step) -> Vec<String> {
-    let output_str = format_args!("Running {}", step.get_name()).to_string();
-    info!("{}", output_str);
-    println!("{}", output_str);
-    outputs.push(output_str);
-    let script = step.get_script().to_string();
-    println!("{script}");
-    clean_script_pathing(&script)
+/// Records that an action failed the run, warning once [`ExecOptions::max_failures`] actions have failed
+fn record_failure(options: &ExecOptions) {
+    let failures = options.failures.record();
+    if options.max_failures == Some(failures) {
+        warn!("Stopping the run after {} failures, as no more are allowed. Actions that have not started will not be run.", failures);
+    }
 }
 
-#[cfg(test)]
-mod tests {
+/// Returns whether [`ExecOptions::max_failures`] actions have failed, so that no more actions may start
+fn too_many_failures(options: &ExecOptions) -> bool {
+    options
+        .max_failures
+        .filter(|max| *max > 0)
+        .is_some_and(|max| options.failures.get() >= max)
+}
 
-    // use crate::parsing::Parser;
+/// The directory docker metrics are written to, unless [`ExecOptions::metrics_dir`] is set
+const DEFAULT_METRICS_DIR: &str = "./metrics";
 
-    // use crate::executor::executor;
+/// The pattern docker metrics files are named with, unless an action sets a `metrics_filename`
+pub const DEFAULT_METRICS_FILENAME: &str = "{date}.csv";
 
-    // #[test]
-    // fn prove_exec_info() {
-    //     let test_config = Parser::new_top_level("example_docker_config.json");
-    //     let actions = test_config.get_all_actions();
-    //     let exec_info = executor::ExecInfo {}
-    // }
+/// The most bytes of each stream of a step's output that are kept, unless [`ExecOptions::max_output_bytes`] is set
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
 
-    #[test]
-    fn create_command_windows() {
-        //
-        let input1 = "input";
-        let input2 = "input";
-        assert!(input1 == input2);
+/// Returns whether the run using `options` has been cancelled
+fn is_cancelled(options: &ExecOptions) -> bool {
+    options
+        .cancel
+        .as_ref()
+        .is_some_and(CancelToken::is_cancelled)
+}
+
+/// Determines how to perform steps defined by an Action
+fn exec_action(action: &Action, options: &ExecOptions) -> Result<ActionResult, CustomError> {
+    let mut exec_info = ExecInfo::new(action);
+    exec_info.ci = options.ci;
+    exec_info.reporters = options.all_reporters();
+    exec_info.cancel = options.cancel.clone();
+    exec_info.step_outputs = options.step_outputs.clone();
+    exec_info.verbose_errors = options.verbose_errors;
+    exec_info.no_cache = options.no_cache;
+    exec_info.profile_resources = options.profile_resources;
+    exec_info.merge_output |= options.merge_output;
+    if exec_info.merge_output && exec_info.fail_on_stderr {
+        warn!(
+            "{} fail_on_stderr has no effect, as the standard error of every step is merged into its standard output",
+            exec_info.log_context(None)
+        );
+    }
+    if exec_info.tty {
+        let ignored = if exec_info.backend.eq_ignore_ascii_case("docker") {
+            Some("the steps of docker actions run while their image is built")
+        } else if !terminal::supported() {
+            Some("steps cannot run within a pseudo-terminal on this platform")
+        } else {
+            None
+        };
+        if let Some(reason) = ignored {
+            warn!("{} tty has no effect, as {}", exec_info.log_context(None), reason);
+            exec_info.tty = false;
+        } else if exec_info.fail_on_stderr {
+            warn!(
+                "{} fail_on_stderr has no effect, as every step writes its standard error to its terminal",
+                exec_info.log_context(None)
+            );
+        }
+    }
+    exec_info.max_output_bytes = options.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+    if options.deterministic {
+        exec_info.concurrency = 1;
+    }
+    exec_info.step_timeout = exec_info
+        .step_timeout
+        .or(options.step_timeout)
+        .filter(|timeout| !timeout.is_zero());
+    let context = exec_info.log_context(None);
+    let started = Instant::now();
+    exec_info.emit(EventKind::ActionStarted, None);
+    if action.action_config.get_skip() {
+        let skipped = "Skipped, as the action is disabled by skip".to_string();
+        info!("{} {}", context, skipped);
+        println!("{}", skipped);
+        exec_info.emit(
+            EventKind::ActionFinished {
+                status: Status::Disabled.to_string(),
+                duration: started.elapsed(),
+            },
+            None,
+        );
+        let reasons = vec!["skip is set, so the action is disabled".to_string()];
+        return Ok(ActionResult::new(action, vec![skipped], Status::Disabled).with_reasons(reasons));
+    }
+    let outcomes = conditions::evaluate_all(
+        exec_info.conditions.as_deref().unwrap_or_default(),
+        &exec_info.step_outputs,
+    );
+    let reasons = outcomes.iter().map(ToString::to_string).collect::<Vec<_>>();
+    if outcomes.last().is_some_and(conditions::ConditionOutcome::blocks) {
+        let skipped = format!("Skipped, as the {}", reasons.last().unwrap());
+        info!("{} {}", context, skipped);
+        println!("{}", skipped);
+        exec_info.emit(
+            EventKind::ActionFinished {
+                status: Status::Skipped.to_string(),
+                duration: started.elapsed(),
+            },
+            None,
+        );
+        return Ok(ActionResult::new(action, vec![skipped], Status::Skipped).with_reasons(reasons));
+    }
+    info!("{} Running with the {} backend", context, exec_info.backend);
+    let mut outputs = vec![];
+    let mut timings = vec![];
+    let mut steps = vec![];
+    if action.shared_config.get_metrics_dir().is_none() {
+        if let Some(metrics_dir) = &options.metrics_dir {
+            exec_info.metrics_dir = metrics_dir.clone();
+        }
+    }
+    let allowed_failure = exec_info.allowed_failure;
+    let hook_info = exec_info.clone();
+    let before = &options.before_each;
+    let before =
+        run_hook(&hook_info, "before_each", before, &mut outputs, &mut timings, &mut steps);
+    let status = match before {
+        Ok(before) if !before.is_fatal() => match exec_info.backend.to_lowercase().as_str() {
+            "bash" => run_bash_scripts(&exec_info, &mut outputs, &mut timings, &mut steps),
+            "batch" => run_batch_script(&exec_info, &mut outputs, &mut timings, &mut steps),
+            "bat" => run_batch_script(&exec_info, &mut outputs, &mut timings, &mut steps),
+            "docker" => run_with_docker(exec_info, options.pull, &mut outputs, &mut timings)
+                .map(|succeeded| Status::new(succeeded, allowed_failure)),
+            &_ => Err(CustomError::UnsupportedBackend(exec_info.backend.clone())),
+        }
+        .map(|status| before.and(status)),
+        before => before,
+    };
+    let after = &options.after_each;
+    let after = run_hook(&hook_info, "after_each", after, &mut outputs, &mut timings, &mut steps);
+    let status = status
+        .and_then(|status| after.map(|after| status.and(after)))
+        .map_err(|err| {
+        error!("{} {}{}", context, err, defined_at(action.get_location()));
+        err
+    })?;
+    info!("{} Finished: {}", context, status);
+    hook_info.emit(
+        EventKind::ActionFinished {
+            status: status.to_string(),
+            duration: started.elapsed(),
+        },
+        None,
+    );
+    Ok(ActionResult::new(action, outputs, status)
+        .with_reasons(reasons)
+        .with_timings(started.elapsed(), timings)
+        .with_steps(steps))
+}
+
+/// Runs the `before_each` or `after_each` steps of the run, named by `hook`, around an action, returning their
+/// combined [`Status`]
+///
+/// The steps run on the machine CIder runs on, even for docker actions, with the batch backend if the action uses it and
+/// bash otherwise. Each step is recorded under the name of the hook followed by its own, such as
+/// `before_each: separator`, and otherwise runs like a step of the action.
+fn run_hook(
+    setup: &ExecInfo,
+    hook: &str,
+    hook_steps: &[Step],
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    steps: &mut Vec<StepResult>,
+) -> Result<Status, CustomError> {
+    if hook_steps.is_empty() {
+        return Ok(Status::Success);
+    }
+    let mut info = setup.clone();
+    info.manual = hook_steps
+        .iter()
+        .map(|step| {
+            let mut step = step.clone();
+            let script = step.get_script().to_string();
+            step.update_script(format!("{}: {}", hook, step.get_name()), script);
+            step
+        })
+        .collect();
+    info.shared_shell = false;
+    info.concurrency = 1;
+    match info.backend.to_lowercase().as_str() {
+        "batch" | "bat" => run_batch_script(&info, outputs, timings, steps),
+        _ => run_bash_scripts(&info, outputs, timings, steps),
+    }
+}
+
+/// Returns the Dockerfile that running `action` with the docker backend builds, without writing or building anything
+///
+/// An action without an image builds from `alpine:latest`, as it does when it is run.
+///
+/// # Examples:
+/// ```
+/// use cider::executor;
+/// use cider::parsing::json_parser;
+///
+/// let config = json_parser::parse_from_str(r#"{
+///     "backend": "docker",
+///     "image": "rust:latest",
+///     "actions": ["Build"],
+///     "Build": { "manual": { "build": "cargo build" } }
+/// }"#);
+///
+/// let dockerfile = executor::preview_dockerfile(&config.get_actions()[0]);
+/// assert!(dockerfile.starts_with("FROM rust:latest"));
+/// ```
+pub fn preview_dockerfile(action: &Action) -> String {
+    let mut info = ExecInfo::new(action);
+    image_setup(&mut info, &mut vec![]);
+    render_dockerfile(&info)
+}
+
+/// How a step of an action would be run, see [`plan_steps`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    /// The name of the step
+    pub name: String,
+    /// The script of the step as it would be run, with its relative paths resolved unless the action disables
+    /// `clean_paths`
+    pub script: String,
+    /// The program the script is run with, such as `sh -c`
+    pub interpreter: String,
+    /// The directory the script is run within
+    pub working_dir: String,
+    /// See [`Step::get_stdin`] for more information.
+    pub stdin: Option<String>,
+}
+
+/// Returns how each step of `action` would be run, in the order they run, without running anything
+///
+/// Steps of docker actions run as `RUN` instructions while the image is built, see [`preview_dockerfile`], so their
+/// scripts are passed to the shell of the image verbatim. Matches of the `redact` patterns of the action are replaced
+/// within the scripts, as they are when the steps are run.
+///
+/// # Examples:
+/// ```
+/// use cider::executor;
+/// use cider::parsing::json_parser;
+///
+/// let config = json_parser::parse_from_str(r#"{
+///     "backend": "docker",
+///     "image": "rust:latest",
+///     "actions": ["Build"],
+///     "Build": { "manual": { "build": "cargo build", "test": "cargo test" } }
+/// }"#);
+///
+/// let steps = executor::plan_steps(&config.get_actions()[0]);
+/// assert_eq!(steps[1].script, "cargo test");
+/// assert_eq!(steps[1].interpreter, "/bin/sh -c, within rust:latest");
+/// assert_eq!(steps[1].working_dir, "/cider/app");
+/// ```
+pub fn plan_steps(action: &Action) -> Vec<PlannedStep> {
+    let info = ExecInfo::new(action);
+    let backend = info.backend.to_lowercase();
+    let (interpreter, working_dir) = match backend.as_str() {
+        "docker" => (
+            format!("/bin/sh -c, within {}", info.image.as_deref().unwrap_or("alpine:latest")),
+            info.docker_workdir.clone(),
+        ),
+        "batch" | "bat" => ("cmd /C".to_string(), current_dir_name()),
+        _ if cfg!(windows) => ("cmd /C".to_string(), current_dir_name()),
+        _ => ("sh -c".to_string(), current_dir_name()),
+    };
+    info.manual
+        .iter()
+        .map(|step| PlannedStep {
+            name: step.get_name().to_string(),
+            script: info.redact_text(&if backend == "docker" {
+                step.get_script().to_string()
+            } else {
+                script_arguments(&info, step.get_script()).join(" ")
+            }),
+            interpreter: interpreter.clone(),
+            working_dir: working_dir.clone(),
+            stdin: step.get_stdin().map(str::to_string),
+        })
+        .collect()
+}
+
+/// Returns the current working directory, which steps that are not run with docker are run within
+fn current_dir_name() -> String {
+    current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|_| ".".to_string())
+}
+
+/// Writes the Dockerfile of a docker action into its source directory, see [`render_dockerfile`]
+fn generate_dockerfile(info: &ExecInfo) -> Result<File, CustomError> {
+    let path = format!("{}/Dockerfile", info.source);
+    let mut file = File::create(&path).map_err(|err| {
+        error!("{} There was an issue creating a dockerfile for your docker backend.\nMake sure there are no files in your project named \"DOCKERFILE\".", info.log_context(None));
+        CustomError::io(&path, err)
+    })?;
+    file.write_all(render_dockerfile(info).as_bytes()).map_err(|err| {
+        error!("{} There was an issue creating a dockerfile for your docker backend.\nMake sure there are no files in your project named \"DOCKERFILE\".", info.log_context(None));
+        CustomError::io(&path, err)
+    })?;
+
+    Ok(file)
+}
+
+/// Returns the contents of the Dockerfile of a docker action, which runs each of its steps as a layer of the image
+fn render_dockerfile(info: &ExecInfo) -> String {
+    let mut str = format_args!("FROM {}\r\n", info.image.as_ref().unwrap()).to_string();
+    str += format_args!("WORKDIR {}\r\n", info.docker_workdir).to_string().as_ref();
+    match &info.docker_user {
+        // The copied source, and the directory it is copied into, belong to the user so that steps can write to them
+        Some(user) => {
+            str += format_args!("COPY --chown={} . ./\r\n", user).to_string().as_ref();
+            str += format_args!("RUN chown {} .\r\n", user).to_string().as_ref();
+            str += format_args!("USER {}\r\n", user).to_string().as_ref();
+        }
+        None => str += "COPY . ./\r\n",
+    }
+    for step in info.manual.iter() {
+        str += format_args!("RUN {}\r\n", step.get_script())
+            .to_string()
+            .as_ref();
+    }
+    str
+}
+
+/// Adds the patterns of the source directory's `.gitignore` to its `.dockerignore`, so they are left out of the image
+///
+/// See [`render_dockerignore`] for how the patterns are combined.
+fn generate_dockerignore(info: &ExecInfo) -> Result<(), CustomError> {
+    let source = Path::new(&info.source);
+    let path = source.join(".dockerignore");
+    let patterns = ignore::read_gitignore(source)
+        .map_err(|err| CustomError::io(source.join(".gitignore").to_string_lossy(), err))?;
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let contents = render_dockerignore(&existing, &patterns);
+    info!(
+        "{} Writing {} patterns to {:#?}",
+        info.log_context(None),
+        contents.lines().count(),
+        path
+    );
+    fs::write(&path, contents).map_err(|err| CustomError::io(path.to_string_lossy(), err))
+}
+
+/// Returns the contents of a `.dockerignore` that holds the lines of the `existing` `.dockerignore`, followed by each of
+/// the `.gitignore` `patterns` that it does not already hold
+fn render_dockerignore(existing: &str, patterns: &[ignore::IgnorePattern]) -> String {
+    let mut lines = existing.lines().map(str::to_string).collect::<Vec<_>>();
+    for pattern in patterns.iter().map(ignore::IgnorePattern::to_dockerignore) {
+        if !lines.contains(&pattern) {
+            lines.push(pattern);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Runs batch scripts defined in an Action's Manual, returning the combined [`Status`] of its steps
+fn run_batch_script(
+    setup: &ExecInfo,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    steps: &mut Vec<StepResult>,
+) -> Result<Status, CustomError> {
+    let mut status = Status::Success;
+    if cfg!(windows) {
+        for step in &setup.manual {
+            let mut command = Command::new("cmd");
+            let mut script = script_setup(setup, outputs, step);
+            let stdin = step_stdin(setup, step)?;
+            command_setup_windows(&mut command, &mut script, false);
+            let command_line = command_line(&command);
+            let started = start_step(setup, step);
+            let (output, resources) = capture_output(setup, &mut command, stdin)
+                .map_err(|err| CustomError::spawn(script.concat(), err))?;
+            let mut result = finish_step(
+                setup,
+                step,
+                started.elapsed(),
+                &output,
+                command_line,
+                outputs,
+                timings,
+            );
+            result.resources = resources;
+            status = status.and(result.status);
+            steps.push(result);
+        }
+    } else {
+        error!(
+            "{} As of now, running batch scripts is unsupported on non-windows systems.",
+            setup.log_context(None)
+        );
+        outputs.push(
+            "A batch script was unable to be processed on Linux and was taken care of accordingly."
+                .to_string(),
+        );
+    }
+    Ok(status)
+}
+
+/// Builds a docker image that runs the steps defined in an Action's Manual, returning whether the build succeeded
+fn run_with_docker(
+    setup: ExecInfo,
+    pull: PullPolicy,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+) -> Result<bool, CustomError> {
+    let mut setup = setup;
+    for step in setup.manual.iter().filter(|step| step.get_stdin().is_some()) {
+        warn!(
+            "{} The stdin of the step is ignored, as docker steps are run while the image is built",
+            setup.log_context(Some(step))
+        );
+    }
+    warn_step_allowed_failure_ignored(&setup, "docker steps are run while the image is built");
+    image_setup(&mut setup, outputs);
+    generate_dockerfile(&setup)?;
+    if setup.use_gitignore {
+        generate_dockerignore(&setup)?;
+    }
+    let cache_key = docker_cache_key(&setup);
+    if let Some(key) = cache_key.as_deref().filter(|_| !setup.no_cache) {
+        if cached_image_matches(&setup, key) {
+            info!(
+                "{} None of the cache_key_files changed, reusing image {}",
+                setup.log_context(None),
+                setup.image_tag
+            );
+            outputs.push(
+                "The image was reused, as none of the cache_key_files changed since it was built."
+                    .to_string(),
+            );
+            return Ok(true);
+        }
+    }
+    let policy = setup.pull_policy.unwrap_or(pull);
+    if let Some(reason) = missing_image_problem(policy, || image_present(&setup)) {
+        error!("{} {}", setup.log_context(None), reason);
+        outputs.push(reason);
+        return Ok(false);
+    }
+    let pull_image = should_pull(policy, || image_present(&setup));
+    info!(
+        "{} {} image {}",
+        setup.log_context(None),
+        if pull_image { "Pulling" } else { "Using the local copy of" },
+        setup.image.as_ref().unwrap()
+    );
+
+    let log_time = Utc::now().format("%d-%m_%H%M%S").to_string();
+    let mut csv_data = vec![pull_image.to_string()];
+    let built = run_docker_phases(&setup, pull_image, outputs, timings, &mut csv_data);
+    // The metrics cover every phase that ran, so they are written even when the build gave up early
+    csv_data.resize(DOCKER_METRICS_HEADERS.len(), String::new());
+    write_docker_metrics(&setup, &log_time, &DOCKER_METRICS_HEADERS, &csv_data)?;
+    Ok(remember_image(&setup, cache_key.as_deref(), built?))
+}
+
+/// The columns of the docker metrics CSV of an action, see [`run_docker_phases`]
+const DOCKER_METRICS_HEADERS: [&str; 4] = [
+    "Image_pulled",
+    "Image_pull_time",
+    "Image_remove_time",
+    "Image_build_time",
+];
+
+/// Runs the pull, clean and build phases of a docker action, returning whether the image was built
+///
+/// Each phase is timed into `timings`, and how long it took is pushed onto `csv_data`, so a phase that is not reached
+/// because an earlier one gave up pushes nothing and its column is left empty by the caller. The pull is only timed into `timings` when `pull_image` is
+/// set, while its column is always written.
+fn run_docker_phases(
+    setup: &ExecInfo,
+    pull_image: bool,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    csv_data: &mut Vec<String>,
+) -> Result<bool, CustomError> {
+    let shell = || Command::new(if cfg!(windows) { "cmd" } else { "sh" });
+
+    let started = Instant::now();
+    let gave_up = if pull_image {
+        pull_with_retries(setup, outputs)?
+    } else {
+        None
+    };
+    if pull_image {
+        timings.push(Timing::new("docker pull", TimingKind::DockerPull, started.elapsed()));
+    }
+    csv_data.push(format!("{:?}", started.elapsed()));
+    if let Some(reason) = gave_up {
+        outputs.push(reason);
+        return Ok(false);
+    }
+
+    let started = Instant::now();
+    let mut cmd = shell();
+    let clean = if cfg!(windows) {
+        docker_clean_windows(&mut cmd, setup, true)
+    } else {
+        docker_clean_unix(&mut cmd, setup, true)
+    };
+    let (_, killed) = wait_for_docker(setup, clean, "docker image rm", outputs)?;
+    timings.push(Timing::new("docker image rm", TimingKind::DockerClean, started.elapsed()));
+    csv_data.push(format!("{:?}", started.elapsed()));
+    if let Some(reason) = killed {
+        outputs.push(reason);
+        return Ok(false);
+    }
+
+    let started = Instant::now();
+    let mut cmd = shell();
+    let build = if cfg!(windows) {
+        docker_build_windows(&mut cmd, setup, true)
+    } else {
+        docker_build_unix(&mut cmd, setup, true)
+    };
+    let (build_status, killed) = build_image(setup, build, outputs)?;
+    timings.push(Timing::new("docker build", TimingKind::DockerBuild, started.elapsed()));
+    csv_data.push(format!("{:?}", started.elapsed()));
+    if let Some(reason) = killed {
+        outputs.push(reason);
+        clean_up_killed_build(setup)?;
+    }
+    Ok(build_status.success())
+}
+
+/// Writes the docker metrics of an action as a CSV file holding `headers` and `data`, returning where it was written
+///
+/// The file is written within the `win` directory of the metrics directory, and is named after the `metrics_filename`
+/// of the action, where `{date}` is replaced by `date`, `{action}` by the title of the action, and `{pipeline}` by the
+/// title of its pipeline.
+fn write_docker_metrics(
+    setup: &ExecInfo,
+    date: &str,
+    headers: &[&str],
+    data: &[String],
+) -> Result<PathBuf, CustomError> {
+    let metrics_dir = Path::new(&setup.metrics_dir).join("win");
+    let filename = setup
+        .metrics_filename
+        .replace("{date}", date)
+        .replace("{action}", setup.title.as_deref().unwrap_or_default())
+        .replace("{pipeline}", setup.pipeline.as_deref().unwrap_or_default());
+    let log_file = metrics_dir.join(sanitize_filename(&filename));
+    fs::create_dir_all(&metrics_dir)
+        .map_err(|err| CustomError::io(metrics_dir.to_string_lossy(), err))?;
+    let mut csv_wtr = Writer::from_path(&log_file)?;
+    csv_wtr.write_record(headers)?;
+    csv_wtr.write_record(data)?;
+    csv_wtr
+        .flush()
+        .map_err(|err| CustomError::io(log_file.to_string_lossy(), err))?;
+    Ok(log_file)
+}
+
+/// The state file holding the cache key that the image of each docker action was last built with
+fn docker_cache_state(setup: &ExecInfo) -> PathBuf {
+    Path::new(&setup.metrics_dir).join("docker_cache")
+}
+
+/// Returns the cache key of a docker action, or None if it sets no `cache_key_files`
+///
+/// Along with the files, the key covers the Dockerfile and platform of the action, so that changing its image or steps
+/// also rebuilds it.
+fn docker_cache_key(setup: &ExecInfo) -> Option<String> {
+    if setup.cache_key_files.is_empty() {
+        return None;
+    }
+    let dockerfile = render_dockerfile(setup);
+    let platform = setup.platform.as_deref().unwrap_or_default();
+    Some(docker_cache::cache_key(
+        &setup.cache_key_files,
+        Path::new(&setup.source),
+        &[&dockerfile, platform],
+    ))
+}
+
+/// Returns whether the image of a docker action was last built with `key`, and is still the image tagged with its
+/// `image_tag`
+fn cached_image_matches(setup: &ExecInfo, key: &str) -> bool {
+    let title = setup.title.as_deref().unwrap_or_default();
+    docker_cache::read_entry(&docker_cache_state(setup), title).is_some_and(|entry| {
+        entry.key == key && image_id(&entry.tag).is_some_and(|id| id == entry.image_id)
+    })
+}
+
+/// Records the image of a docker action as built with `key` if the build succeeded, returning whether it did
+///
+/// Failing to record the image only means that it is rebuilt on the next run, so it is logged rather than returned.
+fn remember_image(setup: &ExecInfo, key: Option<&str>, built: bool) -> bool {
+    let Some(key) = key.filter(|_| built) else {
+        return built;
+    };
+    let Some(image_id) = image_id(&setup.image_tag) else {
+        warn!(
+            "{} The ID of image {} could not be found, so it is rebuilt on the next run",
+            setup.log_context(None),
+            setup.image_tag
+        );
+        return built;
+    };
+    let entry = CacheEntry {
+        key: key.to_string(),
+        tag: setup.image_tag.clone(),
+        image_id,
+    };
+    let state = docker_cache_state(setup);
+    let title = setup.title.as_deref().unwrap_or_default();
+    if let Err(err) = docker_cache::write_entry(&state, title, &entry) {
+        warn!(
+            "{} The cache key of the image could not be written to {}: {}",
+            setup.log_context(None),
+            state.display(),
+            err
+        );
+    }
+    built
+}
+
+/// Returns the ID of the local image tagged `tag`, if there is one
+fn image_id(tag: &str) -> Option<String> {
+    Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", tag])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Runs a docker command to completion, returning its exit status along with why it was killed, if it was
+///
+/// The command is killed once the step timeout of the action passes or the run is cancelled. Its output is still shown
+/// as it is written, and is also pushed onto `outputs` labelled with `phase`, see [`phase_output`].
+fn wait_for_docker(
+    setup: &ExecInfo,
+    cmd: &mut Command,
+    phase: &str,
+    outputs: &mut Vec<String>,
+) -> Result<(ExitStatus, Option<String>), CustomError> {
+    let (status, killed, stdout, stderr) =
+        capture_docker(setup, cmd).map_err(|err| CustomError::spawn(phase, err))?;
+    outputs.push(phase_output(phase, &stdout, &stderr));
+    Ok((status, killed))
+}
+
+/// Runs a docker command like [`wait_for_docker`], returning its redacted standard output and standard error
+fn capture_docker(
+    setup: &ExecInfo,
+    cmd: &mut Command,
+) -> io::Result<(ExitStatus, Option<String>, String, String)> {
+    let mut process = spawn_killable(setup, set_output_piped(cmd))?;
+    let limit = setup.max_output_bytes;
+    let stdout = process.stdout.take().expect("The output of a docker command was not piped.");
+    let stdout = tee_in_background(stdout, setup.redact.clone(), limit, io::stdout());
+    let stderr = process.stderr.take().expect("The output of a docker command was not piped.");
+    let stderr = tee_in_background(stderr, setup.redact.clone(), limit, io::stderr());
+    let (status, killed) = wait_killable(setup, &mut process)?;
+    let stdout = stdout.join().expect("Reading the output of a docker command panicked.")?;
+    let stderr = stderr.join().expect("Reading the output of a docker command panicked.")?;
+    Ok((status, killed, stdout, stderr))
+}
+
+/// Labels the output of the docker command run in `phase`, such as `docker build`, so that the output of each phase
+/// of a docker action can be told apart within the output of the action
+///
+/// The label is the name of the phase within square brackets, on a line of its own before the output.
+fn phase_output(phase: &str, stdout: &str, stderr: &str) -> String {
+    let mut labelled = format!("[{}]", phase);
+    for line in stdout.lines().chain(stderr.lines()) {
+        labelled.push('\n');
+        labelled.push_str(line);
+    }
+    labelled
+}
+
+/// Runs the `docker build` of a docker action, see [`wait_for_docker`]
+///
+/// If the action sets `docker_context_tar`, its build context is first packed into a tarball within the temporary
+/// directory, which is fed to the build through its standard input and removed once the build is done.
+fn build_image(
+    setup: &ExecInfo,
+    cmd: &mut Command,
+    outputs: &mut Vec<String>,
+) -> Result<(ExitStatus, Option<String>), CustomError> {
+    if !setup.docker_context_tar {
+        return wait_for_docker(setup, cmd, "docker build", outputs);
+    }
+    let tar = std::env::temp_dir().join(format!(
+        "cider-context-{}-{}.tar",
+        std::process::id(),
+        setup.image_tag
+    ));
+    let tar_name = tar.to_string_lossy().to_string();
+    info!("{} Packing the build context into {}", setup.log_context(None), tar_name);
+    build_context::create_context_tar(Path::new(&setup.source), &tar)
+        .map_err(|err| CustomError::io(&tar_name, err))?;
+    let built = File::open(&tar)
+        .map_err(|err| CustomError::io(&tar_name, err))
+        .and_then(|context| wait_for_docker(setup, cmd.stdin(context), "docker build", outputs));
+    if let Err(err) = fs::remove_file(&tar) {
+        warn!(
+            "{} The build context {} could not be removed: {}",
+            setup.log_context(None),
+            tar_name,
+            err
+        );
+    }
+    built
+}
+
+/// Returns the build context that `docker build` is given, which is read from its standard input if the action sets
+/// `docker_context_tar`
+fn docker_context(info: &ExecInfo) -> &'static str {
+    if info.docker_context_tar {
+        "-"
+    } else {
+        "."
+    }
+}
+
+/// Pulls the image of a docker action, retrying a failed pull up to `pull_retries` times
+///
+/// Returns why the pull was given up on, if it was. A pull that is killed by the step timeout or by the run being
+/// cancelled is not retried.
+fn pull_with_retries(
+    setup: &ExecInfo,
+    outputs: &mut Vec<String>,
+) -> Result<Option<String>, CustomError> {
+    let attempts = setup.pull_retries + 1;
+    for attempt in 1..=attempts {
+        let mut cmd = Command::new(if cfg!(windows) { "cmd" } else { "sh" });
+        let cmd = if cfg!(windows) {
+            docker_setup_windows(&mut cmd, setup, true)
+        } else {
+            docker_setup_unix(&mut cmd, setup, true)
+        };
+        let (status, killed) = wait_for_docker(setup, cmd, "docker pull", outputs)?;
+        if killed.is_some() {
+            return Ok(killed);
+        }
+        if status.success() {
+            return Ok(None);
+        }
+        if attempt < attempts {
+            warn!(
+                "{} docker pull failed, retrying in {:?} (attempt {} of {})",
+                setup.log_context(None),
+                setup.pull_retry_delay,
+                attempt + 1,
+                attempts
+            );
+            thread::sleep(setup.pull_retry_delay);
+            if setup.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+                return Ok(Some("The run was cancelled before the image could be pulled.".to_string()));
+            }
+        }
+    }
+    let message = format!("docker pull failed after {} attempt(s).", attempts);
+    error!("{} {}", setup.log_context(None), message);
+    Ok(Some(message))
+}
+
+/// Removes the image left behind by a docker build that was killed part way through
+///
+/// The cleanup is never killed itself, so that it still finishes when the build was killed by the run being cancelled.
+fn clean_up_killed_build(setup: &ExecInfo) -> Result<(), CustomError> {
+    info!("{} Cleaning up after the killed docker build", setup.log_context(None));
+    let mut cmd = Command::new(if cfg!(windows) { "cmd" } else { "sh" });
+    if cfg!(windows) {
+        docker_clean_windows(&mut cmd, setup, true);
+    } else {
+        docker_clean_unix(&mut cmd, setup, true);
+    }
+    cmd.status()
+        .map(|_| ())
+        .map_err(|err| CustomError::spawn("docker image rm", err))
+}
+
+///Runs bash scripts defined in an Action's Manual, returning the combined [`Status`] of its steps
+///
+/// Every step runs in its own shell on every platform, so a failing step does not stop the steps after it. Steps only
+/// share a shell, and stop at the first failure, when the action enables `shared_shell`.
+fn run_bash_scripts(
+    setup: &ExecInfo,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    steps: &mut Vec<StepResult>,
+) -> Result<Status, CustomError> {
+    if cfg!(windows) {
+        warn!("{} In order to avoid unexpected behavior, please consider using \"bat\" or \"batch\" backend for windows operating systems.", setup.log_context(None));
+    }
+    if setup.shared_shell {
+        if setup.concurrency > 1 {
+            warn!(
+                "{} concurrency is ignored, as steps run one after another when shared_shell is enabled",
+                setup.log_context(None)
+            );
+        }
+        return run_shared_shell(setup, outputs, timings, steps);
+    }
+    if setup.concurrency > 1 {
+        return run_concurrent_steps(setup, outputs, timings, steps);
+    }
+    let mut status = Status::Success;
+    for step in &setup.manual {
+        let mut script = script_setup(setup, outputs, step);
+        let stdin = step_stdin(setup, step)?;
+        let started = start_step(setup, step);
+        let (output, command, resources) = run_bash_script(setup, &mut script, stdin)?;
+        let duration = started.elapsed();
+        let mut result = finish_step(setup, step, duration, &output, command, outputs, timings);
+        result.resources = resources;
+        status = status.and(result.status);
+        steps.push(result);
+    }
+    Ok(status)
+}
+
+/// How a step run by [`run_concurrent_steps`] went: its output, the command it was run with, how long it took, and the
+/// resources it used
+type ConcurrentStep = (Output, CommandLine, Duration, Option<ResourceUsage>);
+
+/// Runs the steps of an Action's Manual on up to `concurrency` threads at a time, returning the combined [`Status`] of
+/// its steps
+///
+/// Steps start in the order they are defined, and their output is reported in that order once every step has finished,
+/// so that the output of steps running at the same time is never interleaved.
+fn run_concurrent_steps(
+    setup: &ExecInfo,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    steps: &mut Vec<StepResult>,
+) -> Result<Status, CustomError> {
+    info!(
+        "{} Running {} steps with a concurrency of {}",
+        setup.log_context(None),
+        setup.manual.len(),
+        setup.concurrency
+    );
+    let next = AtomicUsize::new(0);
+    let finished = Mutex::new(
+        (0..setup.manual.len())
+            .map(|_| None)
+            .collect::<Vec<Option<Result<ConcurrentStep, CustomError>>>>(),
+    );
+    thread::scope(|scope| {
+        for _ in 0..setup.concurrency.min(setup.manual.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(step) = setup.manual.get(index) else {
+                    break;
+                };
+                let result = step_stdin(setup, step).and_then(|stdin| {
+                    info!("{} Running {}", setup.log_context(Some(step)), step.get_name());
+                    let mut script = script_arguments(setup, step.get_script());
+                    let started = start_step(setup, step);
+                    let (output, command, resources) = run_bash_script(setup, &mut script, stdin)?;
+                    Ok((output, command, started.elapsed(), resources))
+                });
+                finished.lock().expect("A concurrent step panicked.")[index] = Some(result);
+            });
+        }
+    });
+
+    let mut status = Status::Success;
+    let finished = finished.into_inner().expect("A concurrent step panicked.");
+    for (step, result) in setup.manual.iter().zip(finished) {
+        let (output, command, duration, resources) =
+            result.expect("A concurrent step did not run.")?;
+        let running = format!("Running {}", step.get_name());
+        println!("{}", running);
+        outputs.push(running);
+        println!("{}", setup.redact_text(step.get_script()));
+        let mut result = finish_step(setup, step, duration, &output, command, outputs, timings);
+        result.resources = resources;
+        status = status.and(result.status);
+        steps.push(result);
+    }
+    Ok(status)
+}
+
+/// Runs every step of an Action's Manual within a single shell invocation, returning the [`Status`] of the invocation
+///
+/// Steps are chained with `&&`, so state like the working directory carries over between steps and a failing step
+/// prevents the steps after it from running.
+fn run_shared_shell(
+    setup: &ExecInfo,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+    steps: &mut Vec<StepResult>,
+) -> Result<Status, CustomError> {
+    warn_step_allowed_failure_ignored(
+        setup,
+        "steps share a single shell when shared_shell is enabled",
+    );
+    let mut script = vec![];
+    for step in &setup.manual {
+        if step.get_stdin().is_some() {
+            warn!(
+                "{} The stdin of step \"{}\" is ignored, as steps share their standard input when shared_shell is enabled",
+                setup.log_context(None),
+                step.get_name()
+            );
+        }
+        if !script.is_empty() {
+            script.push("&&".to_string());
+        }
+        script.append(&mut script_setup(setup, outputs, step));
+    }
+    let step = Step::new(
+        setup
+            .manual
+            .iter()
+            .map(|step| step.get_name())
+            .collect::<Vec<&str>>()
+            .join(" && "),
+        script.join(" "),
+    );
+
+    let started = start_step(setup, &step);
+    let (output, command, resources) = run_bash_script(setup, &mut script, None)?;
+    let mut result =
+        finish_step(setup, &step, started.elapsed(), &output, command, outputs, timings);
+    result.resources = resources;
+    let status = result.status;
+    steps.push(result);
+    // The steps share one output, so each of them can be looked up by name
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for step in &setup.manual {
+        setup.step_outputs.record(step.get_name(), &stdout);
+    }
+    Ok(status)
+}
+
+/// Warns about every step of an action that sets its own `allowed_failure`, which is ignored as `reason`, so that the
+/// `allowed_failure` of the action decides whether its failure fails the run
+fn warn_step_allowed_failure_ignored(setup: &ExecInfo, reason: &str) {
+    for step in setup.manual.iter().filter(|step| step.get_allowed_failure().is_some()) {
+        warn!(
+            "{} The allowed_failure of the step is ignored, as {}",
+            setup.log_context(Some(step)),
+            reason
+        );
+    }
+}
+
+/// Records that a step is about to run, returning when it started
+fn start_step(setup: &ExecInfo, step: &Step) -> Instant {
+    setup.emit(EventKind::StepStarted, Some(step));
+    Instant::now()
+}
+
+/// Records how long a step took and what it output, returning the [`StepResult`] of the step
+///
+/// A failing step is allowed to fail if it sets `allowed_failure`, and otherwise if its action does.
+fn finish_step(
+    setup: &ExecInfo,
+    step: &Step,
+    duration: Duration,
+    output: &Output,
+    command: CommandLine,
+    outputs: &mut Vec<String>,
+    timings: &mut Vec<Timing>,
+) -> StepResult {
+    let command = setup.redacted_command(command);
+    info!("{} Ran: {}", setup.log_context(Some(step)), command);
+    timings.push(
+        Timing::new(step.get_name(), TimingKind::Step, duration).with_command(command.clone()),
+    );
+    // output_contains conditions read the output as it was written, while everything that can reach
+    // the logs or a report only sees the output once it is redacted
+    setup
+        .step_outputs
+        .record(step.get_name(), &String::from_utf8_lossy(&output.stdout));
+    let output = &setup.redacted(output);
+    for (stream, bytes) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        if !bytes.is_empty() {
+            let text = String::from_utf8_lossy(bytes).into_owned();
+            setup.emit(EventKind::StepOutput { stream, text }, Some(step));
+        }
+    }
+    setup.emit(
+        EventKind::StepFinished {
+            success: step_succeeded(setup, output),
+            duration,
+            command: command.clone(),
+        },
+        Some(step),
+    );
+    let succeeded = collect_piped_output(setup, step, output, outputs);
+    if !succeeded {
+        println!("Reproduce with: {}", command);
+        if setup.verbose_errors {
+            let context = setup.log_context(Some(step));
+            for line in failure_context(step, &command, output, std::env::vars()) {
+                error!("{} {}", context, line);
+            }
+        }
+    }
+    let status = Status::new(
+        succeeded,
+        step.get_allowed_failure().unwrap_or(setup.allowed_failure),
+    );
+    if status == Status::AllowedFailure && step.get_allowed_failure().is_some() {
+        let allowed = format!(
+            "Step {} is allowed to fail, so its failure does not fail the action",
+            step.get_name()
+        );
+        info!("{} {}", setup.log_context(Some(step)), allowed);
+        println!("{}", allowed);
+    }
+    StepResult {
+        name: step.get_name().to_string(),
+        status,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        exit_code: output.status.code(),
+        duration,
+        resources: None,
+    }
+}
+
+/// Describes everything needed to reproduce the failure of `step`, which was run as `command` within the environment
+/// `env` and finished with `output`
+///
+/// The values of environment variables whose names suggest they hold secrets, such as `GITHUB_TOKEN`, are masked.
+fn failure_context(
+    step: &Step,
+    command: &CommandLine,
+    output: &Output,
+    env: impl Iterator<Item = (String, String)>,
+) -> Vec<String> {
+    let exit_code = match output.status.code() {
+        Some(code) => code.to_string(),
+        None => output.status.to_string(),
+    };
+    let mut context = vec![
+        format!("Step {} failed", step.get_name()),
+        format!("  command:     {}", command),
+        format!("  working dir: {}", command.working_dir),
+        format!("  exit code:   {}", exit_code),
+        "  environment:".to_string(),
+    ];
+    let mut env = env.collect::<Vec<(String, String)>>();
+    env.sort();
+    for (name, value) in env {
+        let value = if is_secret_name(&name) {
+            "********".to_string()
+        } else {
+            value
+        };
+        context.push(format!("    {}={}", name, value));
+    }
+    context.push("  stderr:".to_string());
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        context.push(format!("    {}", line));
+    }
+    context
+}
+
+/// Returns whether the environment variable `name` suggests that it holds a secret
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    ["SECRET", "TOKEN", "PASSWORD", "PASSWD", "KEY", "CREDENTIAL", "AUTH"]
+        .iter()
+        .any(|secret| name.contains(secret))
+}
+
+/// Describes the command `cmd` as it is about to be run
+fn command_line(cmd: &Command) -> CommandLine {
+    let working_dir = cmd
+        .get_current_dir()
+        .map(Path::to_path_buf)
+        .or_else(|| current_dir().ok())
+        .unwrap_or_default();
+    CommandLine {
+        program: cmd.get_program().to_string_lossy().into_owned(),
+        args: cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+        working_dir: working_dir.to_string_lossy().into_owned(),
+    }
+}
+
+/// Returns what should be written to the standard input of `step`, reading it from a file if its `stdin` starts with `@`
+///
+/// Relative files are found from the working directory, where the step itself runs.
+fn step_stdin(setup: &ExecInfo, step: &Step) -> Result<Option<Vec<u8>>, CustomError> {
+    match step.get_stdin() {
+        None => Ok(None),
+        Some(stdin) => match stdin.strip_prefix('@') {
+            Some(path) => {
+                info!(
+                    "{} Reading the stdin of the step from {:#?}",
+                    setup.log_context(Some(step)),
+                    path
+                );
+                fs::read(path).map(Some).map_err(|err| CustomError::Read {
+                    path: path.to_string(),
+                    source: err,
+                })
+            }
+            None => Ok(Some(stdin.as_bytes().to_vec())),
+        },
+    }
+}
+
+/// Runs a single cleaned script with the shell used by the bash backend on the current platform, returning its output
+/// along with the command it was run with and the resources it used, if they were measured
+fn run_bash_script(
+    setup: &ExecInfo,
+    script: &mut Vec<String>,
+    stdin: Option<Vec<u8>>,
+) -> Result<(Output, CommandLine, Option<ResourceUsage>), CustomError> {
+    let mut cmd = Command::new(if cfg!(windows) { "cmd" } else { "sh" });
+    if cfg!(windows) {
+        command_setup_windows(&mut cmd, script, false).current_dir(&setup.source);
+    } else {
+        command_setup_unix(&mut cmd, script, false);
+    }
+    let command = command_line(&cmd);
+    let (output, resources) = capture_output(setup, &mut cmd, stdin)
+        .map_err(|err| CustomError::spawn(script.join(" "), err))?;
+    Ok((output, command, resources))
+}
+
+/// Runs a command to completion and captures its output, merging its standard error into its standard output if the
+/// action enables `merge_output`
+///
+/// If the action enables `tty`, the command is given a pseudo-terminal as both its standard output and error instead,
+/// so everything it writes to the terminal is captured as its standard output.
+///
+/// If the action has a step timeout, the command and every process it started are killed once the timeout passes, or
+/// once the run is cancelled. When `stdin` is given, it is written to the standard input of the command, which is
+/// closed once it has all been written; otherwise the command inherits the standard input of CIder.
+///
+/// The resources the command used are returned along with its output if the run profiles resources, see
+/// [`wait_measured`].
+fn capture_output(
+    setup: &ExecInfo,
+    cmd: &mut Command,
+    stdin: Option<Vec<u8>>,
+) -> io::Result<(Output, Option<ResourceUsage>)> {
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    if setup.tty {
+        let (reader, terminal) = terminal::open()?;
+        terminal::control(cmd);
+        cmd.stdout(terminal.try_clone()?).stderr(terminal);
+        return capture_combined_output(setup, cmd, stdin, reader);
+    }
+    if setup.merge_output {
+        let (reader, writer) = io::pipe()?;
+        cmd.stdout(writer.try_clone()?).stderr(writer);
+        return capture_combined_output(setup, cmd, stdin, reader);
+    }
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = spawn_killable(setup, cmd)?;
+    write_in_background(&mut child, stdin);
+    let stdout = read_in_background(
+        child.stdout.take().expect("The standard output of a step was not piped."),
+        setup.max_output_bytes,
+    );
+    let stderr = read_in_background(
+        child.stderr.take().expect("The standard error of a step was not piped."),
+        setup.max_output_bytes,
+    );
+    let (status, _, resources) = wait_measured(setup, &mut child)?;
+    let output = Output {
+        status,
+        stdout: stdout.join().expect("Reading the output of a step panicked.")?,
+        stderr: stderr.join().expect("Reading the output of a step panicked.")?,
+    };
+    Ok((output, resources))
+}
+
+/// Runs a command whose standard output and error have both been pointed at the writing end of `reader`, capturing
+/// everything read as its standard output
+fn capture_combined_output(
+    setup: &ExecInfo,
+    cmd: &mut Command,
+    stdin: Option<Vec<u8>>,
+    reader: impl Read + Send + 'static,
+) -> io::Result<(Output, Option<ResourceUsage>)> {
+    let mut child = spawn_killable(setup, cmd)?;
+    write_in_background(&mut child, stdin);
+    // The command holds on to the writing end, which has to be closed for reading to finish
+    cmd.stdout(Stdio::null()).stderr(Stdio::null());
+    let stdout = read_in_background(reader, setup.max_output_bytes);
+    let (status, _, resources) = wait_measured(setup, &mut child)?;
+    let output = Output {
+        status,
+        stdout: stdout.join().expect("Reading the output of a step panicked.")?,
+        stderr: vec![],
+    };
+    Ok((output, resources))
+}
+
+/// Spawns a command, placing it in its own process group on unix when it may be killed by a step timeout or by the run
+/// being cancelled, so that every process it starts can be killed along with it
+///
+/// A command run within a pseudo-terminal is already placed within a process group of its own, see
+/// [`terminal::control`].
+fn spawn_killable(setup: &ExecInfo, cmd: &mut Command) -> io::Result<Child> {
+    #[cfg(unix)]
+    if (setup.step_timeout.is_some() || setup.cancel.is_some()) && !setup.tty {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    cmd.spawn()
+}
+
+/// Reads everything from `reader` on another thread, so that a full pipe never blocks the process writing to it
+///
+/// Only the first `limit` bytes are kept, unless `limit` is 0. The rest is still read, so that the process can run to
+/// completion, but is dropped and replaced with a marker saying how much was truncated.
+fn read_in_background(
+    mut reader: impl Read + Send + 'static,
+    limit: usize,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if limit == 0 {
+            reader.read_to_end(&mut output)?;
+            return Ok(output);
+        }
+        reader.by_ref().take(limit as u64).read_to_end(&mut output)?;
+        let truncated = io::copy(&mut reader, &mut io::sink())?;
+        if truncated > 0 {
+            warn!("Step output exceeded {} bytes, truncated {} bytes", limit, truncated);
+            output.extend_from_slice(format!("\n... (truncated {} bytes)\n", truncated).as_bytes());
+        }
+        Ok(output)
+    })
+}
+
+/// Reads the lines of `reader` on another thread like [`read_in_background`], writing each to `echo` as it is read
+///
+/// Matches of `patterns` are replaced within every line before it is written or kept.
+fn tee_in_background(
+    reader: impl Read + Send + 'static,
+    patterns: Vec<RedactPattern>,
+    limit: usize,
+    mut echo: impl Write + Send + 'static,
+) -> thread::JoinHandle<io::Result<String>> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut kept = String::new();
+        let mut truncated = 0;
+        let mut line = vec![];
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            let redacted = redaction::redact(&String::from_utf8_lossy(&line), &patterns);
+            // Failing to show the output must not stop it being read, or the command could block
+            let _ = echo.write_all(redacted.as_bytes());
+            if limit == 0 || kept.len() + redacted.len() <= limit {
+                kept.push_str(&redacted);
+            } else {
+                truncated += redacted.len();
+            }
+            line.clear();
+        }
+        if truncated > 0 {
+            warn!("Docker output exceeded {} bytes, truncated {} bytes", limit, truncated);
+            kept.push_str(&format!("\n... (truncated {} bytes)\n", truncated));
+        }
+        Ok(kept)
+    })
+}
+
+/// Writes `input` to the standard input of `child` on another thread, so that a process which does not read all of its
+/// input never blocks the step
+///
+/// A process that exits without reading all of its input is not an error, so failing to write is ignored.
+fn write_in_background(child: &mut Child, input: Option<Vec<u8>>) {
+    if let Some(input) = input {
+        let mut stdin = child.stdin.take().expect("The standard input of a step was not piped.");
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+}
+
+/// Waits for a process to exit, killing it and every process it started once the step timeout of the action passes or
+/// the run is cancelled
+///
+/// Returns the exit status of the process, along with why it was killed, if it was.
+fn wait_killable(setup: &ExecInfo, child: &mut Child) -> io::Result<(ExitStatus, Option<String>)> {
+    wait_measured(setup, child).map(|(status, killed, _)| (status, killed))
+}
+
+/// Waits for a process like [`wait_killable`], also returning the resources it used if the run profiles resources
+///
+/// See [`resources::wait`] for when the resources can be measured.
+fn wait_measured(
+    setup: &ExecInfo,
+    child: &mut Child,
+) -> io::Result<(ExitStatus, Option<String>, Option<ResourceUsage>)> {
+    let reap = |child: &mut Child, block: bool| {
+        if setup.profile_resources {
+            return resources::wait(child, block);
+        }
+        let status = if block {
+            Some(child.wait()?)
+        } else {
+            child.try_wait()?
+        };
+        Ok(status.map(|status| (status, None)))
+    };
+    if setup.step_timeout.is_none() && setup.cancel.is_none() {
+        let (status, usage) = reap(child, true)?.expect("A blocking wait returned early.");
+        return Ok((status, None, usage));
+    }
+    let started = Instant::now();
+    loop {
+        if let Some((status, usage)) = reap(child, false)? {
+            return Ok((status, None, usage));
+        }
+        if let Some(timeout) = setup.step_timeout.filter(|timeout| started.elapsed() >= *timeout) {
+            let message = format!("Step timed out after {:?} and was killed.", timeout);
+            error!("{} {}", setup.log_context(None), message);
+            println!("{}", message);
+            kill_process_tree(child);
+            let (status, usage) = reap(child, true)?.expect("A blocking wait returned early.");
+            return Ok((status, Some(message), usage));
+        }
+        if setup.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            let message = "The run was cancelled, killing the running step.".to_string();
+            warn!("{} {}", setup.log_context(None), message);
+            kill_process_tree(child);
+            let (status, usage) = reap(child, true)?.expect("A blocking wait returned early.");
+            return Ok((status, Some(message), usage));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Kills a process along with every process it started
+fn kill_process_tree(child: &mut Child) {
+    let killed = if cfg!(windows) {
+        Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    } else {
+        Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    };
+    if !killed.is_ok_and(|status| status.success()) {
+        child.kill().ok();
+    }
+}
+
+/// Cleans paths used within scripts.
+/// TODO: Fix paths being "overcleaned" i.e. directory/"some other directory"/low_dir being split incorrectly
+///
+fn clean_script_pathing(script: &str) -> Vec<String> {
+    clean_script_pathing_in(script, &current_dir().unwrap())
+}
+
+/// Splits `script` into its arguments, resolving every argument that is a relative path against `root`
+///
+/// See [`is_relative_path`] for which arguments are resolved.
+fn clean_script_pathing_in(script: &str, root: &Path) -> Vec<String> {
+    script
+        .split(' ')
+        .map(|item| {
+            if is_relative_path(item, root) {
+                RelativePath::new(item)
+                    .to_path(root)
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            } else {
+                item.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Returns whether the script argument `item` is a relative path that should be resolved against `root`
+///
+/// Flags such as `--path=./x` and URLs are never paths. Otherwise, arguments starting with `./` or `../` are paths, and
+/// so are other arguments holding `./` or `../`, such as `src/../Cargo.toml`, as long as they exist within `root`.
+fn is_relative_path(item: &str, root: &Path) -> bool {
+    if item.starts_with('-') || item.contains("://") {
+        return false;
+    }
+    if item.starts_with("./") || item.starts_with("../") {
+        return true;
+    }
+    item.contains("./") && root.join(item).exists()
+}
+
+/// Contains data necessary to perform specific actions in a configurable manner
+/// Combines information from both [`crate::utils::config::ShareableConfiguration`] and [`crate::utils::config::ActionConfig`]
+/// See [`crate::utils::config`] for more information.
+#[derive(Debug, Clone)]
+pub struct ExecInfo {
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub backend: String,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub image: Option<String>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub title: Option<String>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub platform: Option<String>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub use_gitignore: bool,
+    /// The `redact` patterns of the action, leaving out any that are not valid.
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub redact: Vec<RedactPattern>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub clean_paths: bool,
+    /// The title of the pipeline the action belongs to, if any. See [`crate::utils::config::Action`] for more information.
+    pub pipeline: Option<String>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub tags: Option<HashMap<String, String>>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub metadata: Option<HashMap<String, String>>,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub output: String,
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub source: String,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub conditions: Option<Vec<Condition>>,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub manual: Vec<Step>,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub retries: i8,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub allowed_failure: bool,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub shared_shell: bool,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub merge_output: bool,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub fail_on_stderr: bool,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub tty: bool,
+    /// How long each step may run before it is killed, if it may not run for as long as it needs.
+    /// See [`crate::utils::config::ActionConfig`] and [`ExecOptions`] for more information.
+    pub step_timeout: Option<Duration>,
+    /// The tag the image of a docker action is built with, which is unique to the action, see [`default_image_tag`]
+    pub image_tag: String,
+    /// The directory the steps of a docker action run within inside its image.
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub docker_workdir: String,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub docker_user: Option<String>,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub pull_retries: u32,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub pull_retry_delay: Duration,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub concurrency: usize,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub cache_key_files: Vec<String>,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub docker_context_tar: bool,
+    /// See [`crate::utils::config::ActionConfig`] for more information.
+    pub pull_policy: Option<PullPolicy>,
+    /// See [`ExecOptions`] for more information.
+    pub ci: CiFormat,
+    /// Every reporter of the run, including [`ExecOptions::events`].
+    /// See [`ExecOptions`] for more information.
+    pub reporters: Reporters,
+    /// See [`ExecOptions`] for more information.
+    pub cancel: Option<CancelToken>,
+    /// The directory docker metrics are written to, which is the `metrics_dir` of the action if it sets one.
+    /// See [`crate::utils::config::ShareableConfiguration`] and [`ExecOptions`] for more information.
+    pub metrics_dir: String,
+    /// The pattern docker metrics files are named with.
+    /// See [`crate::utils::config::ShareableConfiguration`] for more information.
+    pub metrics_filename: String,
+    /// The most bytes of each stream of a step's output that are kept, where 0 keeps everything.
+    /// See [`ExecOptions`] for more information.
+    pub max_output_bytes: usize,
+    /// See [`ExecOptions`] for more information.
+    pub step_outputs: StepOutputs,
+    /// See [`ExecOptions`] for more information.
+    pub verbose_errors: bool,
+    /// See [`ExecOptions`] for more information.
+    pub no_cache: bool,
+    /// See [`ExecOptions`] for more information.
+    pub profile_resources: bool,
+}
+
+/**
+ * Functions to be used by the ExecInfo struct.
+ * Should only contain a constructor and/or cleanup scripts.
+ */
+impl ExecInfo {
+    fn new(action: &Action) -> Self {
+        ExecInfo {
+            backend: action.shared_config.get_backend().to_string(),
+            image: action.shared_config.image().map(str::to_string),
+            title: action.shared_config.title().map(str::to_string),
+            platform: action.shared_config.get_platform().map(str::to_string),
+            use_gitignore: action.shared_config.get_use_gitignore(),
+            redact: action
+                .shared_config
+                .get_redact()
+                .iter()
+                .filter_map(|pattern| {
+                    RedactPattern::parse(pattern)
+                        .inspect_err(|reason| {
+                            error!("The redact pattern {} is not valid, so it is ignored: {}", pattern, reason)
+                        })
+                        .ok()
+                })
+                .collect(),
+            clean_paths: action.shared_config.get_clean_paths(),
+            pipeline: action.get_pipeline().map(str::to_string),
+            tags: action.shared_config.tags().cloned(),
+            metadata: action.shared_config.metadata().cloned(),
+            output: action.shared_config.get_output().to_string(),
+            source: action.shared_config.get_source().to_string(),
+            conditions: action.action_config.get_all_conditions(),
+            manual: action.action_config.get_manual().to_vec(),
+            retries: *action.action_config.get_retries(),
+            allowed_failure: *action.action_config.get_allowed_failure(),
+            shared_shell: *action.action_config.get_shared_shell(),
+            merge_output: *action.action_config.get_merge_output(),
+            fail_on_stderr: action.action_config.get_fail_on_stderr(),
+            tty: action.action_config.get_tty(),
+            step_timeout: action.action_config.get_step_timeout(),
+            image_tag: default_image_tag(action.get_pipeline(), action.shared_config.title()),
+            docker_workdir: action
+                .action_config
+                .get_docker_workdir()
+                .unwrap_or(DEFAULT_DOCKER_WORKDIR)
+                .to_string(),
+            docker_user: action.action_config.get_docker_user().map(str::to_string),
+            pull_retries: action.action_config.get_pull_retries(),
+            pull_retry_delay: action.action_config.get_pull_retry_delay(),
+            concurrency: action.action_config.get_concurrency(),
+            cache_key_files: action.action_config.get_cache_key_files().to_vec(),
+            docker_context_tar: action.action_config.get_docker_context_tar(),
+            pull_policy: action.action_config.get_pull_policy(),
+            ci: CiFormat::default(),
+            reporters: Reporters::default(),
+            cancel: None,
+            metrics_dir: action
+                .shared_config
+                .get_metrics_dir()
+                .unwrap_or(DEFAULT_METRICS_DIR)
+                .to_string(),
+            metrics_filename: action
+                .shared_config
+                .get_metrics_filename()
+                .unwrap_or(DEFAULT_METRICS_FILENAME)
+                .to_string(),
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            step_outputs: StepOutputs::default(),
+            verbose_errors: false,
+            no_cache: false,
+            profile_resources: false,
+        }
+    }
+
+    /// Sends an [`Event`] about the action, or one of its steps, to every reporter of the run
+    fn emit(&self, kind: EventKind, step: Option<&Step>) {
+        if !self.reporters.is_empty() {
+            self.reporters.event(Event::new(
+                kind,
+                self.pipeline.clone(),
+                self.title.clone(),
+                step.map(|step| step.get_name().to_string()),
+            ));
+        }
+    }
+
+    /// Returns `text` with every match of the `redact` patterns of the action replaced
+    fn redact_text(&self, text: &str) -> String {
+        redaction::redact(text, &self.redact)
+    }
+
+    /// Returns `command` with every match of the `redact` patterns of the action replaced within its program, its
+    /// arguments and its working directory, so that a secret written into a script is not logged or reported
+    fn redacted_command(&self, command: CommandLine) -> CommandLine {
+        CommandLine {
+            program: self.redact_text(&command.program),
+            args: command.args.iter().map(|arg| self.redact_text(arg)).collect(),
+            working_dir: self.redact_text(&command.working_dir),
+        }
+    }
+
+    /// Returns `output` with every match of the `redact` patterns of the action replaced within both of its streams
+    fn redacted(&self, output: &Output) -> Output {
+        if self.redact.is_empty() {
+            return output.clone();
+        }
+        let redact = |bytes: &[u8]| {
+            redaction::redact(&String::from_utf8_lossy(bytes), &self.redact).into_bytes()
+        };
+        Output {
+            status: output.status,
+            stdout: redact(&output.stdout),
+            stderr: redact(&output.stderr),
+        }
+    }
+
+    /// Returns a prefix for log lines that identifies the pipeline, action, and optionally step being run
+    ///
+    /// The prefix looks like `[pipeline=Build_Pipeline action=Build step=build]`, where the pipeline and step are left
+    /// out when they do not apply.
+    fn log_context(&self, step: Option<&Step>) -> String {
+        let mut fields = vec![];
+        if let Some(pipeline) = &self.pipeline {
+            fields.push(format!("pipeline={}", pipeline));
+        }
+        fields.push(format!(
+            "action={}",
+            self.title.as_deref().unwrap_or("<untitled>")
+        ));
+        if let Some(step) = step {
+            fields.push(format!("step={}", step.get_name()));
+        }
+        format!("[{}]", fields.join(" "))
+    }
+}
+
+/// Derives the tag the image of an action is built with from the titles of the action and its pipeline
+///
+/// Tags are lowercased, and characters docker does not allow within image names are replaced with `-`, so that
+/// actions that can run at the same time never build, remove or reuse each other's images.
+fn default_image_tag(pipeline: Option<&str>, title: Option<&str>) -> String {
+    let mut tag = "cider-image".to_string();
+    for part in [pipeline, Some(title.unwrap_or("untitled"))].into_iter().flatten() {
+        let part = part
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect::<String>();
+        // Image names must not end with, or have two in a row of, `.` or `_`, so only `-` is used
+        let part = part.trim_matches('-');
+        if !part.is_empty() {
+            tag.push('-');
+            tag.push_str(part);
+        }
+    }
+    tag
+}
+
+fn command_setup_windows<'a>(
+    cmd: &'a mut Command,
+    args: &mut Vec<String>,
+    inherit: bool,
+) -> &'a mut Command {
+    //pass command first?
+
+    args.insert(0, "/C".to_string());
+    if inherit {
+        return set_output_inherit(cmd.args(args).current_dir(current_dir().unwrap()));
+    }
+    set_output_piped(cmd.args(args).current_dir(current_dir().unwrap()))
+}
+
+fn image_setup(setup: &mut ExecInfo, outputs: &mut Vec<String>) {
+    if setup.image.is_none() {
+        setup.image = Some("alpine:latest".to_string());
+        warn!(
+            "{} There was no image detected in a configured action.",
+            setup.log_context(None)
+        );
+        outputs.push(
+            "There was no docker image found to build off of. Using Alpine Linux by default."
+                .to_string(),
+        );
+    }
+}
+
+/// Decides whether a base image is pulled under `policy`, only checking whether the image is present when needed
+fn should_pull(policy: PullPolicy, image_present: impl FnOnce() -> bool) -> bool {
+    match policy {
+        PullPolicy::Always => true,
+        PullPolicy::Missing => !image_present(),
+        PullPolicy::Never => false,
+    }
+}
+
+/// Returns why a docker action cannot be built under `policy`, which is when the policy never pulls the base image and
+/// the image is not present locally
+fn missing_image_problem(
+    policy: PullPolicy,
+    image_present: impl FnOnce() -> bool,
+) -> Option<String> {
+    (policy == PullPolicy::Never && !image_present()).then(|| {
+        "The image is not present locally, and is never pulled as the pull_policy is never."
+            .to_string()
+    })
+}
+
+/// Returns whether the base image of a docker action is already present locally
+fn image_present(info: &ExecInfo) -> bool {
+    Command::new("docker")
+        .args(["image", "inspect", info.image.as_ref().unwrap()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Returns the arguments that select the platform of an image, if one is configured
+fn docker_platform_args(info: &ExecInfo) -> Vec<String> {
+    match &info.platform {
+        Some(platform) => vec!["--platform".to_string(), platform.to_string()],
+        None => vec![],
+    }
+}
+
+fn docker_setup_unix<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    let mut args = vec!["docker".to_string(), "pull".to_string()];
+    args.append(&mut docker_platform_args(info));
+    args.push(info.image.clone().unwrap());
+    cmd.arg("-c").arg(args.join(" ")).current_dir(&info.source);
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn docker_setup_windows<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    cmd.args(["/C", "docker", "pull"])
+        .args(docker_platform_args(info))
+        .arg(info.image.clone().unwrap())
+        .current_dir(&info.source);
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn docker_clean_unix<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    cmd.arg("-c").arg(format!("docker image rm -f {}", info.image_tag));
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn docker_clean_windows<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    cmd.args(["/C", "docker", "image", "rm", "-f", &info.image_tag]);
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn docker_build_unix<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    let mut args = vec!["docker".to_string(), "build".to_string()];
+    args.append(&mut docker_platform_args(info));
+    args.extend(["-t", &info.image_tag, docker_context(info)].map(str::to_string));
+    cmd.arg("-c").arg(args.join(" ")).current_dir(&info.source);
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn docker_build_windows<'a>(cmd: &'a mut Command, info: &ExecInfo, inherit: bool) -> &'a mut Command {
+    cmd.args(["/C", "docker", "build"])
+        .args(docker_platform_args(info))
+        .args(["-t", &info.image_tag, docker_context(info)])
+        .current_dir(&info.source);
+    if inherit {
+        return set_output_inherit(cmd);
+    }
+    set_output_piped(cmd)
+}
+
+fn command_setup_unix<'a>(
+    cmd: &'a mut Command,
+    args: &mut Vec<String>,
+    inherit: bool,
+) -> &'a mut Command {
+    let mut arg_string = String::new();
+    for arg in args {
+        arg_string += &(arg.to_owned() + " ");
+    }
+
+    arg_string = arg_string.trim().to_string();
+    if inherit {
+        return set_output_inherit(cmd.arg("-c").arg(arg_string));
+    }
+    set_output_piped(cmd.arg("-c").arg(arg_string))
+}
+
+/// Collects the output of a step, returning whether the step exited successfully
+///
+/// Potential issues:
+/// Some success outputs may be read as failures on Linux environments. Look into this more.
+fn collect_piped_output(
+    setup: &ExecInfo,
+    step: &Step,
+    output: &Output,
+    outputs: &mut Vec<String>,
+) -> bool {
+    let context = setup.log_context(Some(step));
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    let failure = if !output.status.success() {
+        Some(format!("Step {} failed with {}", step.get_name(), output.status))
+    } else if !step_succeeded(setup, output) {
+        Some(format!(
+            "Step {} wrote to its standard error, which fails it as fail_on_stderr is enabled",
+            step.get_name()
+        ))
+    } else {
+        None
+    };
+    for line in format_step_output(setup.ci, &context, step, &stdout, &stderr, failure.as_deref()) {
+        println!("{}", line);
+    }
+
+    outputs.push(if stdout.is_empty() {
+        if stderr.is_empty() {
+            "No standard output detected. Check to see if it was piped to another file.".to_string()
+        } else {
+            error!("{} Standard error: {}", context, stderr);
+            stderr
+        }
+    } else {
+        info!("{} Standard output: {}", context, stdout);
+        stdout
+    });
+    if let Some(failure) = &failure {
+        error!("{} {}", context, failure);
+    }
+    failure.is_none()
+}
+
+/// Returns whether a step that finished with `output` succeeded, which requires it to exit successfully and, if the
+/// action enables `fail_on_stderr`, to have written nothing to its standard error
+fn step_succeeded(setup: &ExecInfo, output: &Output) -> bool {
+    output.status.success() && (!setup.fail_on_stderr || output.stderr.is_empty())
+}
+
+/// Formats the lines printed once a step has finished, according to `ci`
+fn format_step_output(
+    ci: CiFormat,
+    context: &str,
+    step: &Step,
+    stdout: &str,
+    stderr: &str,
+    failure: Option<&str>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    match ci {
+        CiFormat::Plain => {
+            lines.push(format!("stdout from {}: {stdout}", step.get_name()));
+            lines.push(format!("stderr from {}: {stderr}", step.get_name()));
+        }
+        CiFormat::Github => {
+            lines.push(format!("::group::{}", context));
+            lines.extend(
+                [stdout, stderr]
+                    .iter()
+                    .map(|output| output.trim_end())
+                    .filter(|output| !output.is_empty())
+                    .map(str::to_string),
+            );
+            lines.push("::endgroup::".to_string());
+            if let Some(failure) = failure {
+                lines.push(format!("::error::{} {}", context, escape_github_data(failure)));
+            }
+        }
+    }
+    lines
+}
+
+/// Escapes a message so it can be used within a GitHub Actions workflow command
+fn escape_github_data(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn set_output_inherit(command: &mut Command) -> &mut Command {
+    command.stdout(Stdio::inherit()).stderr(Stdio::inherit())
+}
+
+fn set_output_piped(command: &mut Command) -> &mut Command {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped())
+}
+
+fn script_setup(setup: &ExecInfo, outputs: &mut Vec<String>, step: &Step) -> Vec<String> {
+    let output_str = format_args!("Running {}", step.get_name()).to_string();
+    info!("{} {}", setup.log_context(Some(step)), output_str);
+    println!("{}", output_str);
+    outputs.push(output_str);
+    let script = step.get_script().to_string();
+    println!("{}", setup.redact_text(&script));
+    script_arguments(setup, &script)
+}
+
+/// Splits `script` into its arguments, which have their relative paths resolved unless the action disables clean_paths
+fn script_arguments(setup: &ExecInfo, script: &str) -> Vec<String> {
+    if setup.clean_paths {
+        clean_script_pathing(script)
+    } else {
+        script.split(' ').map(str::to_string).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsing::json_parser;
+
+    // use crate::parsing::Parser;
+
+    // use crate::executor::executor;
+
+    // #[test]
+    // fn prove_exec_info() {
+    //     let test_config = Parser::new_top_level("example_docker_config.json");
+    //     let actions = test_config.get_all_actions();
+    //     let exec_info = executor::ExecInfo {}
+    // }
+
+    #[test]
+    fn clean_script_pathing_only_resolves_relative_paths() {
+        let root = current_dir().unwrap();
+        let resolve = |item: &str| root.join(item).to_str().unwrap().to_string();
+        assert_eq!(
+            clean_script_pathing_in("cat ./Cargo.toml src/../Cargo.toml", &root),
+            ["cat", &resolve("./Cargo.toml"), &resolve("src/../Cargo.toml")]
+        );
+        assert_eq!(
+            clean_script_pathing_in("curl https://example.com/./a/../b", &root),
+            ["curl", "https://example.com/./a/../b"]
+        );
+        assert_eq!(
+            clean_script_pathing_in("cargo build --path=./x -o ./out", &root),
+            ["cargo", "build", "--path=./x", "-o", &resolve("./out")]
+        );
+        assert_eq!(clean_script_pathing_in("echo a/./b", &root), ["echo", "a/./b"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failure_context_masks_secrets() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo oops >&2; exit 3"]);
+        let command = command_line(&cmd);
+        let output = cmd.output().unwrap();
+        let env = [
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("GITHUB_TOKEN".to_string(), "ghp_hunter2".to_string()),
+        ];
+        let step = Step::new("fail".to_string(), "exit 3".to_string());
+        let context = failure_context(&step, &command, &output, env.into_iter());
+        assert_eq!(
+            context,
+            [
+                "Step fail failed".to_string(),
+                format!("  command:     {}", command),
+                format!("  working dir: {}", command.working_dir),
+                "  exit code:   3".to_string(),
+                "  environment:".to_string(),
+                "    GITHUB_TOKEN=********".to_string(),
+                "    PATH=/usr/bin".to_string(),
+                "  stderr:".to_string(),
+                "    oops".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn array_scripts_run_like_their_joined_lines() {
+        let run = |script: &str| {
+            let config = json_parser::parse_from_str(&format!(
+                r#"{{
+                    "actions": ["Lines"],
+                    "Lines": {{
+                        "manual": {{ "short": {}, "long": {{ "script": {} }} }}
+                    }}
+                }}"#,
+                script, script
+            ));
+            let action = &config.get_actions()[0];
+            let result = exec_action(action, &ExecOptions::default()).unwrap();
+            (action.action_config.get_manual().to_vec(), result.status, result.output)
+        };
+        let (array_steps, array_status, array_output) =
+            run(r#"["echo one", "false", "echo two"]"#);
+        let (joined_steps, joined_status, joined_output) =
+            run(r#""echo one && false && echo two""#);
+        assert_eq!(array_steps, joined_steps);
+        assert_eq!(array_steps[0].get_script(), "echo one && false && echo two");
+        assert_eq!(array_status, Status::Failure);
+        assert_eq!((array_status, array_output), (joined_status, joined_output));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scripts_run_verbatim_without_clean_paths() {
+        let run = |clean_paths: bool| {
+            let config = json_parser::parse_from_str(&format!(
+                r#"{{
+                    "clean_paths": {},
+                    "actions": ["Echo"],
+                    "Echo": {{ "manual": {{ "echo": "echo ./x" }} }}
+                }}"#,
+                clean_paths
+            ));
+            exec_action(&config.get_actions()[0], &ExecOptions::default())
+                .unwrap()
+                .output
+                .last()
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+        assert_eq!(run(false), "./x");
+        assert_eq!(run(true), current_dir().unwrap().join("./x").to_str().unwrap());
+    }
+
+    #[test]
+    fn create_command_windows() {
+        //
+        let input1 = "input";
+        let input2 = "input";
+        assert!(input1 == input2);
+    }
+
+    #[cfg(unix)]
+    fn cd_then_pwd(shared_shell: bool) -> String {
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "actions": ["cd_then_pwd"],
+                "cd_then_pwd": {{
+                    "shared_shell": {},
+                    "manual": {{ "cd": "cd /", "pwd": "pwd" }}
+                }}
+            }}"#,
+            shared_shell
+        ));
+        exec_action(&config.get_actions()[0], &ExecOptions::default())
+            .unwrap()
+            .output
+            .last()
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn merge_output_keeps_stdout_and_stderr_in_order() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["interleaved"],
+                "interleaved": {
+                    "merge_output": true,
+                    "manual": { "interleave": "echo first ; echo second >&2 ; echo third" }
+                }
+            }"#,
+        );
+        let result = exec_action(&config.get_actions()[0], &ExecOptions::default()).unwrap();
+        assert_eq!(result.output.last().unwrap(), "first\nsecond\nthird\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn stdin_is_written_to_the_step() {
+        let input = std::env::temp_dir().join(format!("cider_stdin_test_{}", std::process::id()));
+        fs::write(&input, "from a file").unwrap();
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "actions": ["piped"],
+                "piped": {{
+                    "manual": {{
+                        "literal": {{ "script": "cat", "stdin": "piped in" }},
+                        "file": {{ "script": "cat", "stdin": "@{}" }},
+                        "none": "echo no stdin"
+                    }}
+                }}
+            }}"#,
+            input.display()
+        ));
+        let result = exec_action(&config.get_actions()[0], &ExecOptions::default()).unwrap();
+        fs::remove_file(input).unwrap();
+        assert_eq!(result.status, Status::Success);
+        assert!(result.output.contains(&"piped in".to_string()));
+        assert!(result.output.contains(&"from a file".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn steps_can_be_allowed_to_fail_on_their_own() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["Expendable", "Required", "Strict"],
+                "Expendable": {
+                    "manual": {
+                        "lint": { "script": "exit 1", "allowed_failure": true },
+                        "build": "echo built"
+                    }
+                },
+                "Required": {
+                    "manual": {
+                        "lint": { "script": "exit 1", "allowed_failure": true },
+                        "test": "exit 1"
+                    }
+                },
+                "Strict": {
+                    "allowed_failure": true,
+                    "manual": {
+                        "optional": "exit 1",
+                        "required": { "script": "exit 1", "allowed_failure": false }
+                    }
+                }
+            }"#,
+        );
+        assert_eq!(
+            config.get_actions()[0].action_config.get_manual()[0].get_allowed_failure(),
+            Some(true)
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| result.status)
+                .collect::<Vec<_>>(),
+            [Status::AllowedFailure, Status::Failure, Status::Failure]
+        );
+        assert!(report.actions[0].output.contains(&"built\n".to_string()));
+        assert_eq!(report.actions[1].timings.len(), 2);
+    }
+
+    #[test]
+    fn docker_metrics_are_written_to_the_configured_directory() {
+        let dir = std::env::temp_dir().join(format!("cider_metrics_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "backend": "docker",
+                "image": "Cider-Invalid-Image",
+                "source_directory": "{}",
+                "metrics_dir": "{}",
+                "metrics_filename": "{{date}}_{{action}}.csv",
+                "actions": ["Build image"],
+                "Build image": {{ "pull_policy": "always", "manual": {{ "build": "cargo build" }} }}
+            }}"#,
+            dir.display(),
+            dir.join("metrics").display()
+        ));
+        // An image name that is not valid fails to pull straight away, with or without a daemon, so
+        // the CSV holds only the decision to pull and how long the pull took
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        let files = fs::read_dir(dir.join("metrics/win"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        let contents = fs::read_to_string(&files[0]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(report.actions[0].status, Status::Failure);
+        assert_eq!(files.len(), 1);
+        assert!(files[0].to_string_lossy().ends_with("_Build_image.csv"));
+        let mut lines = contents.lines();
+        assert_eq!(
+            lines.next(),
+            Some("Image_pulled,Image_pull_time,Image_remove_time,Image_build_time")
+        );
+        let row = lines.next().unwrap().split(',').collect::<Vec<_>>();
+        assert_eq!(row[0], "true");
+        assert!(!row[1].is_empty());
+        assert_eq!(row[2..], ["", ""]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn step_results_describe_each_step() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["Build"],
+                "Build": {
+                    "manual": {
+                        "build": "echo built",
+                        "lint": "echo warned >&2 && exit 3"
+                    }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        let steps = &report.actions[0].steps;
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| (step.name.as_str(), step.status, step.exit_code))
+                .collect::<Vec<_>>(),
+            [("build", Status::Success, Some(0)), ("lint", Status::Failure, Some(3))]
+        );
+        assert_eq!(steps[0].stdout, "built\n");
+        assert_eq!(steps[1].stderr, "warned\n");
+        assert_eq!(report.to_legacy_strings(), [report.actions[0].output.clone()]);
+        assert!(steps.iter().all(|step| step.resources.is_none()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn profiled_steps_record_their_resources() {
+        let action = Action::builder("Build")
+            .step("build", "i=0; while [ $i -lt 20000 ]; do i=$((i + 1)); done")
+            .step("lint", "exit 2")
+            .build();
+        let options = ExecOptions {
+            profile_resources: true,
+            step_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let report = exec_actions_with_options(&[action], &options).unwrap();
+        let steps = &report.actions[0].steps;
+        assert_eq!(steps[1].exit_code, Some(2));
+        let usage = steps[0].resources.unwrap();
+        assert!(usage.peak_memory_bytes > 0);
+        assert!(usage.cpu_time > Duration::ZERO);
+        assert!(steps[1].resources.is_some());
+        let stats = report.stats(Duration::from_secs(1), 1);
+        assert!(stats.iter().any(|row| row.starts_with("peak memory")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn concurrent_steps_run_together_and_report_in_order() {
+        let marker = std::env::temp_dir().join(format!("cider_concurrency_test_{}", std::process::id()));
+        let _ = fs::remove_file(&marker);
+        // The first step only finishes once the second has run, so the action would time out if they ran in turn
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "actions": ["together"],
+                "together": {{
+                    "concurrency": 2,
+                    "step_timeout": 10,
+                    "manual": {{
+                        "waits": "while [ ! -f {marker} ]; do sleep 0.01; done; echo waited",
+                        "signals": "touch {marker} && echo signalled",
+                        "fails": "echo failed >&2; exit 1"
+                    }}
+                }}
+            }}"#,
+            marker = marker.display()
+        ));
+        let result = exec_action(&config.get_actions()[0], &ExecOptions::default()).unwrap();
+        let _ = fs::remove_file(&marker);
+        assert_eq!(result.status, Status::Failure);
+        let outputs = result
+            .output
+            .iter()
+            .filter(|output| !output.starts_with("Step fails failed"))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outputs,
+            [
+                "Running waits",
+                "waited\n",
+                "Running signals",
+                "signalled\n",
+                "Running fails",
+                "failed\n"
+            ]
+        );
+        assert_eq!(
+            result.timings.iter().map(|timing| timing.name.as_str()).collect::<Vec<_>>(),
+            ["waits", "signals", "fails"]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn steps_record_the_command_they_ran() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["greet"],
+                "greet": { "manual": { "hello": "echo hello" } }
+            }"#,
+        );
+        let result = exec_action(&config.get_actions()[0], &ExecOptions::default()).unwrap();
+        let command = result.timings[0].command.as_ref().unwrap();
+        assert_eq!(command.program, "sh");
+        assert_eq!(command.args, ["-c", "echo hello"]);
+        assert_eq!(command.working_dir, current_dir().unwrap().to_string_lossy());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn step_output_past_the_limit_is_truncated() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["noisy"],
+                "noisy": { "manual": { "flood": "head -c 100 /dev/zero | tr '\\0' a; echo done" } }
+            }"#,
+        );
+        let options = ExecOptions {
+            max_output_bytes: Some(10),
+            ..ExecOptions::default()
+        };
+        let result = exec_action(&config.get_actions()[0], &options).unwrap();
+        assert_eq!(result.status, Status::Success);
+        assert_eq!(result.output.last().unwrap(), "aaaaaaaaaa\n... (truncated 95 bytes)\n");
+    }
+
+    #[test]
+    fn dockerfile_uses_the_configured_workdir_and_user() {
+        let source = std::env::temp_dir().join(format!("cider_workdir_test_{}", std::process::id()));
+        fs::create_dir_all(&source).unwrap();
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "backend": "docker",
+                "image": "rust:latest",
+                "source_directory": "{}",
+                "actions": ["Default", "Custom", "User"],
+                "Default": {{ "manual": {{ "build": "cargo build" }} }},
+                "Custom": {{ "docker_workdir": "/workspace/app", "manual": {{ "build": "cargo build" }} }},
+                "User": {{ "docker_user": "builder:staff", "manual": {{ "build": "cargo build" }} }}
+            }}"#,
+            source.display()
+        ));
+        let mut dockerfiles = vec![];
+        for action in config.get_actions() {
+            generate_dockerfile(&ExecInfo::new(action)).unwrap();
+            dockerfiles.push(fs::read_to_string(source.join("Dockerfile")).unwrap());
+        }
+        fs::remove_dir_all(source).unwrap();
+        assert_eq!(
+            dockerfiles[0],
+            "FROM rust:latest\r\nWORKDIR /cider/app\r\nCOPY . ./\r\nRUN cargo build\r\n"
+        );
+        assert_eq!(
+            dockerfiles[1],
+            "FROM rust:latest\r\nWORKDIR /workspace/app\r\nCOPY . ./\r\nRUN cargo build\r\n"
+        );
+        assert_eq!(
+            dockerfiles[2],
+            "FROM rust:latest\r\nWORKDIR /cider/app\r\nCOPY --chown=builder:staff . ./\r\n\
+             RUN chown builder:staff .\r\nUSER builder:staff\r\nRUN cargo build\r\n"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn before_each_and_after_each_run_around_every_action() {
+        let marker = std::env::temp_dir().join(format!("cider_each_{}", std::process::id()));
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "before_each": {{ "before": "echo before >> {0}" }},
+                "after_each": {{ "after": "echo after >> {0}" }},
+                "pipelines": ["Checks"],
+                "actions": ["Build"],
+                "post_run": ["Clean"],
+                "Build": {{ "manual": {{ "build": "echo build >> {0}" }} }},
+                "Clean": {{ "manual": {{ "clean": "echo clean >> {0}" }} }},
+                "Checks": {{
+                    "actions": ["Lint", "Test"],
+                    "Lint": {{ "manual": {{ "lint": "echo lint >> {0} && exit 1" }} }},
+                    "Test": {{ "manual": {{ "test": "echo test >> {0}" }} }}
+                }}
+            }}"#,
+            marker.display()
+        ));
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        exec_post_run(&config, &ExecOptions::default());
+        let order = fs::read_to_string(&marker).unwrap_or_default();
+        let _ = fs::remove_file(&marker);
+        assert_eq!(
+            order.lines().collect::<Vec<_>>(),
+            [
+                "before", "build", "after", "before", "lint", "after", "before", "test", "after",
+                "clean"
+            ]
+        );
+        let lint = &report.actions[1];
+        assert_eq!(lint.status, Status::Failure);
+        assert_eq!(
+            lint.steps.iter().map(|step| step.name.as_str()).collect::<Vec<_>>(),
+            ["before_each: before", "lint", "after_each: after"]
+        );
+        assert_eq!(report.actions[2].status, Status::Success);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failing_before_each_fails_the_action_without_running_it() {
+        let marker = std::env::temp_dir().join(format!("cider_before_each_{}", std::process::id()));
+        let action = Action::builder("Build")
+            .step("build", format!("touch {}", marker.display()))
+            .build();
+        let options = ExecOptions {
+            before_each: vec![Step::from_command("check", "exit 1")],
+            after_each: vec![Step::from_command("report", "echo done")],
+            ..Default::default()
+        };
+        let report = exec_actions_with_options(&[action], &options).unwrap();
+        let ran = marker.exists();
+        let _ = fs::remove_file(&marker);
+        assert!(!ran);
+        assert_eq!(report.actions[0].status, Status::Failure);
+        assert_eq!(
+            report.actions[0].steps.iter().map(|step| step.name.as_str()).collect::<Vec<_>>(),
+            ["before_each: check", "after_each: report"]
+        );
+    }
+
+    #[cfg(unix)]
+    fn post_run_config(main_step: &str, post_run_step: &str, marker: &Path) -> TopLevelConfiguration {
+        json_parser::parse_from_str(&format!(
+            r#"{{
+                "actions": ["Main"],
+                "post_run": ["Clean", "Record"],
+                "Main": {{ "manual": {{ "main": "{}" }} }},
+                "Clean": {{ "manual": {{ "clean": "{}" }} }},
+                "Record": {{ "manual": {{ "record": "touch {}" }} }}
+            }}"#,
+            main_step,
+            post_run_step,
+            marker.display()
+        ))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn post_run_runs_after_a_failed_run() {
+        let marker = std::env::temp_dir().join(format!("cider_post_run_failed_{}", std::process::id()));
+        let config = post_run_config("exit 1", "echo cleaned", &marker);
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        let post_run = exec_post_run(&config, &ExecOptions::default());
+        let recorded = marker.exists();
+        let _ = fs::remove_file(&marker);
+        assert!(!report.succeeded());
+        assert!(post_run.succeeded());
+        assert_eq!(post_run.actions.len(), 2);
+        assert!(recorded);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failing_post_run_does_not_stop_the_others_or_fail_the_run() {
+        let marker = std::env::temp_dir().join(format!("cider_post_run_succeeded_{}", std::process::id()));
+        let config = post_run_config("echo main", "exit 1", &marker);
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        let post_run = exec_post_run(&config, &ExecOptions::default());
+        let recorded = marker.exists();
+        let _ = fs::remove_file(&marker);
+        assert!(report.succeeded());
+        assert_eq!(report.actions.len(), 1);
+        assert!(!post_run.succeeded());
+        assert_eq!(post_run.actions[0].status, Status::Failure);
+        assert!(recorded);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fail_on_stderr_fails_steps_that_write_to_stderr() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["lenient", "strict"],
+                "lenient": { "manual": { "warn": "echo warning >&2" } },
+                "strict": {
+                    "fail_on_stderr": true,
+                    "manual": { "quiet": "echo fine", "warn": "echo warning >&2", "after": "echo after" }
+                }
+            }"#,
+        );
+        let lenient = exec_action(&config.get_actions()[0], &ExecOptions::default()).unwrap();
+        assert_eq!(lenient.status, Status::Success);
+        let strict = exec_action(&config.get_actions()[1], &ExecOptions::default()).unwrap();
+        assert_eq!(strict.status, Status::Failure);
+        assert!(strict.output.contains(&"after\n".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shared_shell_keeps_state_between_steps() {
+        assert_eq!(cd_then_pwd(true), "/");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn isolated_steps_do_not_share_state() {
+        assert_eq!(
+            cd_then_pwd(false),
+            current_dir().unwrap().to_str().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    fn failing_pipeline_report(allowed_failure: bool) -> RunReport {
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "pipelines": ["Optional"],
+                "Optional": {{
+                    "allowed_failure": {},
+                    "actions": ["fail"],
+                    "fail": {{ "manual": {{ "fail": "exit 1" }} }}
+                }}
+            }}"#,
+            allowed_failure
+        ));
+        exec_config(&config, &ExecOptions::default()).unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn allowed_pipeline_failure_is_not_fatal() {
+        let report = failing_pipeline_report(true);
+        assert_eq!(report.actions[0].status, Status::Failure);
+        assert_eq!(report.pipeline_status("Optional"), Status::AllowedFailure);
+        assert!(report.succeeded());
+        assert!(report
+            .summary()
+            .contains(&"pipeline Optional failed (allowed)".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn disallowed_pipeline_failure_is_fatal() {
+        let report = failing_pipeline_report(false);
+        assert_eq!(report.pipeline_status("Optional"), Status::Failure);
+        assert!(!report.succeeded());
+        assert!(report
+            .summary()
+            .contains(&"pipeline Optional failed".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unmet_warning_condition_runs_action() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["warned", "blocked"],
+                "warned": {
+                    "conditions": { "Plan 9 only": { "condition": "os:plan9", "mode": "warn" } },
+                    "manual": { "pass": "echo pass" }
+                },
+                "blocked": {
+                    "conditions": { "Plan 9 only": { "condition": "os:plan9", "mode": "block" } },
+                    "manual": { "fail": "exit 1" }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions[0].status, Status::Success);
+        assert_eq!(report.actions[1].status, Status::Skipped);
+        assert_eq!(
+            report.explain(),
+            vec![
+                "action warned passed",
+                "    condition \"Plan 9 only\" (os:plan9) was not met, which only warns",
+                "action blocked skipped",
+                "    condition \"Plan 9 only\" (os:plan9) was not met",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn actions_inherit_the_conditions_of_their_pipeline() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Plan 9"],
+                "actions": ["anywhere"],
+                "anywhere": { "manual": { "pass": "echo pass" } },
+                "Plan 9": {
+                    "conditions": { "Plan 9 only": "os:plan9" },
+                    "actions": ["blocked"],
+                    "blocked": { "manual": { "fail": "exit 1" } }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions[0].status, Status::Success);
+        assert_eq!(report.actions[1].status, Status::Skipped);
+        assert!(report.succeeded());
+        assert!(report
+            .explain()
+            .contains(&"    condition \"Plan 9 only\" (os:plan9) was not met".to_string()));
+
+        let config = json_parser::parse_from_str(
+            r#"{
+                "conditions": { "Plan 9 only": "os:plan9" },
+                "actions": ["blocked"],
+                "blocked": { "manual": { "fail": "exit 1" } }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions[0].status, Status::Skipped);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn disabled_action_is_skipped() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["disabled", "enabled"],
+                "disabled": {
+                    "skip": true,
+                    "manual": { "fail": "exit 1" }
+                },
+                "enabled": {
+                    "skip": false,
+                    "manual": { "pass": "echo pass" }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions[0].status, Status::Disabled);
+        assert!(report.actions[0].steps.is_empty());
+        assert!(report.succeeded());
+        assert_eq!(
+            report.summary(),
+            vec![
+                "action disabled skipped (disabled)",
+                "action enabled passed",
+                "actions 2, 1 passed, 1 skipped (disabled)",
+                "run passed",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unmet_condition_skips_action() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["never", "always"],
+                "never": {
+                    "conditions": { "Never run": "os:plan9" },
+                    "manual": { "fail": "exit 1" }
+                },
+                "always": {
+                    "conditions": { "Always run": "true" },
+                    "manual": { "pass": "echo pass" }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions[0].status, Status::Skipped);
+        assert_eq!(report.actions[1].status, Status::Success);
+        assert!(report.succeeded());
+        assert_eq!(
+            report.explain(),
+            vec![
+                "action never skipped",
+                "    condition \"Never run\" (os:plan9) was not met",
+                "action always passed",
+                "    condition \"Always run\" (true) was met",
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_backend_is_an_error() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "powershell",
+                "actions": ["greet"],
+                "greet": { "manual": { "greet": "echo hello" } }
+            }"#,
+        );
+        match exec_actions(config.get_actions()) {
+            Err(CustomError::UnsupportedBackend(backend)) => assert_eq!(backend, "powershell"),
+            other => panic!("Expected an unsupported backend error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn docker_commands_include_platform() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "image": "rust:latest",
+                "platform": "linux/amd64",
+                "source_directory": "./",
+                "actions": ["build"],
+                "build": { "manual": { "build": "cargo build" } }
+            }"#,
+        );
+        let mut info = ExecInfo::new(&config.get_actions()[0]);
+        let mut cmd = Command::new("sh");
+        let build = docker_build_unix(&mut cmd, &info, false);
+        assert_eq!(
+            build.get_args().collect::<Vec<_>>(),
+            ["-c", "docker build --platform linux/amd64 -t cider-image-build ."]
+        );
+        info.docker_context_tar = true;
+        let mut cmd = Command::new("sh");
+        let build = docker_build_unix(&mut cmd, &info, false);
+        assert_eq!(
+            build.get_args().collect::<Vec<_>>(),
+            ["-c", "docker build --platform linux/amd64 -t cider-image-build -"]
+        );
+        info.docker_context_tar = false;
+        let mut cmd = Command::new("sh");
+        let pull = docker_setup_unix(&mut cmd, &info, false);
+        assert_eq!(
+            pull.get_args().collect::<Vec<_>>(),
+            ["-c", "docker pull --platform linux/amd64 rust:latest"]
+        );
+    }
+
+    #[test]
+    fn docker_actions_use_distinct_image_tags() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "source_directory": "./",
+                "pipelines": ["Build Pipeline", "Test_Pipeline"],
+                "Build Pipeline": {
+                    "actions": ["build"],
+                    "build": { "manual": { "build": "cargo build" } }
+                },
+                "Test_Pipeline": {
+                    "actions": ["build", "Unit Tests."],
+                    "build": { "manual": { "build": "cargo build" } },
+                    "Unit Tests.": { "manual": { "test": "cargo test" } }
+                }
+            }"#,
+        );
+        let infos = config
+            .get_pipelines()
+            .iter()
+            .flat_map(|pipeline| pipeline.pipeline_config.get_actions())
+            .map(ExecInfo::new)
+            .collect::<Vec<_>>();
+        let tags = infos.iter().map(|info| info.image_tag.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            [
+                "cider-image-build-pipeline-build",
+                "cider-image-test-pipeline-build",
+                "cider-image-test-pipeline-unit-tests"
+            ]
+        );
+        // Each action builds, removes and looks up only its own image
+        for info in &infos {
+            let mut cmd = Command::new("sh");
+            let build = docker_build_unix(&mut cmd, info, false);
+            let build = build.get_args().last().unwrap().to_string_lossy().into_owned();
+            assert!(build.contains(&format!("-t {} ", info.image_tag)));
+            let mut cmd = Command::new("sh");
+            let clean = docker_clean_unix(&mut cmd, info, false);
+            let clean = clean.get_args().last().unwrap().to_string_lossy().into_owned();
+            assert_eq!(clean, format!("docker image rm -f {}", info.image_tag));
+        }
+        assert_eq!(default_image_tag(None, None), "cider-image-untitled");
+    }
+
+    #[test]
+    fn dockerignore_includes_gitignore_patterns() {
+        let source = std::env::temp_dir().join(format!("cider_dockerignore_{}", std::process::id()));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join(".gitignore"), "target/\n# build output\n/dist\n").unwrap();
+        std::fs::write(source.join(".dockerignore"), "**/target\n.git\n").unwrap();
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "backend": "docker",
+                "use_gitignore": true,
+                "source_directory": "{}",
+                "actions": ["build"],
+                "build": {{ "manual": {{ "build": "cargo build" }} }}
+            }}"#,
+            source.to_str().unwrap()
+        ));
+        generate_dockerignore(&ExecInfo::new(&config.get_actions()[0])).unwrap();
+        let dockerignore = std::fs::read_to_string(source.join(".dockerignore")).unwrap();
+        std::fs::remove_dir_all(source).unwrap();
+        assert_eq!(dockerignore, "**/target\n.git\ndist\n");
+    }
+
+    #[test]
+    fn rendered_dockerignore_adds_missing_patterns() {
+        let patterns = ["target/", "# build output", "/dist", "*.log"]
+            .into_iter()
+            .filter_map(ignore::IgnorePattern::parse)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            render_dockerignore("**/target\n.git\n", &patterns),
+            "**/target\n.git\ndist\n**/*.log\n"
+        );
+        assert_eq!(render_dockerignore("", &[]), "\n");
+    }
+
+    #[test]
+    fn rendered_dockerfile_runs_each_step_as_a_layer() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "image": "rust:1.65.0",
+                "actions": ["Build"],
+                "Build": {
+                    "docker_workdir": "/workspace",
+                    "manual": {
+                        "version": "rustc --version",
+                        "build": "cargo build --release",
+                        "test": "cargo test && cargo doc"
+                    }
+                }
+            }"#,
+        );
+        assert_eq!(
+            render_dockerfile(&ExecInfo::new(&config.get_actions()[0])),
+            "FROM rust:1.65.0\r\nWORKDIR /workspace\r\nCOPY . ./\r\nRUN rustc --version\r\n\
+             RUN cargo build --release\r\nRUN cargo test && cargo doc\r\n"
+        );
+    }
+
+    #[test]
+    fn pull_policy_decides_whether_to_pull() {
+        assert!(should_pull(PullPolicy::Missing, || false));
+        assert!(!should_pull(PullPolicy::Missing, || true));
+        assert!(should_pull(PullPolicy::Always, || {
+            panic!("The image should not be inspected when always pulling")
+        }));
+        assert!(!should_pull(PullPolicy::Never, || {
+            panic!("The image should not be inspected when it is never pulled")
+        }));
+    }
+
+    #[test]
+    fn never_pulling_requires_the_image_to_be_present() {
+        assert!(missing_image_problem(PullPolicy::Never, || false).is_some());
+        assert!(missing_image_problem(PullPolicy::Never, || true).is_none());
+        for policy in [PullPolicy::Missing, PullPolicy::Always] {
+            assert!(missing_image_problem(policy, || {
+                panic!("The image should only be inspected here when it is never pulled")
+            })
+            .is_none());
+        }
+    }
+
+    #[test]
+    fn actions_choose_their_own_pull_policy() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "image": "rust:latest",
+                "actions": ["Offline", "Fresh", "Default"],
+                "Offline": { "pull_policy": "never", "manual": { "build": "cargo build" } },
+                "Fresh": { "pull_policy": "Always", "manual": { "build": "cargo build" } },
+                "Default": { "manual": { "build": "cargo build" } }
+            }"#,
+        );
+        let policies = config
+            .get_actions()
+            .iter()
+            .map(|action| ExecInfo::new(action).pull_policy.unwrap_or(PullPolicy::Missing))
+            .collect::<Vec<_>>();
+        assert_eq!(policies, [PullPolicy::Never, PullPolicy::Always, PullPolicy::Missing]);
+        assert_eq!(config.get_actions()[0].to_json()["pull_policy"], "never");
+    }
+
+    #[test]
+    fn github_output_groups_steps_and_annotates_failures() {
+        let step = Step::new("build".to_string(), "cargo build".to_string());
+        let lines = format_step_output(
+            CiFormat::Github,
+            "[action=Build step=build]",
+            &step,
+            "compiling\n",
+            "",
+            Some("Step build failed\nwith 100%"),
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "::group::[action=Build step=build]",
+                "compiling",
+                "::endgroup::",
+                "::error::[action=Build step=build] Step build failed%0Awith 100%25",
+            ]
+        );
+        let lines = format_step_output(CiFormat::Plain, "", &step, "compiling", "", None);
+        assert_eq!(lines[0], "stdout from build: compiling");
+    }
+
+    #[test]
+    fn log_context_names_pipeline_action_and_step() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Build_Pipeline"],
+                "Build_Pipeline": {
+                    "actions": ["Build"],
+                    "Build": { "manual": { "build": "echo build" } }
+                },
+                "actions": ["Lint"],
+                "Lint": { "manual": { "lint": "echo lint" } }
+            }"#,
+        );
+        let actions = config.get_all_actions();
+        let build = actions.iter().find(|action| action.get_pipeline().is_some()).unwrap();
+        let build_info = ExecInfo::new(build);
+        assert_eq!(
+            build_info.log_context(Some(&build_info.manual[0])),
+            "[pipeline=Build_Pipeline action=Build step=build]"
+        );
+        let lint = actions.iter().find(|action| action.get_pipeline().is_none()).unwrap();
+        assert_eq!(ExecInfo::new(lint).log_context(None), "[action=Lint]");
+    }
+
+    #[test]
+    fn cyclic_requires_are_rejected() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["A", "B", "C"],
+                "A": { "requires": ["C"], "actions": ["a"], "a": { "manual": { "a": "echo a" } } },
+                "B": { "requires": ["A"], "actions": ["b"], "b": { "manual": { "b": "echo b" } } },
+                "C": { "requires": ["B"], "actions": ["c"], "c": { "manual": { "c": "echo c" } } }
+            }"#,
+        );
+        match exec_config(&config, &ExecOptions::default()) {
+            Err(CustomError::CyclicRequirement(cycle)) => assert_eq!(cycle, ["A", "C", "B", "A"]),
+            other => panic!("Expected a cyclic requirement error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn pipelines_stop_waiting_for_requires_after_the_timeout() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Test", "Deploy", "Build"],
+                "Test": {
+                    "requires": ["Build.build"],
+                    "actions": ["test"],
+                    "test": { "manual": { "test": "echo test" } }
+                },
+                "Deploy": {
+                    "requires": ["Build"],
+                    "requires_timeout": 0,
+                    "actions": ["deploy"],
+                    "deploy": { "manual": { "deploy": "echo deploy" } }
+                },
+                "Build": {
+                    "actions": ["build"],
+                    "build": { "manual": { "build": "sleep 1 && echo build" } }
+                }
+            }"#,
+        );
+        let options = ExecOptions {
+            requires_timeout: Some(Duration::from_millis(200)),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| (result.name.as_str(), result.status))
+                .collect::<Vec<_>>(),
+            [("test", Status::Failure), ("deploy", Status::Success), ("build", Status::Success)]
+        );
+        assert_eq!(
+            report.actions[0].output,
+            ["Pipeline Test failed, as dependency Build.build did not complete within 200ms"]
+        );
+        assert!(report.actions[0].steps.is_empty());
+        assert!(!report.succeeded());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn requires_timeouts_count_towards_max_failures() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Test", "Lint", "Build"],
+                "Test": {
+                    "requires": ["Build"],
+                    "actions": ["test"],
+                    "test": { "manual": { "test": "echo test" } }
+                },
+                "Lint": {
+                    "requires": ["Test"],
+                    "actions": ["lint"],
+                    "lint": { "manual": { "lint": "echo lint" } }
+                },
+                "Build": {
+                    "actions": ["build"],
+                    "build": { "manual": { "build": "sleep 0.5 && echo build" } }
+                }
+            }"#,
+        );
+        let options = ExecOptions {
+            requires_timeout: Some(Duration::from_millis(200)),
+            max_failures: Some(1),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| (result.name.as_str(), result.status))
+                .collect::<Vec<_>>(),
+            [("test", Status::Failure), ("build", Status::Success)]
+        );
+        assert_eq!(options.failures.get(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn requires_timeout_starts_once_what_is_required_has_started() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Deploy", "Package", "Build"],
+                "Deploy": {
+                    "requires": ["Package"],
+                    "actions": ["deploy"],
+                    "deploy": { "manual": { "deploy": "echo deploy" } }
+                },
+                "Package": {
+                    "requires": ["Build"],
+                    "requires_timeout": 0,
+                    "actions": ["package"],
+                    "package": { "manual": { "package": "echo package" } }
+                },
+                "Build": {
+                    "actions": ["build"],
+                    "build": { "manual": { "build": "sleep 0.5 && echo build" } }
+                }
+            }"#,
+        );
+        let options = ExecOptions {
+            requires_timeout: Some(Duration::from_millis(300)),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        let names = report.actions.iter().map(|result| result.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["deploy", "package", "build"]);
+        assert!(report.succeeded());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parallel_pipelines_respect_requires_and_order() {
+        let marker = std::env::temp_dir().join(format!("cider_requires_{}", std::process::id()));
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "pipelines": ["Test", "Build", "Lint"],
+                "Test": {{
+                    "requires": ["Build"],
+                    "actions": ["test"],
+                    "test": {{ "manual": {{ "test": "test -f {marker}" }} }}
+                }},
+                "Build": {{
+                    "actions": ["build"],
+                    "build": {{ "manual": {{ "build": "sleep 0.2 && touch {marker}" }} }}
+                }},
+                "Lint": {{
+                    "actions": ["lint"],
+                    "lint": {{ "manual": {{ "lint": "echo lint" }} }}
+                }}
+            }}"#,
+            marker = marker.to_str().unwrap()
+        ));
+        let options = ExecOptions {
+            jobs: 3,
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        std::fs::remove_file(marker).unwrap();
+        assert!(report.succeeded());
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| result.name.as_str())
+                .collect::<Vec<_>>(),
+            ["test", "build", "lint"]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn conditions_can_check_the_output_of_earlier_steps() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["Build", "Deploy", "Release"],
+                "Build": { "manual": { "build": "echo Compiling cider" } },
+                "Deploy": {
+                    "conditions": { "compiled": "output_contains:build:\"Compiling\"" },
+                    "manual": { "deploy": "echo deploy" }
+                },
+                "Release": {
+                    "conditions": { "tested": "output_contains:test:\"test result: ok\"" },
+                    "manual": { "release": "echo release" }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| result.status)
+                .collect::<Vec<_>>(),
+            [Status::Success, Status::Success, Status::Skipped]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn deterministic_runs_pipelines_and_steps_one_at_a_time() {
+        let marker = std::env::temp_dir().join(format!("cider_deterministic_{}", std::process::id()));
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "pipelines": ["Slow", "Fast"],
+                "Slow": {{
+                    "actions": ["slow"],
+                    "slow": {{
+                        "concurrency": 2,
+                        "manual": {{
+                            "first": "sleep 0.3 && echo first >> {marker}",
+                            "second": "echo second >> {marker}"
+                        }}
+                    }}
+                }},
+                "Fast": {{
+                    "actions": ["fast"],
+                    "fast": {{ "manual": {{ "fast": "echo fast >> {marker}" }} }}
+                }}
+            }}"#,
+            marker = marker.to_str().unwrap()
+        ));
+        let options = ExecOptions {
+            jobs: 2,
+            deterministic: true,
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        let written = std::fs::read_to_string(&marker).unwrap();
+        std::fs::remove_file(marker).unwrap();
+        assert!(report.succeeded());
+        assert_eq!(written, "first\nsecond\nfast\n");
+    }
+
+    #[test]
+    fn default_weights_only_limit_the_number_of_pipelines() {
+        let mut scheduler = Scheduler::new(2, None, vec![1, 1, 1]);
+        assert!(scheduler.can_start(0));
+        scheduler.start(0);
+        assert!(scheduler.can_start(1));
+        scheduler.start(1);
+        assert!(!scheduler.can_start(2));
+        scheduler.finish(0);
+        assert!(scheduler.can_start(2));
+    }
+
+    #[test]
+    fn heavy_pipelines_do_not_run_together() {
+        let mut scheduler = Scheduler::new(4, Some(3), vec![2, 2, 1, 0]);
+        scheduler.start(0);
+        assert_eq!(scheduler.in_flight(), 2);
+        assert!(!scheduler.can_start(1));
+        assert!(scheduler.can_start(2));
+        scheduler.start(2);
+        assert!(scheduler.can_start(3));
+        scheduler.finish(0);
+        assert!(scheduler.can_start(1));
+    }
+
+    #[test]
+    fn pipelines_over_the_budget_run_alone() {
+        let mut scheduler = Scheduler::new(2, Some(2), vec![5, 1]);
+        assert!(scheduler.can_start(0));
+        scheduler.start(0);
+        assert!(!scheduler.can_start(1));
+        scheduler.finish(0);
+        scheduler.start(1);
+        assert!(!scheduler.can_start(0));
+        scheduler.finish(1);
+        assert!(scheduler.is_idle());
+    }
+
+    #[test]
+    fn pipeline_weight_is_its_heaviest_action() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Build", "Empty"],
+                "Build": {
+                    "actions": ["compile", "lint"],
+                    "compile": { "weight": 3, "manual": { "compile": "cargo build" } },
+                    "lint": { "manual": { "lint": "cargo clippy" } }
+                },
+                "Empty": { "actions": [] }
+            }"#,
+        );
+        assert_eq!(
+            config.get_pipelines().iter().map(pipeline_weight).collect::<Vec<_>>(),
+            [3, 0]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn pipelines_can_require_actions_of_other_pipelines() {
+        let marker = std::env::temp_dir().join(format!("cider_requires_action_{}", std::process::id()));
+        let config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "pipelines": ["Build", "Deploy"],
+                "Build": {{
+                    "actions": ["compile", "package"],
+                    "compile": {{ "manual": {{ "compile": "echo compile" }} }},
+                    "package": {{ "manual": {{ "package": "sleep 0.5 && touch {marker}" }} }}
+                }},
+                "Deploy": {{
+                    "requires": ["Build.compile"],
+                    "actions": ["deploy"],
+                    "deploy": {{ "manual": {{ "deploy": "test ! -f {marker}" }} }}
+                }}
+            }}"#,
+            marker = marker.to_str().unwrap()
+        ));
+        let options = ExecOptions {
+            jobs: 2,
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        std::fs::remove_file(marker).unwrap();
+        assert!(report.succeeded());
+    }
+
+    #[test]
+    fn unknown_action_requirements_are_rejected() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["A", "B"],
+                "A": { "actions": ["a"], "a": { "manual": { "a": "echo a" } } },
+                "B": { "requires": ["A.missing"], "actions": ["b"], "b": { "manual": { "b": "echo b" } } }
+            }"#,
+        );
+        match check_requires(&config) {
+            Err(CustomError::UnknownRequirement { pipeline, requirement }) => {
+                assert_eq!(pipeline, "B");
+                assert_eq!(requirement, "A.missing");
+            }
+            other => panic!("Expected an unknown requirement error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn steps_are_killed_after_their_timeout() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["hang", "configured"],
+                "hang": { "manual": { "hang": "sleep 30" } },
+                "configured": { "step_timeout": 30, "manual": { "wait": "sleep 2" } }
+            }"#,
+        );
+        let options = ExecOptions {
+            step_timeout: Some(Duration::from_secs(1)),
+            ..ExecOptions::default()
+        };
+        let started = Instant::now();
+        let report = exec_config(&config, &options).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|result| result.status)
+                .collect::<Vec<_>>(),
+            [Status::Failure, Status::Success]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn docker_commands_are_killed_after_their_timeout() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "backend": "docker",
+                "source_directory": "./",
+                "actions": ["build"],
+                "build": { "step_timeout": 1, "manual": { "build": "cargo build" } }
+            }"#,
+        );
+        let info = ExecInfo::new(&config.get_actions()[0]);
+        // Stands in for a docker build that never finishes
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30"]);
+        let started = Instant::now();
+        let (status, killed) =
+            wait_for_docker(&info, &mut cmd, "docker build", &mut vec![]).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(!status.success());
+        assert_eq!(killed.as_deref(), Some("Step timed out after 1s and was killed."));
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "true"]);
+        let (status, killed) =
+            wait_for_docker(&info, &mut cmd, "docker build", &mut vec![]).unwrap();
+        assert!(status.success());
+        assert!(killed.is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn events_are_sent_as_actions_run() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["build", "skipped"],
+                "build": { "manual": { "compile": "echo compiled" } },
+                "skipped": { "conditions": { "never": "false" }, "manual": { "never": "echo never" } }
+            }"#,
+        );
+        let (events, receiver) = EventSender::channel();
+        let options = ExecOptions {
+            events: Some(events),
+            ..ExecOptions::default()
+        };
+        exec_config(&config, &options).unwrap();
+        drop(options);
+        let events = receiver.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.kind.name(), event.action.as_deref(), event.step.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("action_started", Some("build"), None),
+                ("step_started", Some("build"), Some("compile")),
+                ("step_output", Some("build"), Some("compile")),
+                ("step_finished", Some("build"), Some("compile")),
+                ("action_finished", Some("build"), None),
+                ("action_started", Some("skipped"), None),
+                ("action_finished", Some("skipped"), None),
+            ]
+        );
+        assert_eq!(
+            events[2].kind,
+            EventKind::StepOutput {
+                stream: "stdout",
+                text: "compiled\n".to_string()
+            }
+        );
+        assert_eq!(events[6].to_json()["status"], "skipped");
+    }
+
+    /// A reporter that records what it was called with, as `method:action:step`
+    #[derive(Debug, Default)]
+    struct RecordingReporter(Mutex<Vec<String>>);
+
+    impl crate::utils::reporters::Reporter for RecordingReporter {
+        fn on_action_start(&self, event: &Event) {
+            let action = event.action.clone().unwrap_or_default();
+            self.0.lock().unwrap().push(format!("action_start:{}", action));
+        }
+
+        fn on_step_finish(&self, event: &Event) {
+            let action = event.action.clone().unwrap_or_default();
+            let step = event.step.clone().unwrap_or_default();
+            self.0.lock().unwrap().push(format!("step_finish:{}:{}", action, step));
+        }
+
+        fn on_run_finish(&self, report: &RunReport) {
+            let line = format!("run_finish:{}", report.actions.len());
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reporters_are_called_as_the_run_progresses() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["build"],
+                "pipelines": ["Release"],
+                "build": { "manual": { "compile": "echo compiled", "test": "echo tested" } },
+                "Release": { "actions": ["package"], "package": { "manual": { "zip": "echo zipped" } } }
+            }"#,
+        );
+        let first = Arc::new(RecordingReporter::default());
+        let second = Arc::new(RecordingReporter::default());
+        let (events, receiver) = EventSender::channel();
+        let mut options = ExecOptions {
+            events: Some(events),
+            ..ExecOptions::default()
+        };
+        options.reporters.attach(first.clone());
+        options.reporters.attach(second.clone());
+        exec_config(&config, &options).unwrap();
+        drop(options);
+        let expected = [
+            "action_start:build",
+            "step_finish:build:compile",
+            "step_finish:build:test",
+            "action_start:package",
+            "step_finish:package:zip",
+            "run_finish:2",
+        ];
+        assert_eq!(*first.0.lock().unwrap(), expected);
+        assert_eq!(*second.0.lock().unwrap(), expected);
+        // The events of the run are still sent through the channel along with the reporters
+        assert_eq!(receiver.into_iter().count(), 13);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn planned_steps_show_their_resolved_scripts() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["Cleaned", "Verbatim"],
+                "Cleaned": { "manual": { "run": "sh ./scripts/build.sh" } },
+                "Verbatim": { "clean_paths": false, "manual": { "run": "sh ./scripts/build.sh" } }
+            }"#,
+        );
+        let cwd = current_dir().unwrap();
+        let cleaned = plan_steps(&config.get_actions()[0]);
+        assert_eq!(
+            cleaned,
+            [PlannedStep {
+                name: "run".to_string(),
+                script: format!("sh {}", cwd.join("./scripts/build.sh").display()),
+                interpreter: "sh -c".to_string(),
+                working_dir: cwd.display().to_string(),
+                stdin: None,
+            }]
+        );
+        let verbatim = plan_steps(&config.get_actions()[1]);
+        assert_eq!(verbatim[0].script, "sh ./scripts/build.sh");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn tty_steps_write_to_a_terminal() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["terminal", "piped"],
+                "terminal": {
+                    "tty": true,
+                    "step_timeout": 30,
+                    "manual": { "check": "if [ -t 1 ]; then echo tty; else echo pipe; fi; echo warning >&2" }
+                },
+                "piped": {
+                    "manual": { "check": "if [ -t 1 ]; then echo tty; else echo pipe; fi" }
+                }
+            }"#,
+        );
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        let terminal = &report.actions[0].steps[0];
+        assert_eq!(terminal.status, Status::Success);
+        assert_eq!(terminal.stdout, "tty\nwarning\n");
+        assert_eq!(terminal.stderr, "");
+        assert_eq!(report.actions[1].steps[0].stdout, "pipe\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn output_is_redacted_before_it_is_reported() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "redact": ["ghp_[A-Za-z0-9]+"],
+                "actions": ["deploy"],
+                "deploy": {
+                    "redact": ["[\\w.]+@example\\.com"],
+                    "manual": {
+                        "push": "echo pushing with ghp_S3cr3t; echo notify ops@example.com >&2",
+                        "done": "echo done"
+                    }
+                }
+            }"#,
+        );
+        let (events, receiver) = EventSender::channel();
+        let options = ExecOptions {
+            events: Some(events),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        drop(options);
+        let push = &report.actions[0].steps[0];
+        assert_eq!(push.stdout, "pushing with ***\n");
+        assert_eq!(push.stderr, "notify ***\n");
+        let mut reported = report.actions[0].output.clone();
+        for event in receiver {
+            if let EventKind::StepOutput { text, .. } = event.kind {
+                reported.push(text);
+            }
+        }
+        assert!(reported.iter().any(|text| text.contains("***")));
+        assert!(reported.iter().all(|text| !text.contains("S3cr3t") && !text.contains("ops@")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn commands_are_redacted_before_they_are_reported() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "redact": ["ghp_[A-Za-z0-9]+"],
+                "actions": ["deploy"],
+                "deploy": {
+                    "allowed_failure": true,
+                    "manual": { "push": "git push https://ghp_S3cr3t@example.com/repo; false" }
+                }
+            }"#,
+        );
+        let (events, receiver) = EventSender::channel();
+        let options = ExecOptions {
+            events: Some(events),
+            ..ExecOptions::default()
+        };
+        let setup = ExecInfo::new(&config.get_actions()[0]);
+        let report = exec_config(&config, &options).unwrap();
+        drop(options);
+        let mut commands = report.actions[0]
+            .timings
+            .iter()
+            .filter_map(|timing| timing.command.as_ref())
+            .map(|command| command.to_string())
+            .collect::<Vec<String>>();
+        for event in receiver {
+            if let EventKind::StepFinished { command, .. } = event.kind {
+                commands.push(command.to_string());
+            }
+        }
+        assert_eq!(commands.len(), 2);
+        assert!(commands.iter().all(|command| command.contains("https://***@example.com")));
+        assert!(commands.iter().all(|command| !command.contains("S3cr3t")));
+
+        // The failure context of a step is only logged, so it is checked on its own
+        let command = CommandLine {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo ghp_S3cr3t".to_string()],
+            working_dir: "/tmp".to_string(),
+        };
+        let context = failure_context(
+            &Step::new("push".to_string(), String::new()),
+            &setup.redacted_command(command),
+            &Command::new("false").output().unwrap(),
+            std::iter::empty(),
+        );
+        assert!(context.iter().any(|line| line.contains("echo ***")));
+        assert!(context.iter().all(|line| !line.contains("S3cr3t")));
+    }
+
+    #[test]
+    fn test_phase_output() {
+        assert_eq!(phase_output("docker pull", "pulled\n", ""), "[docker pull]\npulled");
+        assert_eq!(
+            phase_output("docker build", "step 1\nstep 2\n", "warning\n"),
+            "[docker build]\nstep 1\nstep 2\nwarning"
+        );
+        assert_eq!(phase_output("docker image rm", "", ""), "[docker image rm]");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn docker_phases_are_labelled_within_the_output() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "redact": ["ghp_[A-Za-z0-9]+"],
+                "backend": "docker",
+                "actions": ["Build"],
+                "Build": { "manual": { "build": "cargo build" } }
+            }"#,
+        );
+        let setup = ExecInfo::new(&config.get_actions()[0]);
+        let mut outputs = vec![];
+        // Docker is stood in for by commands that write what each phase might
+        for (phase, script) in [
+            ("docker pull", "echo pulled"),
+            ("docker image rm", "true"),
+            ("docker build", "echo built with ghp_S3cr3t; echo warning >&2"),
+        ] {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", script]);
+            let (status, killed) = wait_for_docker(&setup, &mut cmd, phase, &mut outputs).unwrap();
+            assert!(status.success() && killed.is_none());
+        }
+        assert_eq!(
+            outputs,
+            [
+                "[docker pull]\npulled",
+                "[docker image rm]",
+                "[docker build]\nbuilt with ***\nwarning"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn cancelled_runs_kill_their_steps() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["hang", "after"],
+                "hang": { "manual": { "hang": "sleep 30" } },
+                "after": { "manual": { "after": "echo after" } }
+            }"#,
+        );
+        let cancel = CancelToken::default();
+        let options = ExecOptions {
+            cancel: Some(cancel.clone()),
+            ..ExecOptions::default()
+        };
+        let started = Instant::now();
+        let run = thread::spawn(move || exec_config(&config, &options));
+        thread::sleep(Duration::from_millis(300));
+        cancel.cancel();
+        assert!(matches!(run.join().unwrap(), Err(CustomError::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fail_fast_stops_after_first_failure() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "actions": ["fail", "pass"],
+                "fail": { "manual": { "fail": "exit 1" } },
+                "pass": { "manual": { "pass": "echo pass" } }
+            }"#,
+        );
+        let options = ExecOptions {
+            fail_fast: true,
+            ..ExecOptions::default()
+        };
+        assert_eq!(exec_config(&config, &options).unwrap().actions.len(), 1);
+
+        let report = exec_config(&config, &ExecOptions::default()).unwrap();
+        assert_eq!(report.actions.len(), 2);
+        assert!(!report.succeeded());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn max_failures_stops_after_enough_failures() {
+        let config = json_parser::parse_from_str(
+            r#"{
+                "pipelines": ["Optional", "First", "Second"],
+                "actions": ["top"],
+                "top": { "manual": { "fail": "exit 1" } },
+                "Optional": {
+                    "allowed_failure": true,
+                    "actions": ["optional"],
+                    "optional": { "manual": { "fail": "exit 1" } }
+                },
+                "First": {
+                    "requires": ["Optional"],
+                    "actions": ["pass", "first", "after"],
+                    "pass": { "manual": { "pass": "echo pass" } },
+                    "first": { "manual": { "fail": "exit 1" } },
+                    "after": { "manual": { "fail": "exit 1" } }
+                },
+                "Second": {
+                    "requires": ["First"],
+                    "actions": ["second"],
+                    "second": { "manual": { "fail": "exit 1" } }
+                }
+            }"#,
+        );
+        let options = ExecOptions {
+            max_failures: Some(2),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        let names = report.actions.iter().map(|result| result.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["top", "optional", "pass", "first"]);
+        assert_eq!(options.failures.get(), 2);
+        assert!(!report.succeeded());
+
+        let options = ExecOptions {
+            max_failures: Some(5),
+            ..ExecOptions::default()
+        };
+        let report = exec_config(&config, &options).unwrap();
+        assert_eq!(report.actions.len(), 6);
+        assert_eq!(options.failures.get(), 4);
     }
 }
@@ -0,0 +1,193 @@
+use log::{info, warn};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A single pattern from a `.gitignore` file
+///
+/// Supports the common subset of the gitignore format: `*` and `?` match within a single path component, `**` matches
+/// across components, a leading `/` anchors the pattern to the directory the file is in, and a trailing `/` only matches
+/// directories. Patterns without a `/` in their middle match a file or directory of that name at any depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnorePattern {
+    pattern: String,
+    anchored: bool,
+    directory_only: bool,
+}
+
+impl IgnorePattern {
+    /// Parses a line of a `.gitignore` file, returning None for blank lines, comments, and negated patterns
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::ignore::IgnorePattern;
+    ///
+    /// let pattern = IgnorePattern::parse("target/").unwrap();
+    /// assert!(pattern.matches("target", true));
+    /// assert!(!pattern.matches("target", false));
+    /// assert!(IgnorePattern::parse("# a comment").is_none());
+    /// ```
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.starts_with('#') {
+            return None;
+        }
+        if line.starts_with('!') {
+            warn!("Negated gitignore patterns are not supported, ignoring: {}", line);
+            return None;
+        }
+        let directory_only = line.ends_with('/');
+        let pattern = line.trim_end_matches('/');
+        let anchored = pattern.starts_with('/') || pattern.contains('/');
+        Some(IgnorePattern {
+            pattern: pattern.trim_start_matches('/').to_string(),
+            anchored,
+            directory_only,
+        })
+    }
+
+    /// Parses a line of a `.dockerignore` file, returning None for blank lines, comments, and negated patterns
+    ///
+    /// Unlike gitignore, every dockerignore pattern is relative to the root of the build context, and a trailing `/`
+    /// is dropped rather than limiting the pattern to directories.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::ignore::IgnorePattern;
+    ///
+    /// let pattern = IgnorePattern::parse_dockerignore("target/").unwrap();
+    /// assert!(pattern.matches("target", false));
+    /// assert!(!pattern.matches("nested/target", true));
+    /// ```
+    pub fn parse_dockerignore(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        if line.starts_with('!') {
+            warn!("Negated dockerignore patterns are not supported, ignoring: {}", line);
+            return None;
+        }
+        let pattern = line.trim_start_matches("./").trim_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(IgnorePattern {
+            pattern: pattern.to_string(),
+            anchored: true,
+            directory_only: false,
+        })
+    }
+
+    /// Returns whether the pattern matches `path`, given relative to the directory of the `.gitignore` file
+    pub fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.pattern, path)
+        } else {
+            path.rsplit('/')
+                .next()
+                .is_some_and(|name| glob_match(&self.pattern, name))
+        }
+    }
+
+    /// Returns the pattern as a line of a `.dockerignore` file
+    ///
+    /// Unlike gitignore, dockerignore patterns are always relative to the root of the build context, so unanchored
+    /// patterns are prefixed with `**/` to keep matching at any depth.
+    pub fn to_dockerignore(&self) -> String {
+        if self.anchored {
+            self.pattern.clone()
+        } else {
+            format!("**/{}", self.pattern)
+        }
+    }
+}
+
+/// Reads the patterns of the `.gitignore` file within `dir`
+///
+/// Returns no patterns if there is no `.gitignore` file.
+pub fn read_gitignore(dir: &Path) -> io::Result<Vec<IgnorePattern>> {
+    let path = dir.join(".gitignore");
+    if !path.exists() {
+        info!("No .gitignore found in {:#?}", dir);
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(IgnorePattern::parse)
+        .collect())
+}
+
+/// Reads the patterns of the `.dockerignore` file within `dir`
+///
+/// Returns no patterns if there is no `.dockerignore` file.
+pub fn read_dockerignore(dir: &Path) -> io::Result<Vec<IgnorePattern>> {
+    let path = dir.join(".dockerignore");
+    if !path.exists() {
+        info!("No .dockerignore found in {:#?}", dir);
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(IgnorePattern::parse_dockerignore)
+        .collect())
+}
+
+/// Returns whether any of `patterns` matches `path`, given relative to the directory of the `.gitignore` file
+pub fn is_ignored(patterns: &[IgnorePattern], path: &str, is_dir: bool) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(path, is_dir))
+}
+
+/// Matches `text` against a glob `pattern`, where `*` and `?` do not match `/` but `**` does
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern {
+            [] => text.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+                (0..=text.len()).any(|start| matches(rest, &text[start..]))
+            }
+            [b'*', rest @ ..] => (0..=text.len())
+                .take_while(|end| !text[..*end].contains(&b'/'))
+                .any(|start| matches(rest, &text[start..])),
+            [b'?', rest @ ..] => text
+                .split_first()
+                .is_some_and(|(c, text)| *c != b'/' && matches(rest, text)),
+            [c, rest @ ..] => text
+                .split_first()
+                .is_some_and(|(t, text)| t == c && matches(rest, text)),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_patterns() {
+        let patterns = ["target/", "*.rlib", "/Cargo.lock", "docs/**/*.html"]
+            .iter()
+            .filter_map(|line| IgnorePattern::parse(line))
+            .collect::<Vec<_>>();
+        assert!(is_ignored(&patterns, "target", true));
+        assert!(is_ignored(&patterns, "nested/target", true));
+        assert!(is_ignored(&patterns, "lib/libcider.rlib", false));
+        assert!(is_ignored(&patterns, "Cargo.lock", false));
+        assert!(!is_ignored(&patterns, "nested/Cargo.lock", false));
+        assert!(is_ignored(&patterns, "docs/api/v1/index.html", false));
+        assert!(is_ignored(&patterns, "docs/index.html", false));
+        assert!(!is_ignored(&patterns, "src/main.rs", false));
+        assert_eq!(
+            patterns
+                .iter()
+                .map(IgnorePattern::to_dockerignore)
+                .collect::<Vec<_>>(),
+            ["**/target", "**/*.rlib", "Cargo.lock", "docs/**/*.html"]
+        );
+    }
+}
@@ -1,15 +1,59 @@
+/// This module generates shell completion scripts from the definition of the command-line flags
+pub mod completions;
+
+/// This module resolves the conditions that decide whether actions are run
+pub mod conditions;
+
 /// Holds information relevant to configuration
 pub mod config;
 
-/// This module will create a configuration for you
-/// Not implemented yet.
+/// This module creates starter configurations
 pub mod config_generator;
 
+/// This module packs the build context of docker actions into tarballs
+pub mod build_context;
+
+/// This module remembers the cache keys that the images of docker actions were built with
+pub mod docker_cache;
+
+/// This module checks whether the environment CIder runs in can run configurations
+pub mod doctor;
+
+/// This module contains the errors that can occur while running actions
+pub mod error;
+
+/// This module contains the events sent while actions are running
+pub mod events;
+
 /// This module executes scripts based on the configuration provided
 pub mod executor;
 
+/// This module reads the patterns of `.gitignore` files
+pub mod ignore;
+
 /// This module contains the necessary functionality to parse configuration files into a usable form.
 pub mod parsing;
 
-/// This module contains functionality relevant to the watch functionality of this program (Not implemented yet.)
+/// This module redacts patterns from the output of steps
+pub mod redaction;
+
+/// This module fetches configurations from remote locations
+pub mod remote;
+
+/// This module contains the reporters that are told about the results of a run as it progresses
+pub mod reporters;
+
+/// This module measures the resources used by the steps of actions
+pub mod resources;
+
+/// This module contains the results produced by running actions
+pub mod results;
+
+/// This module copies the source of a configuration into a throwaway directory to run it in
+pub mod sandbox;
+
+/// This module runs steps within pseudo-terminals
+pub mod terminal;
+
+/// This module contains functionality relevant to the watch functionality of this program
 pub mod watcher;
@@ -1,14 +1,80 @@
 /// Parses Json information into a program-readable configuration
-
 pub mod json_parser {
 
     use crate::utils::config::*;
+    use crate::utils::ignore::glob_match;
+    use crate::utils::redaction::RedactPattern;
     use json::JsonValue;
-    use log::{error, warn};
+    use log::{error, info, warn};
     use relative_path::RelativePath;
-    use std::env::current_dir;
+    use std::env::{self, current_dir};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
     use std::{collections::HashMap, fs};
 
+    /// Options that change how a configuration is parsed
+    ///
+    /// The default options match the behavior of [`new_top_level`].
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct ParseOptions {
+        /// Whether a `${VAR}` reference to an environment variable that is not set should be treated as an error.
+        ///
+        /// When false, the reference is left as-is and a warning is logged.
+        pub strict_env: bool,
+        /// A dotenv file to load environment variables from, used instead of the `env_file` of the configuration
+        pub env_file: Option<String>,
+        /// A JSON file holding an object of variables that `${NAME}` references within the configuration can be
+        /// replaced with. A variable that is also set in the environment takes the value of the environment.
+        ///
+        /// The variables are not loaded into the environment, so the steps that are run do not see them.
+        pub vars_file: Option<String>,
+        /// The format of the configuration file, used instead of the format implied by its extension
+        pub format: Option<ConfigFormat>,
+        /// Whether relative paths within a configuration file are resolved against the directory CIder is run from,
+        /// rather than against the directory containing the configuration file.
+        ///
+        /// Configurations parsed from a string, rather than a file, always resolve against the current directory.
+        pub paths_relative_to_cwd: bool,
+    }
+
+    /// The formats a configuration file can be written in
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ConfigFormat {
+        /// A JSON configuration, usually with a `.json` extension
+        Json,
+    }
+
+    impl ConfigFormat {
+        /// Returns the format implied by the extension of `path`, if the extension is recognized
+        ///
+        /// ```
+        /// use cider::parsing::json_parser::ConfigFormat;
+        ///
+        /// assert_eq!(ConfigFormat::from_extension("cider_config.json"), Some(ConfigFormat::Json));
+        /// assert_eq!(ConfigFormat::from_extension("cider_config"), None);
+        /// ```
+        pub fn from_extension(path: impl AsRef<Path>) -> Option<Self> {
+            let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+            match extension.as_str() {
+                "json" => Some(ConfigFormat::Json),
+                _ => None,
+            }
+        }
+    }
+
+    /// Decides the format of `path`, preferring the format given in `options` over the one implied by its extension
+    ///
+    /// Panics if neither determines the format.
+    fn resolve_format(path: &Path, options: &ParseOptions) -> ConfigFormat {
+        options
+            .format
+            .or_else(|| ConfigFormat::from_extension(path))
+            .unwrap_or_else(|| {
+                error!("The format of the configuration file {} could not be determined from its extension. Only JSON configuration files are supported; use --config-format json to parse a file without a .json extension.", path.display());
+                panic!("The format of the configuration file {} could not be determined from its extension. Only JSON configuration files are supported; use --config-format json to parse a file without a .json extension.", path.display());
+            })
+    }
+
     /// Parses a map of JSON information into a HashMap<String,String>
     ///
     /// Iterates through a JSON hashmap and parses its data into a HashMap<String,String>
@@ -27,14 +93,35 @@ pub mod json_parser {
         map
     }
 
+    /// Parses the conditions of a JSON object, where each condition is either a string, or an object holding the
+    /// `condition` along with its `mode`
+    ///
+    /// Panics if a condition object has no condition, or an unknown mode.
     fn parse_json_to_conditions(json: &JsonValue) -> Vec<Condition> {
         // info!("{:#?}", json);
         let mut conditions = vec![];
         for key_value in json.entries() {
-            conditions.push(Condition::new(
+            if !key_value.1.is_object() {
+                conditions.push(Condition::new(
+                    key_value.0.to_string(),
+                    key_value.1.to_string(),
+                ));
+                continue;
+            }
+            let mut condition = Condition::new(
                 key_value.0.to_string(),
-                key_value.1.to_string(),
-            ));
+                key_value.1["condition"].as_str().map(str::to_string).unwrap_or_else(|| {
+                    error!("There was no valid value for condition in the configuration. Error occured in Condition: {}", key_value.0);
+                    panic!("There was no valid value for condition in the configuration. Error occured in Condition: {}", key_value.0);
+                }),
+            );
+            if !key_value.1["mode"].is_null() {
+                condition.set_mode(key_value.1["mode"].as_str().and_then(ConditionMode::parse).unwrap_or_else(|| {
+                    error!("There was no valid value for mode in the configuration, it must be \"block\" or \"warn\". Error occured in Condition: {}", key_value.0);
+                    panic!("There was no valid value for mode in the configuration, it must be \"block\" or \"warn\". Error occured in Condition: {}", key_value.0);
+                }));
+            }
+            conditions.push(condition);
         }
         conditions
     }
@@ -43,11 +130,57 @@ pub mod json_parser {
         // info!("{:#?}", json);
         let mut steps = vec![];
         for key_value in json.entries() {
-            steps.push(Step::new(key_value.0.to_string(), key_value.1.to_string()));
+            if !key_value.1.is_object() {
+                let script = if key_value.1.is_array() {
+                    parse_script(key_value.1, key_value.0)
+                } else {
+                    key_value.1.to_string()
+                };
+                steps.push(Step::new(key_value.0.to_string(), script));
+                continue;
+            }
+            warn_unrecognized_keys(
+                find_unknown_keys(key_value.1, STEP_KEYS, &[]),
+                &format!("Step: {}", key_value.0),
+            );
+            let mut step = Step::new(
+                key_value.0.to_string(),
+                parse_script(&key_value.1["script"], key_value.0),
+            );
+            if !key_value.1["stdin"].is_null() {
+                step.set_stdin(Some(key_value.1["stdin"].as_str().map(str::to_string).unwrap_or_else(|| {
+                    error!("There was no valid value for stdin in the configuration. Error occured in Step: {}", key_value.0);
+                    panic!("There was no valid value for stdin in the configuration. Error occured in Step: {}", key_value.0);
+                })));
+            }
+            if !key_value.1["allowed_failure"].is_null() {
+                step.set_allowed_failure(Some(lenient_bool(&key_value.1["allowed_failure"]).unwrap_or_else(|| {
+                    error!("There was no valid value for allowed_failure in the configuration. Error occured in Step: {}", key_value.0);
+                    panic!("There was no valid value for allowed_failure in the configuration. Error occured in Step: {}", key_value.0);
+                })));
+            }
+            steps.push(step);
         }
         steps
     }
 
+    /// Parses the script of the step named `name`, which is either a string or an array of lines
+    ///
+    /// The lines of an array are joined with `&&`, so they run one after another until one of them fails.
+    fn parse_script(json: &JsonValue, name: &str) -> String {
+        if json.is_array() && json.members().all(JsonValue::is_string) && !json.is_empty() {
+            return json
+                .members()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>()
+                .join(" && ");
+        }
+        json.as_str().map(str::to_string).unwrap_or_else(|| {
+            error!("There was no valid value for script in the configuration. Error occured in Step: {}", name);
+            panic!("There was no valid value for script in the configuration. Error occured in Step: {}", name);
+        })
+    }
+
     fn parse_json_vector(json: &JsonValue) -> Vec<String> {
         // println!("{:#?}", json);
         let mut vec = vec![];
@@ -62,6 +195,418 @@ pub mod json_parser {
         vec
     }
 
+    /// Reads a whole number from `json`, accepting quoted numbers such as `"3"` and floats such as `3.0`
+    ///
+    /// A number with a fractional part is rounded towards zero with a warning. Returns None if the value is not a
+    /// number, or does not fit within an i8.
+    fn lenient_i8(json: &JsonValue, key: &str, location: &str) -> Option<i8> {
+        let value = match json.as_str() {
+            Some(text) => text.trim().parse::<f64>().ok()?,
+            None => json.as_f64()?,
+        };
+        if !value.is_finite() {
+            return None;
+        }
+        if value.fract() != 0.0 {
+            warn!(
+                "The value {} for {} in {} is not a whole number, so {} is used",
+                value,
+                key,
+                location,
+                value.trunc()
+            );
+        }
+        let value = value.trunc();
+        if value < i8::MIN as f64 || value > i8::MAX as f64 {
+            return None;
+        }
+        Some(value as i8)
+    }
+
+    /// Reads a boolean from `json`, accepting the strings `"true"` and `"false"` in any case
+    fn lenient_bool(json: &JsonValue) -> Option<bool> {
+        match json.as_str().map(|text| text.trim().to_ascii_lowercase()) {
+            Some(text) if text == "true" => Some(true),
+            Some(text) if text == "false" => Some(false),
+            Some(_) => None,
+            None => json.as_bool(),
+        }
+    }
+
+    /// Replaces every `${VAR}` reference in `value` with the value of the environment variable `VAR`, or with the value
+    /// of `VAR` within `vars` if it is not set in the environment
+    ///
+    /// Panics if a referenced variable is not set and `strict_env` is enabled.
+    fn interpolate_env_str(
+        value: &str,
+        vars: &HashMap<String, String>,
+        strict_env: bool,
+    ) -> String {
+        let mut interpolated = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let reference = &rest[start..start + len + 1];
+            let name = &reference[2..reference.len() - 1];
+            interpolated += &rest[..start];
+            match env::var(name).ok().or_else(|| vars.get(name).cloned()) {
+                Some(var) => interpolated += &var,
+                None if strict_env => {
+                    error!("Environment variable {} referenced in the configuration is not set.", name);
+                    panic!("Environment variable {} referenced in the configuration is not set.", name);
+                }
+                None => {
+                    warn!(
+                        "Environment variable {} referenced in the configuration is not set. {} will be left as-is.",
+                        name, reference
+                    );
+                    interpolated += reference;
+                }
+            }
+            rest = &rest[start + len + 1..];
+        }
+        interpolated + rest
+    }
+
+    /// Parses the `KEY=VALUE` lines of a dotenv file
+    ///
+    /// Blank lines and lines starting with `#` are skipped, and a leading `export` is ignored. Values may be wrapped in
+    /// single or double quotes, otherwise anything following a ` #` is treated as a comment.
+    fn parse_env_file(contents: &str) -> Vec<(String, String)> {
+        let mut vars = vec![];
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Skipping a line in an env file that is not of the form KEY=VALUE: {}", line);
+                continue;
+            };
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) if value.len() > 1 && value.ends_with(quote) => {
+                    &value[1..value.len() - 1]
+                }
+                _ => value.split(" #").next().unwrap_or_default().trim_end(),
+            };
+            vars.push((key.trim().to_string(), value.to_string()));
+        }
+        vars
+    }
+
+    /// Loads the variables of a dotenv file into the environment, so they can be referenced by the configuration and
+    /// by the steps it runs
+    ///
+    /// Variables that are already set in the environment are not overridden. Panics if the file cannot be read.
+    fn load_env_file(filename: &str) {
+        info!("Loading environment variables from: {}", filename);
+        let contents = fs::read_to_string(resolve_path(filename)).unwrap_or_else(|err| {
+            error!("The env file {} could not be read: {}", filename, err);
+            panic!("The env file {} could not be read: {}", filename, err);
+        });
+        for (key, value) in parse_env_file(&contents) {
+            if env::var_os(&key).is_none() {
+                env::set_var(key, value);
+            }
+        }
+    }
+
+    /// Reads the variables of a vars file, a JSON object whose values are strings, numbers or booleans
+    ///
+    /// Panics if the file cannot be read, or does not hold such an object.
+    fn load_vars_file(filename: &str) -> HashMap<String, String> {
+        info!("Loading variables from: {}", filename);
+        let contents = fs::read_to_string(resolve_path(filename)).unwrap_or_else(|err| {
+            error!("The vars file {} could not be read: {}", filename, err);
+            panic!("The vars file {} could not be read: {}", filename, err);
+        });
+        let json = json::parse(&contents).unwrap_or_else(|err| {
+            error!("The vars file {} is not valid JSON: {}", filename, err);
+            panic!("The vars file {} is not valid JSON: {}", filename, err);
+        });
+        if !json.is_object() {
+            error!("The vars file {} must hold a JSON object of variables.", filename);
+            panic!("The vars file {} must hold a JSON object of variables.", filename);
+        }
+        let mut vars = HashMap::new();
+        for (name, value) in json.entries() {
+            let text = match value.as_str() {
+                Some(text) => text.to_string(),
+                None if value.is_number() || value.is_boolean() => value.dump(),
+                None => {
+                    error!("The variable {} of the vars file {} must be a string, number or boolean.", name, filename);
+                    panic!("The variable {} of the vars file {} must be a string, number or boolean.", name, filename);
+                }
+            };
+            vars.insert(name.to_string(), text);
+        }
+        vars
+    }
+
+    /// Interpolates environment variables, and the variables of `vars`, into every string value of a JSON document
+    ///
+    /// Scripts within a manual, or within the `before_each` and `after_each` of the configuration, are left untouched,
+    /// so that `${VAR}` references within them are expanded by the shell that runs them. See [`interpolate_env_str`] for
+    /// more information.
+    fn interpolate_env(json: &mut JsonValue, vars: &HashMap<String, String>, strict_env: bool) {
+        if let Some(value) = json.as_str() {
+            *json = interpolate_env_str(value, vars, strict_env).into();
+        } else if json.is_object() {
+            for (key, value) in json.entries_mut() {
+                if !SCRIPT_KEYS.contains(&key) {
+                    interpolate_env(value, vars, strict_env);
+                }
+            }
+        } else {
+            for value in json.members_mut() {
+                interpolate_env(value, vars, strict_env);
+            }
+        }
+    }
+
+    /// Keys whose values are paths, which are resolved against the directory containing the configuration file
+    const PATH_KEYS: &[&str] = &["source_directory", "output_directory", "metrics_dir"];
+
+    /// Returns the directory that relative paths within the configuration file at `path` are resolved against, or None
+    /// if they are resolved against the current directory
+    fn config_base_dir(path: &Path, options: &ParseOptions) -> Option<PathBuf> {
+        if options.paths_relative_to_cwd {
+            return None;
+        }
+        let dir = path.parent()?;
+        if dir.is_absolute() {
+            return Some(dir.to_path_buf());
+        }
+        current_dir().ok().map(|cwd| cwd.join(dir))
+    }
+
+    /// Resolves `path` against `base`, or against the current directory if there is no `base`
+    ///
+    /// Absolute paths are left as they are.
+    fn resolve_path_against(path: &str, base: Option<&Path>) -> String {
+        match base {
+            Some(base) if !Path::new(path).is_absolute() => RelativePath::new(path)
+                .to_path(base)
+                .to_string_lossy()
+                .into_owned(),
+            _ => resolve_path(path),
+        }
+    }
+
+    /// Resolves the relative paths of every level of a JSON document against `base`, including the default source and
+    /// output directories of the top level when they are not set
+    fn resolve_document_paths(json: &mut JsonValue, base: &Path) {
+        for (key, default) in [("source_directory", "./src"), ("output_directory", "./dist/cider/")] {
+            if json[key].is_null() {
+                json[key] = default.into();
+            }
+        }
+        resolve_paths(json, base);
+    }
+
+    /// Resolves the value of every [`PATH_KEYS`] key within a JSON value against `base`
+    fn resolve_paths(json: &mut JsonValue, base: &Path) {
+        if json.is_object() {
+            for (key, value) in json.entries_mut() {
+                if PATH_KEYS.contains(&key) {
+                    if let Some(path) = value.as_str() {
+                        *value = resolve_path_against(path, Some(base)).into();
+                    }
+                } else if !SCRIPT_KEYS.contains(&key) {
+                    resolve_paths(value, base);
+                }
+            }
+        } else {
+            for value in json.members_mut() {
+                resolve_paths(value, base);
+            }
+        }
+    }
+
+    /// Keys that can be set at every level of a configuration
+    const SHARED_KEYS: &[&str] = &[
+        "metadata",
+        "tags",
+        "language",
+        "image",
+        "backend",
+        "output_directory",
+        "source_directory",
+        "platform",
+        "use_gitignore",
+        "clean_paths",
+        "metrics_dir",
+        "metrics_filename",
+        "redact",
+    ];
+
+    /// Keys that can only be set at the top level of a configuration
+    const TOP_LEVEL_KEYS: &[&str] = &[
+        "title",
+        "pipelines",
+        "actions",
+        "post_run",
+        "env_file",
+        "before_each",
+        "after_each",
+        "conditions",
+    ];
+
+    /// Keys whose values are scripts, which are left for the shell that runs them
+    const SCRIPT_KEYS: &[&str] = &["manual", "before_each", "after_each"];
+
+    /// Keys that can only be set on a [`Pipeline`]
+    const PIPELINE_KEYS: &[&str] =
+        &["conditions", "actions", "requires", "allowed_failure", "requires_timeout"];
+
+    /// Keys that can be set on a [`Step`] written as an object
+    const STEP_KEYS: &[&str] = &["script", "stdin", "allowed_failure"];
+
+    /// Keys that can only be set on an [`Action`]
+    const ACTION_KEYS: &[&str] = &[
+        "conditions",
+        "retries",
+        "allowed_failure",
+        "manual",
+        "shared_shell",
+        "merge_output",
+        "fail_on_stderr",
+        "tty",
+        "docker_workdir",
+        "docker_user",
+        "step_timeout",
+        "pull_retries",
+        "pull_retry_delay",
+        "weight",
+        "concurrency",
+        "cache_key_files",
+        "skip",
+        "docker_context_tar",
+        "pull_policy",
+    ];
+
+    /// Returns every key of a JSON object that is neither a known key nor the name of a definition
+    fn find_unknown_keys(json: &JsonValue, known_keys: &[&str], defs: &[String]) -> Vec<String> {
+        json.entries()
+            .map(|(key, _)| key)
+            .filter(|key| !known_keys.contains(key) && !defs.iter().any(|def| def == key))
+            .map(|key| key.to_string())
+            .collect()
+    }
+
+    /// Warns about every key of a JSON object that will be ignored by CIder
+    ///
+    /// Keys are only recognized if they can be set at the level of configuration they were found at, or if they are the name of
+    /// a pipeline or action that is listed as a definition at that level.
+    fn warn_unknown_keys(json: &JsonValue, level_keys: &[&str], defs: &[String], location: &str) {
+        let known_keys = [SHARED_KEYS, level_keys].concat();
+        warn_unrecognized_keys(find_unknown_keys(json, &known_keys, defs), location);
+    }
+
+    /// Warns about each of `keys`, which were found in `location` and will be ignored by CIder
+    fn warn_unrecognized_keys(keys: Vec<String>, location: &str) {
+        for key in keys {
+            warn!(
+                "Unrecognized key \"{}\" found in {}. It is not a known setting or a listed definition, and will be ignored.",
+                key, location
+            );
+        }
+    }
+
+    /// Describes why the `image` set within `json` at `location` is ignored, which is when `config`, the configuration
+    /// parsed from it, does not use the docker backend
+    fn ignored_image(
+        json: &JsonValue,
+        config: &ShareableConfiguration,
+        location: &str,
+    ) -> Option<String> {
+        if json["image"].is_null() || config.get_backend().eq_ignore_ascii_case("docker") {
+            return None;
+        }
+        Some(format!(
+            "The image \"{}\" set in {} will be ignored, as its backend is {} rather than docker. Set \"backend\": \"docker\" to run its steps within the image, or remove the image.",
+            json["image"],
+            location,
+            config.get_backend()
+        ))
+    }
+
+    /// Warns when the `image` set within `json` at `location` is ignored, see [`ignored_image`]
+    fn warn_ignored_image(json: &JsonValue, config: &ShareableConfiguration, location: &str) {
+        if let Some(warning) = ignored_image(json, config, location) {
+            warn!("{}", warning);
+        }
+    }
+
+    /// Returns whether an entry of an `actions` list is a wildcard, such as `*` or `test_*`, rather than a name
+    fn is_wildcard(entry: &str) -> bool {
+        entry.contains(['*', '?'])
+    }
+
+    /// Returns the names of the actions that may be defined within `json`, which are the keys holding objects that are
+    /// neither settings of the level, one of `level_keys`, nor one of `excluded`
+    fn action_definitions(
+        json: &JsonValue,
+        level_keys: &[&str],
+        excluded: &[String],
+    ) -> Vec<String> {
+        json.entries()
+            .filter(|(key, value)| {
+                value.is_object()
+                    && !SHARED_KEYS.contains(key)
+                    && !level_keys.contains(key)
+                    && !excluded.iter().any(|excluded| excluded == key)
+            })
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    /// Replaces every wildcard within the `actions` listed at a level of a configuration with the names of the
+    /// `definitions` at that level that it matches
+    ///
+    /// Wildcards are globs, where `*` matches any run of characters and `?` matches any single character, so `*` matches
+    /// every definition and `test_*` matches every definition starting with `test_`. Matches are added in the order they
+    /// are defined, leaving out those that are listed by name or matched by an earlier wildcard, so that names and
+    /// wildcards can be mixed. A wildcard that matches nothing is warned about.
+    fn expand_wildcards(
+        entries: Vec<String>,
+        definitions: &[String],
+        location: &str,
+    ) -> Vec<String> {
+        if !entries.iter().any(|entry| is_wildcard(entry)) {
+            return entries;
+        }
+        let named = entries.iter().filter(|entry| !is_wildcard(entry)).cloned().collect::<Vec<_>>();
+        let mut expanded: Vec<String> = vec![];
+        for entry in &entries {
+            if !is_wildcard(entry) {
+                expanded.push(entry.clone());
+                continue;
+            }
+            let matches = definitions
+                .iter()
+                .filter(|definition| glob_match(entry, definition))
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                warn!(
+                    "The wildcard \"{}\" in the actions of {} does not match any action",
+                    entry, location
+                );
+            }
+            for definition in matches {
+                if !named.contains(definition) && !expanded.contains(definition) {
+                    expanded.push(definition.clone());
+                }
+            }
+        }
+        info!("The actions of {} expanded to: {:?}", location, expanded);
+        expanded
+    }
+
     fn parse_action_defs(
         shared_config: &ShareableConfiguration,
         action_defs: &Vec<String>,
@@ -74,28 +619,71 @@ pub mod json_parser {
         actions
     }
 
-    fn parse_action(
-        shared_config: &ShareableConfiguration,
-        json: &JsonValue,
-        name: &str,
-    ) -> Action {
-        let root = current_dir().unwrap();
-        if json.is_null() {
-            panic!(
-                "Could not find action defined with appropriate tag: {}",
-                name
+    /// Returns whether `path` is absolute within a container, which may run either Linux, such as `/cider/app`, or
+    /// Windows, such as `C:\cider\app`, regardless of the platform CIder runs on
+    fn is_absolute_container_path(path: &str) -> bool {
+        let mut chars = path.chars();
+        path.starts_with('/')
+            || matches!(
+                (chars.next(), chars.next(), chars.next()),
+                (Some(drive), Some(':'), Some('\\' | '/')) if drive.is_ascii_alphabetic()
             )
+    }
+
+    /// Resolves a path from a configuration file against the current working directory
+    ///
+    /// Absolute paths are left as they are.
+    pub(crate) fn resolve_path(path: &str) -> String {
+        if Path::new(path).is_absolute() {
+            return path.to_string();
+        }
+        RelativePath::new(path)
+            .to_path(current_dir().unwrap())
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Chooses the backend for the platform `os`, of the family `family`, from a `backend` given per platform
+    ///
+    /// The backend of the operating system itself, such as `linux` or `macos`, is preferred over that of its family,
+    /// either `unix` or `windows`, which is preferred over the `default`. Returns None if none of them are given.
+    fn resolve_platform_backend(backends: &JsonValue, os: &str, family: &str) -> Option<String> {
+        for (platform, _) in backends.entries() {
+            if !["unix", "windows", "linux", "macos", "default"].contains(&platform) {
+                warn!("Unknown platform \"{}\" in backend will never be used", platform);
+            }
         }
+        [os, family, "default"]
+            .iter()
+            .find_map(|platform| backends[*platform].as_str())
+            .map(str::to_string)
+    }
+
+    /// Parses the [`ShareableConfiguration`] of a JSON object, inheriting anything it does not define from `parent`
+    ///
+    /// Metadata and tags are never inherited, as they describe only the level of configuration they are defined on.
+    fn parse_inherited_config(
+        parent: &ShareableConfiguration,
+        json: &JsonValue,
+        title: Option<String>,
+    ) -> ShareableConfiguration {
         let backend = {
             if json["backend"].is_null() {
-                shared_config.get_backend().to_string()
+                parent.get_backend().to_string()
+            } else if json["backend"].is_object() {
+                let location = title.as_deref().unwrap_or("the top level of the configuration");
+                resolve_platform_backend(&json["backend"], env::consts::OS, env::consts::FAMILY)
+                    .unwrap_or_else(|| {
+                        error!("There was no valid value for backend in the configuration for the platform {}. Error occured in: {}", env::consts::OS, location);
+                        panic!("There was no valid value for backend in the configuration for the platform {}. Error occured in: {}", env::consts::OS, location);
+                    })
             } else {
                 json["backend"].to_string()
             }
         };
-        
-        
-        let new_shared_config = ShareableConfiguration::new(
+
+        let mut config = ShareableConfiguration::new(
             {
                 if json["metadata"].is_null() {
                     None
@@ -103,7 +691,7 @@ pub mod json_parser {
                     Some(parse_json_map(&json["metadata"]))
                 }
             },
-            Some(name.to_string()),
+            title,
             {
                 if json["tags"].is_null() {
                     None
@@ -113,21 +701,14 @@ pub mod json_parser {
             },
             {
                 if json["language"].is_null() {
-                    shared_config.get_language().to_string()
+                    parent.get_language().to_string()
                 } else {
                     json["language"].to_string()
                 }
             },
             {
-                if !backend.to_lowercase().eq("docker")
-                    && !backend.is_empty()
-                    && backend != "bash"
-                    && backend != "batch"
-                {
-                    warn!("Image cannot be set if docker is not the backend.");
-                    None
-                } else if json["image"].is_null() {
-                    shared_config.get_image()
+                if json["image"].is_null() {
+                    parent.image().map(str::to_string)
                 } else {
                     Some(json["image"].to_string())
                 }
@@ -135,29 +716,96 @@ pub mod json_parser {
             backend,
             {
                 if json["output_directory"].is_null() {
-                    shared_config.get_output().to_string()
+                    parent.get_output().to_string()
                 } else {
-                    RelativePath::new(&json["output_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
+                    resolve_path(&json["output_directory"].to_string())
                 }
             },
             {
                 if json["source_directory"].is_null() {
-                    shared_config.get_source().to_string()
+                    parent.get_source().to_string()
                 } else {
-                    RelativePath::new(&json["source_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
+                    resolve_path(&json["source_directory"].to_string())
                 }
             },
         );
+        let platform = if json["platform"].is_null() {
+            parent.get_platform().map(str::to_string)
+        } else {
+            Some(json["platform"].to_string())
+        };
+        if platform.is_some() && !config.get_backend().eq_ignore_ascii_case("docker") {
+            if !json["platform"].is_null() {
+                warn!("Platform cannot be set if docker is not the backend.");
+            }
+        } else {
+            config.set_platform(platform);
+        }
+        config.set_use_gitignore(if json["use_gitignore"].is_null() {
+            parent.get_use_gitignore()
+        } else {
+            json["use_gitignore"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for use_gitignore in the configuration.");
+                panic!("There was no valid value for use_gitignore in the configuration.");
+            })
+        });
+        config.set_clean_paths(if json["clean_paths"].is_null() {
+            parent.get_clean_paths()
+        } else {
+            json["clean_paths"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for clean_paths in the configuration.");
+                panic!("There was no valid value for clean_paths in the configuration.");
+            })
+        });
+        config.set_metrics_dir(if json["metrics_dir"].is_null() {
+            parent.get_metrics_dir().map(str::to_string)
+        } else {
+            Some(resolve_path(&json["metrics_dir"].to_string()))
+        });
+        config.set_metrics_filename(if json["metrics_filename"].is_null() {
+            parent.get_metrics_filename().map(str::to_string)
+        } else {
+            Some(json["metrics_filename"].to_string())
+        });
+        let mut redact = parent.get_redact().to_vec();
+        if !json["redact"].is_null() {
+            let patterns = json["redact"].is_array().then(|| json["redact"].members().map(|pattern| pattern.as_str().map(str::to_string)).collect::<Option<Vec<String>>>()).flatten().unwrap_or_else(|| {
+                error!("There was no valid value for redact in the configuration, it must be an array of patterns.");
+                panic!("There was no valid value for redact in the configuration, it must be an array of patterns.");
+            });
+            for pattern in patterns {
+                if let Err(reason) = RedactPattern::parse(&pattern) {
+                    error!("There was no valid value for redact in the configuration, {} is not a valid pattern: {}.", pattern, reason);
+                    panic!("There was no valid value for redact in the configuration, {} is not a valid pattern: {}.", pattern, reason);
+                }
+                if !redact.contains(&pattern) {
+                    redact.push(pattern);
+                }
+            }
+        }
+        config.set_redact(redact);
+        config
+    }
+
+    /// Parses a single [`Action`] named `name`, inheriting unset configuration from `shared_config`
+    ///
+    /// Panics if the action is not defined or its configuration is invalid.
+    fn parse_action(
+        shared_config: &ShareableConfiguration,
+        json: &JsonValue,
+        name: &str,
+    ) -> Action {
+        if json.is_null() {
+            panic!(
+                "Could not find action defined with appropriate tag: {}",
+                name
+            )
+        }
+        warn_unknown_keys(json, ACTION_KEYS, &[], &format!("Action: {}", name));
+        let new_shared_config = parse_inherited_config(shared_config, json, Some(name.to_string()));
+        warn_ignored_image(json, &new_shared_config, &format!("Action: {}", name));
 
-        let action_config = ActionConfig::new(
+        let mut action_config = ActionConfig::new(
             {
                 let conditions = parse_json_to_conditions(&json["conditions"]);
                 if conditions.is_empty() {
@@ -170,7 +818,8 @@ pub mod json_parser {
                 if json["retries"].is_null() {
                     Some(0)
                 } else {
-                    Some(json["retries"].as_i8().unwrap_or_else(|| {
+                    let location = format!("Action: {}", name);
+                    Some(lenient_i8(&json["retries"], "retries", &location).unwrap_or_else(|| {
                             error!("There was no valid value for retries in the configuration. Error occured in Action: {}", name);
                             panic!("There was no valid value for retries in the configuration. Error occured in Action: {}", name);
                         }))
@@ -180,9 +829,9 @@ pub mod json_parser {
                 if json["allowed_failure"].is_null() {
                     Some(false)
                 } else {
-                    Some(json["allowed_failure"].as_bool().unwrap_or_else(|| {
-                            error!("There was no valid value for retries in the configuration. Error occured in Action: {}", name);
-                            panic!("There was no valid value for retries in the configuration. Error occured in Action: {}", name);
+                    Some(lenient_bool(&json["allowed_failure"]).unwrap_or_else(|| {
+                            error!("There was no valid value for allowed_failure in the configuration. Error occured in Action: {}", name);
+                            panic!("There was no valid value for allowed_failure in the configuration. Error occured in Action: {}", name);
                             }
                         ))
                 }
@@ -196,12 +845,120 @@ pub mod json_parser {
                 manual
             },
         );
+        if !json["shared_shell"].is_null() {
+            action_config.set_shared_shell(json["shared_shell"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for shared_shell in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for shared_shell in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["merge_output"].is_null() {
+            action_config.set_merge_output(json["merge_output"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for merge_output in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for merge_output in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["fail_on_stderr"].is_null() {
+            action_config.set_fail_on_stderr(json["fail_on_stderr"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for fail_on_stderr in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for fail_on_stderr in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["tty"].is_null() {
+            action_config.set_tty(json["tty"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for tty in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for tty in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["docker_workdir"].is_null() {
+            if new_shared_config.get_backend().eq_ignore_ascii_case("docker") {
+                action_config.set_docker_workdir(Some(json["docker_workdir"].as_str().filter(|workdir| is_absolute_container_path(workdir)).map(str::to_string).unwrap_or_else(|| {
+                    error!("There was no valid value for docker_workdir in the configuration, it must be an absolute path. Error occured in Action: {}", name);
+                    panic!("There was no valid value for docker_workdir in the configuration, it must be an absolute path. Error occured in Action: {}", name);
+                })));
+            } else {
+                warn!("Docker workdir cannot be set if docker is not the backend. Error occured in Action: {}", name);
+            }
+        }
+        if !json["docker_user"].is_null() {
+            if new_shared_config.get_backend().eq_ignore_ascii_case("docker") {
+                action_config.set_docker_user(Some(json["docker_user"].as_str().filter(|user| !user.is_empty() && !user.contains(char::is_whitespace)).map(str::to_string).unwrap_or_else(|| {
+                    error!("There was no valid value for docker_user in the configuration. Error occured in Action: {}", name);
+                    panic!("There was no valid value for docker_user in the configuration. Error occured in Action: {}", name);
+                })));
+            } else {
+                warn!("Docker user cannot be set if docker is not the backend. Error occured in Action: {}", name);
+            }
+        }
+        if !json["step_timeout"].is_null() {
+            action_config.set_step_timeout(Some(Duration::from_secs(json["step_timeout"].as_u64().unwrap_or_else(|| {
+                error!("There was no valid value for step_timeout in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for step_timeout in the configuration. Error occured in Action: {}", name);
+            }))));
+        }
+        for key in [
+            "pull_retries",
+            "pull_retry_delay",
+            "cache_key_files",
+            "docker_context_tar",
+            "pull_policy",
+        ] {
+            if !json[key].is_null() && !new_shared_config.get_backend().eq_ignore_ascii_case("docker") {
+                warn!("{} has no effect if docker is not the backend. Error occured in Action: {}", key, name);
+            }
+        }
+        if !json["pull_retries"].is_null() {
+            action_config.set_pull_retries(json["pull_retries"].as_u32().unwrap_or_else(|| {
+                error!("There was no valid value for pull_retries in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for pull_retries in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["pull_retry_delay"].is_null() {
+            action_config.set_pull_retry_delay(Duration::from_secs(json["pull_retry_delay"].as_u64().unwrap_or_else(|| {
+                error!("There was no valid value for pull_retry_delay in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for pull_retry_delay in the configuration. Error occured in Action: {}", name);
+            })));
+        }
+        if !json["weight"].is_null() {
+            action_config.set_weight(json["weight"].as_usize().unwrap_or_else(|| {
+                error!("There was no valid value for weight in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for weight in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["concurrency"].is_null() {
+            action_config.set_concurrency(json["concurrency"].as_usize().filter(|concurrency| *concurrency > 0).unwrap_or_else(|| {
+                error!("There was no valid value for concurrency in the configuration, it must be at least 1. Error occured in Action: {}", name);
+                panic!("There was no valid value for concurrency in the configuration, it must be at least 1. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["cache_key_files"].is_null() {
+            let files = json["cache_key_files"].is_array().then(|| json["cache_key_files"].members().map(|file| file.as_str().map(str::to_string)).collect::<Option<Vec<String>>>()).flatten();
+            action_config.set_cache_key_files(files.unwrap_or_else(|| {
+                error!("There was no valid value for cache_key_files in the configuration, it must be an array of paths. Error occured in Action: {}", name);
+                panic!("There was no valid value for cache_key_files in the configuration, it must be an array of paths. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["skip"].is_null() {
+            action_config.set_skip(json["skip"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for skip in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for skip in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["docker_context_tar"].is_null() {
+            action_config.set_docker_context_tar(json["docker_context_tar"].as_bool().unwrap_or_else(|| {
+                error!("There was no valid value for docker_context_tar in the configuration. Error occured in Action: {}", name);
+                panic!("There was no valid value for docker_context_tar in the configuration. Error occured in Action: {}", name);
+            }));
+        }
+        if !json["pull_policy"].is_null() {
+            action_config.set_pull_policy(Some(json["pull_policy"].as_str().and_then(PullPolicy::parse).unwrap_or_else(|| {
+                error!("There was no valid value for pull_policy in the configuration, it must be always, if-not-present or never. Error occured in Action: {}", name);
+                panic!("There was no valid value for pull_policy in the configuration, it must be always, if-not-present or never. Error occured in Action: {}", name);
+            })));
+        }
         Action::new(new_shared_config, action_config)
     }
 
-    /**
-     *
-     */
+    /// Parses every [`Pipeline`] named in `pipeline_defs` from the top level of a configuration
     fn parse_pipeline_defs(
         shared_config: &ShareableConfiguration,
         json: &JsonValue,
@@ -214,237 +971,158 @@ pub mod json_parser {
         pipelines
     }
 
-    /**
-     *
-     *
-     */
+    /// Returns the names of the actions listed in the `actions` of the pipeline named `name`
+    ///
+    /// Each entry is either the name of an action defined within the pipeline, a wildcard matching the actions defined
+    /// within the pipeline (see [`expand_wildcards`]), or an action defined inline as an object, which is named by its
+    /// `title`.
+    ///
+    /// Panics if the pipeline does not contain a list of actions, or if an inline action has no title.
+    fn parse_pipeline_action_defs(json: &JsonValue, name: &str) -> Vec<String> {
+        let actions = &json["actions"];
+        if actions.is_null() {
+            panic!("No list of action definitions found!");
+        }
+        if actions.is_empty() {
+            warn!("No mappable values found in json vector {:#?}", actions);
+        }
+        let entries = actions
+            .members()
+            .map(|entry| {
+                if entry.is_object() {
+                    entry["title"].as_str().map(str::to_string).unwrap_or_else(|| {
+                        error!("Actions defined inline must have a title. Error occured in Pipeline: {}", name);
+                        panic!("Actions defined inline must have a title. Error occured in Pipeline: {}", name);
+                    })
+                } else {
+                    entry.to_string()
+                }
+            })
+            .collect();
+        let definitions = action_definitions(json, PIPELINE_KEYS, &[]);
+        expand_wildcards(entries, &definitions, &format!("Pipeline: {}", name))
+    }
+
+    /// Parses a single [`Pipeline`] named `name`, along with every [`Action`] it defines
+    ///
+    /// Panics if the pipeline is not defined or does not contain a list of actions.
     fn parse_pipeline(
         shared_config: &ShareableConfiguration,
         json: &JsonValue,
         name: &str,
     ) -> Pipeline {
-        let root = current_dir().unwrap();
         if json.is_null() {
             panic!("No pipeline found with the name: {}", name);
         }
-        let backend = {
-            if json["backend"].is_null() {
-                shared_config.get_backend().to_string()
-            } else {
-                json["backend"].to_string()
-            }
-        };
+        let action_defs = parse_pipeline_action_defs(json, name);
+        warn_unknown_keys(
+            json,
+            PIPELINE_KEYS,
+            &action_defs,
+            &format!("Pipeline: {}", name),
+        );
+        ensure_unique(
+            &action_defs,
+            "action",
+            &format!("the actions of Pipeline: {}", name),
+        );
+        let new_shared_config = parse_inherited_config(shared_config, json, Some(name.to_string()));
+        warn_ignored_image(json, &new_shared_config, &format!("Pipeline: {}", name));
 
-        let new_shared_config = ShareableConfiguration::new(
+        let mut pipeline_config = PipelineConfig::new(
             {
-                if json["metadata"].is_null() {
+                let conditions = parse_json_to_conditions(&json["conditions"]);
+                if conditions.is_empty() {
                     None
                 } else {
-                    Some(parse_json_map(&json["metadata"]))
+                    Some(conditions)
                 }
             },
-            Some(name.to_string()),
+            action_defs.clone(),
             {
-                if json["tags"].is_null() {
-                    None
-                } else {
-                    Some(parse_json_map(&json["tags"]))
+                let mut actions = vec![];
+                for action_name in &action_defs {
+                    let action_json = json["actions"]
+                        .members()
+                        .find(|entry| entry.is_object() && entry["title"] == action_name.as_str())
+                        .unwrap_or(&json[action_name.as_str()]);
+                    actions.push(parse_action(&new_shared_config, action_json, action_name));
                 }
-            },
-            {
-                if json["language"].is_null() {
-                    shared_config.get_language().to_string()
-                } else {
-                    json["language"].to_string()
+                for action in &mut actions {
+                    action.set_pipeline(Some(name.to_string()));
                 }
+                actions
             },
             {
-                if !backend.to_lowercase().eq("docker")
-                    && !backend.is_empty()
-                    && backend != "bash"
-                    && backend != "batch"
-                {
-                    warn!("Image cannot be set if docker is not the backend.");
+                if json["requires"].is_null() {
                     None
-                } else if json["image"].is_null() {
-                    shared_config.get_image()
-                } else {
-                    Some(json["image"].to_string())
-                }
-            },
-            backend,
-            {
-                if json["output_directory"].is_null() {
-                    shared_config.get_output().to_string()
                 } else {
-                    RelativePath::new(&json["output_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                }
-            },
-            {
-                if json["source_directory"].is_null() {
-                    shared_config.get_source().to_string()
-                } else {
-                    RelativePath::new(&json["source_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
+                    Some(parse_json_vector(&json["requires"]))
                 }
             },
         );
+        if !json["allowed_failure"].is_null() {
+            pipeline_config.set_allowed_failure(lenient_bool(&json["allowed_failure"]).unwrap_or_else(|| {
+                error!("There was no valid value for allowed_failure in the configuration. Error occured in Pipeline: {}", name);
+                panic!("There was no valid value for allowed_failure in the configuration. Error occured in Pipeline: {}", name);
+            }));
+        }
+        if !json["requires_timeout"].is_null() {
+            pipeline_config.set_requires_timeout(Some(Duration::from_secs(json["requires_timeout"].as_u64().unwrap_or_else(|| {
+                error!("There was no valid value for requires_timeout in the configuration. Error occured in Pipeline: {}", name);
+                panic!("There was no valid value for requires_timeout in the configuration. Error occured in Pipeline: {}", name);
+            }))));
+        }
+        Pipeline::new(new_shared_config, pipeline_config)
+    }
 
-        let pipeline_config = PipelineConfig::new(
-            {
-                let conditions = parse_json_to_conditions(&json["conditions"]);
-                if conditions.is_empty() {
-                    None
-                } else {
-                    Some(conditions)
-                }
-            },
-            {
-                if json["actions"].is_null() {
-                    panic!("No list of action definitions found!");
-                } else {
-                    parse_json_vector(&json["actions"])
-                }
-            },
-            parse_action_defs(
-                &new_shared_config,
-                &parse_json_vector(&json["actions"]),
-                json,
-            ),
-            {
-                if json["requires"].is_null() {
-                    None
-                } else {
-                    Some(parse_json_vector(&json["requires"]))
-                }
-            },
-        );
-        Pipeline::new(new_shared_config, pipeline_config)
-    }
-
-    /**
-     *
-     */
+    /// Parses the top-level [`ShareableConfiguration`], defaulting any values that are not configured
     fn parse_shared_config(json: &JsonValue) -> ShareableConfiguration {
-        let root = current_dir().unwrap();
-        let backend = {
-            if json["backend"].is_null() {
-                "bash".to_string()
-            } else {
-                json["backend"].to_string()
-            }
-        };
-
-        let new_shared_config = ShareableConfiguration::new(
-            {
-                if json["metadata"].is_null() {
-                    None
-                } else {
-                    Some(parse_json_map(&json["metadata"]))
-                }
-            },
-            Some(json["title"].to_string()),
-            {
-                if json["tags"].is_null() {
-                    None
-                } else {
-                    Some(parse_json_map(&json["tags"]))
-                }
-            },
-            {
-                if json["language"].is_null() {
-                    "Python".to_string()
-                } else {
-                    json["language"].to_string()
-                }
-            },
-            {
-                if !backend.to_lowercase().eq("docker")
-                    && !backend.is_empty()
-                    && backend != "bash"
-                    && backend != "batch"
-                {
-                    warn!("Image cannot be set if docker is not the backend.");
-                    None
-                } else if json["image"].is_null() {
-                    None
-                } else {
-                    Some(json["image"].to_string())
-                }
-            },
-            backend,
-            {
-                if json["output_directory"].is_null() {
-                    RelativePath::new("./dist/cider/")
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                } else {
-                    RelativePath::new(&json["output_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                }
-            },
-            {
-                if json["source_directory"].is_null() {
-                    RelativePath::new("./src")
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                } else {
-                    RelativePath::new(&json["source_directory"].to_string())
-                        .to_path(&root)
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                }
-            },
+        let defaults = ShareableConfiguration::new(
+            None,
+            None,
+            None,
+            "Python".to_string(),
+            None,
+            "bash".to_string(),
+            resolve_path("./dist/cider/"),
+            resolve_path("./src"),
         );
-        new_shared_config
+        let config = parse_inherited_config(&defaults, json, Some(json["title"].to_string()));
+        warn_ignored_image(json, &config, "the top level of the configuration");
+        config
     }
 
-    /// Creates a new set of configuration data specific to the top-level of a CIder configuration.
-    ///
-    /// Parses a JSON file's contents into a set of data that is readable by CIder in order to successfully execute
-    /// the instructions provided via said JSON
+    /// Reads the contents of a configuration file
     ///
-    /// ```
-    /// use cider::parsing::json_parser;
-    /// let config = json_parser::new_top_level("./cider_config.json");
-    /// ```
-    /// This function will panic when provided with a configuration file that is not found on the host device.
-    ///  
-
-    pub fn new_top_level(filename: &str) -> TopLevelConfiguration {
-        println!("{}", filename);
-        let file_contents = fs::read_to_string(filename).unwrap_or_else(|err| {
+    /// Panics if the file cannot be found or read.
+    fn read_config_file(path: &Path) -> String {
+        fs::read_to_string(path).unwrap_or_else(|err| {
             eprintln!("{}", err);
             error!(
                 "There was an error locating your configuration file: {}",
                 err
             );
             panic!("{}", err.to_string());
-        });
-        let parsed_data = json::parse(&file_contents).unwrap_or_else(|err| {
+        })
+    }
+
+    /// Parses the contents of a configuration file into JSON
+    ///
+    /// Panics if the contents are not valid JSON.
+    fn parse_json_document(contents: &str) -> JsonValue {
+        json::parse(contents).unwrap_or_else(|err| {
             eprintln!();
             error!(
                 "There was an error parsing your configuration file: {}",
                 err
             );
             panic!("{}", err.to_string());
-        });
-        let s_config = parse_shared_config(&parsed_data);
+        })
+    }
+
+    /// Returns the pipeline, action and post_run definitions found at the top level of a configuration
+    fn parse_top_level_defs(parsed_data: &JsonValue) -> (Vec<String>, Vec<String>, Vec<String>) {
         let pipeline_defs = {
             if (parsed_data["pipelines"]).is_null() {
                 vec![]
@@ -452,7 +1130,6 @@ pub mod json_parser {
                 parse_json_vector(&parsed_data["pipelines"])
             }
         };
-        let pipelines = parse_pipeline_defs(&s_config, &parsed_data, &pipeline_defs);
         let action_defs = {
             if (parsed_data["actions"]).is_null() {
                 vec![]
@@ -460,66 +1137,1387 @@ pub mod json_parser {
                 parse_json_vector(&parsed_data["actions"])
             }
         };
-        let actions = parse_action_defs(&s_config, &action_defs, &parsed_data);
-        TopLevelConfiguration::new(s_config, pipeline_defs, pipelines, action_defs, actions)
+        let post_run_defs = {
+            if (parsed_data["post_run"]).is_null() {
+                vec![]
+            } else {
+                parse_json_vector(&parsed_data["post_run"])
+            }
+        };
+        let definitions = action_definitions(
+            parsed_data,
+            TOP_LEVEL_KEYS,
+            &[pipeline_defs.as_slice(), post_run_defs.as_slice()].concat(),
+        );
+        let action_defs =
+            expand_wildcards(action_defs, &definitions, "the top level of the configuration");
+        warn_unknown_keys(
+            parsed_data,
+            TOP_LEVEL_KEYS,
+            &[pipeline_defs.as_slice(), action_defs.as_slice(), post_run_defs.as_slice()].concat(),
+            "the top level of the configuration",
+        );
+        ensure_unique(
+            &pipeline_defs,
+            "pipeline",
+            "the pipelines of the top level of the configuration",
+        );
+        ensure_unique(
+            &action_defs,
+            "action",
+            "the actions of the top level of the configuration",
+        );
+        ensure_unique(&post_run_defs, "action", "the post_run of the configuration");
+        (pipeline_defs, action_defs, post_run_defs)
     }
 
-    /**
-     *
-     */
-    pub fn overwrite_top_level(
-        mut config: TopLevelConfiguration,
-        filename: &str,
+    /// Returns every name that appears more than once within `names`, in the order they are first repeated
+    fn find_duplicates<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+        let mut seen = vec![];
+        let mut duplicates = vec![];
+        for name in names {
+            if seen.contains(&name) {
+                if !duplicates.contains(&name) {
+                    duplicates.push(name);
+                }
+            } else {
+                seen.push(name);
+            }
+        }
+        duplicates
+    }
+
+    /// Describes every name listed more than once within `names`, where `kind` is what the names refer to and
+    /// `location` is where they are listed
+    fn duplicate_problems<'a>(
+        names: impl IntoIterator<Item = &'a str>,
+        kind: &str,
+        location: &str,
+    ) -> Vec<String> {
+        find_duplicates(names)
+            .into_iter()
+            .map(|name| format!("The {} \"{}\" is listed more than once in {}", kind, name, location))
+            .collect()
+    }
+
+    /// Panics if a name is listed more than once within `names`, as names have to refer to exactly one definition
+    fn ensure_unique(names: &[String], kind: &str, location: &str) {
+        if let Some(problem) = duplicate_problems(names.iter().map(String::as_str), kind, location).first() {
+            error!("{}", problem);
+            panic!("{}", problem);
+        }
+    }
+
+    /// Creates a new set of configuration data specific to the top-level of a CIder configuration.
+    ///
+    /// Parses a JSON file's contents into a set of data that is readable by CIder in order to successfully execute
+    /// the instructions provided via said JSON
+    ///
+    /// ```
+    /// use cider::parsing::json_parser;
+    /// let config = json_parser::new_top_level("./cider_config.json");
+    /// ```
+    /// This function will panic when provided with a configuration file that is not found on the host device.
+    ///
+    /// The path can be given as anything that can be viewed as a [`Path`], such as a `&str` or a `PathBuf`.
+    pub fn new_top_level(path: impl AsRef<Path>) -> TopLevelConfiguration {
+        new_top_level_with_options(path, &ParseOptions::default())
+    }
+
+    /// Creates a new [`TopLevelConfiguration`] from a configuration file, parsed according to `options`.
+    ///
+    /// ```
+    /// use cider::parsing::json_parser::{self, ParseOptions};
+    /// let options = ParseOptions { strict_env: true, ..Default::default() };
+    /// let config = json_parser::new_top_level_with_options("./cider_config.json", &options);
+    /// ```
+    /// This function will panic when provided with a configuration file that is not found on the host device.
+    ///
+    /// Relative paths within the file are resolved against the directory containing it, unless
+    /// [`ParseOptions::paths_relative_to_cwd`] is set.
+    pub fn new_top_level_with_options(
+        path: impl AsRef<Path>,
+        options: &ParseOptions,
     ) -> TopLevelConfiguration {
-        let file_contents = fs::read_to_string(filename).unwrap_or_else(|err| {
-            eprintln!("{}", err);
-            error!(
-                "There was an error locating your configuration file: {}",
-                err
-            );
-            panic!("{}", err.to_string());
-        });
-        let parsed_data = json::parse(&file_contents).unwrap_or_else(|err| {
-            eprintln!();
-            error!(
-                "There was an error parsing your configuration file: {}",
-                err
-            );
-            panic!("{}", err.to_string());
-        });
-        config.s_config = parse_shared_config(&parsed_data);
-        config.set_pipeline_defs({
-            if (parsed_data["pipelines"]).is_null() {
-                vec![]
+        let path = path.as_ref();
+        let format = resolve_format(path, options);
+        info!("Parsing {:?} configuration file: {}", format, path.display());
+        let base = config_base_dir(path, options);
+        let contents = read_config_file(path);
+        ensure_cider_document(&contents);
+        let mut config = build_top_level(&prepare_document(&contents, options, base.as_deref()));
+        locate_definitions(&mut config, &contents, Some(&path.display().to_string()));
+        config
+    }
+
+    /// Creates a new [`TopLevelConfiguration`] from the contents of a configuration file.
+    ///
+    /// Behaves the same as [`new_top_level`], but does not require the configuration to exist on disk.
+    ///
+    /// ```
+    /// use cider::parsing::json_parser;
+    /// let config = json_parser::parse_from_str(r#"{
+    ///     "actions": ["hello"],
+    ///     "hello": { "manual": { "greet": "echo hello" } }
+    /// }"#);
+    ///
+    /// assert_eq!(config.get_action_defs(), &vec!["hello".to_string()]);
+    /// ```
+    /// This function will panic when provided with contents that are not valid JSON.
+    pub fn parse_from_str(contents: &str) -> TopLevelConfiguration {
+        parse_from_str_with_options(contents, &ParseOptions::default())
+    }
+
+    /// Creates a new [`TopLevelConfiguration`] from the contents of a configuration file, parsed according to `options`.
+    ///
+    /// Before any configuration is built, the variables of the configured env file are loaded into the environment, and
+    /// every `${VAR}` reference found in a string value is replaced with the value of the environment variable `VAR`.
+    ///
+    /// This function will panic when provided with contents that are not valid JSON.
+    pub fn parse_from_str_with_options(contents: &str, options: &ParseOptions) -> TopLevelConfiguration {
+        ensure_cider_document(contents);
+        let mut config = build_top_level(&prepare_document(contents, options, None));
+        locate_definitions(&mut config, contents, None);
+        config
+    }
+
+    /// Builds a [`TopLevelConfiguration`] from a JSON document that has been prepared with [`prepare_document`]
+    fn build_top_level(parsed_data: &JsonValue) -> TopLevelConfiguration {
+        let s_config = parse_shared_config(parsed_data);
+        let (pipeline_defs, action_defs, post_run_defs) = parse_top_level_defs(parsed_data);
+        let conditions = parse_json_to_conditions(&parsed_data["conditions"]);
+        let mut pipelines = parse_pipeline_defs(&s_config, parsed_data, &pipeline_defs);
+        let mut actions = parse_action_defs(&s_config, &action_defs, parsed_data);
+        let mut post_run = parse_action_defs(&s_config, &post_run_defs, parsed_data);
+        for action in actions.iter_mut().chain(&mut post_run) {
+            action.action_config.set_inherited_conditions(conditions.clone());
+        }
+        for pipeline in &mut pipelines {
+            let mut inherited = conditions.clone();
+            for condition in pipeline.pipeline_config.conditions() {
+                if !inherited.contains(condition) {
+                    inherited.push(condition.clone());
+                }
+            }
+            let mut pipeline_actions = pipeline.pipeline_config.get_actions().clone();
+            for action in &mut pipeline_actions {
+                action.action_config.set_inherited_conditions(inherited.clone());
+            }
+            pipeline.pipeline_config.set_actions(pipeline_actions);
+        }
+        let mut config = TopLevelConfiguration::new(s_config, pipeline_defs, pipelines, action_defs, actions);
+        config.set_post_run(post_run);
+        config.set_before_each(parse_hook(parsed_data, "before_each"));
+        config.set_after_each(parse_hook(parsed_data, "after_each"));
+        config.set_conditions(conditions);
+        config
+    }
+
+    /// Finds the line and column that each object within a JSON document starts at, which the `json` crate does not
+    /// keep track of
+    ///
+    /// Each object is found by the keys that lead to it from the root of the document, where the elements of arrays are
+    /// named by their index, such as `#0`. An object is located at its key, or at its opening brace within an array.
+    fn definition_positions(contents: &str) -> HashMap<Vec<String>, (usize, usize)> {
+        // Whether each enclosing container is an object, the index of the current element within it, and
+        // whether it was given a name within the path
+        let mut containers: Vec<(bool, usize, bool)> = vec![];
+        let mut path = vec![];
+        let mut positions = HashMap::new();
+        let mut key: Option<(String, (usize, usize))> = None;
+        let mut expect_key = false;
+        let chars = contents.chars().collect::<Vec<_>>();
+        let (mut line, mut column) = (1, 1);
+        let mut i = 0;
+        while i < chars.len() {
+            let start = (line, column);
+            match chars[i] {
+                '"' => {
+                    let mut text = String::new();
+                    i += 1;
+                    column += 1;
+                    while i < chars.len() && chars[i] != '"' {
+                        if chars[i] == '\\' {
+                            text.push(chars[i]);
+                            i += 1;
+                            column += 1;
+                        }
+                        if let Some(c) = chars.get(i) {
+                            text.push(*c);
+                            i += 1;
+                            column += 1;
+                        }
+                    }
+                    if expect_key {
+                        key = Some((text, start));
+                        expect_key = false;
+                    }
+                }
+                open @ ('{' | '[') => {
+                    let name = match containers.last() {
+                        Some((true, _, _)) => key.take(),
+                        Some((false, index, _)) => Some((format!("#{}", index), start)),
+                        None => None,
+                    };
+                    let named = name.is_some();
+                    if let Some((name, position)) = name {
+                        path.push(name);
+                        positions.entry(path.clone()).or_insert(position);
+                    }
+                    containers.push((open == '{', 0, named));
+                    expect_key = open == '{';
+                }
+                '}' | ']' => {
+                    if let Some((_, _, true)) = containers.pop() {
+                        path.pop();
+                    }
+                    key = None;
+                    expect_key = false;
+                }
+                ',' => {
+                    key = None;
+                    if let Some((object, index, _)) = containers.last_mut() {
+                        *index += 1;
+                        expect_key = *object;
+                    }
+                }
+                _ => {}
+            }
+            if chars.get(i) == Some(&'\n') {
+                line += 1;
+                column = 1;
             } else {
-                parse_json_vector(&parsed_data["pipelines"])
+                column += 1;
+            }
+            i += 1;
+        }
+        positions
+    }
+
+    /// Records where each pipeline and action of `config` was defined within `contents`, the configuration file it was
+    /// parsed from, which is named `file` if it was read from one
+    ///
+    /// Definitions that already have a location keep it. An action defined inline within a list of actions is located
+    /// at its place in the list.
+    fn locate_definitions(config: &mut TopLevelConfiguration, contents: &str, file: Option<&str>) {
+        let document = parse_json_document(contents);
+        let positions = definition_positions(contents);
+        let locate = |parent: &[&str], title: &str| {
+            let parent_json = parent.iter().fold(&document, |json, key| &json[*key]);
+            let inline = parent_json["actions"]
+                .members()
+                .position(|entry| entry.is_object() && entry["title"] == title)
+                .map(|index| format!("#{}", index));
+            let mut keys = parent.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+            match inline {
+                Some(index) => keys.extend(["actions".to_string(), index]),
+                None => keys.push(title.to_string()),
+            }
+            Some(SourceLocation {
+                file: file.map(str::to_string),
+                position: positions.get(&keys).copied(),
+            })
+        };
+        let locate_actions = |actions: &[Action], parent: &[&str]| {
+            let mut actions = actions.to_vec();
+            for action in actions.iter_mut().filter(|action| action.get_location().is_none()) {
+                let title = action.shared_config.title().unwrap_or_default();
+                action.set_location(locate(parent, title));
             }
+            actions
+        };
+        config.set_actions(locate_actions(config.get_actions(), &[]));
+        config.set_post_run(locate_actions(config.get_post_run(), &[]));
+        let mut pipelines = config.get_pipelines().clone();
+        for pipeline in &mut pipelines {
+            let title = pipeline.shared_config.title().unwrap_or_default().to_string();
+            if pipeline.get_location().is_none() {
+                pipeline.set_location(Some(SourceLocation {
+                    file: file.map(str::to_string),
+                    position: positions.get(&vec![title.clone()]).copied(),
+                }));
+            }
+            let actions = locate_actions(pipeline.pipeline_config.get_actions(), &[&title]);
+            pipeline.pipeline_config.set_actions(actions);
+        }
+        config.set_pipelines(pipelines);
+    }
+
+    /// Panics if the contents of a configuration file do not look like a CIder configuration, see
+    /// [`foreign_document_problem`]
+    ///
+    /// The contents are checked as they were written, as preparing them fills in the default directories.
+    fn ensure_cider_document(contents: &str) {
+        if let Some(problem) = foreign_document_problem(&parse_json_document(contents)) {
+            error!("{}", problem);
+            panic!("{}", problem);
+        }
+    }
+
+    /// Returns why a document does not look like a CIder configuration, if it does not
+    ///
+    /// A configuration has to be an object, and unless it is empty, has to set at least one key that CIder knows, such
+    /// as `actions`, `pipelines` or `backend`. This catches a path that points at an unrelated file, such as a
+    /// `package.json`, which would otherwise parse into a configuration that runs nothing.
+    fn foreign_document_problem(parsed_data: &JsonValue) -> Option<String> {
+        if !parsed_data.is_object() {
+            return Some(
+                "This doesn't look like a cider config, as it is not an object. Check that the right configuration file was given."
+                    .to_string(),
+            );
+        }
+        let known_keys = [SHARED_KEYS, TOP_LEVEL_KEYS].concat();
+        let keys = parsed_data.entries().map(|(key, _)| key).collect::<Vec<_>>();
+        if keys.is_empty() || keys.iter().any(|key| known_keys.contains(key)) {
+            return None;
+        }
+        Some(format!(
+            "This doesn't look like a cider config, as none of its keys ({}) are CIder settings. Check that the right configuration file was given.",
+            keys.join(", ")
+        ))
+    }
+
+    /// Parses the steps of the `before_each` or `after_each` of a configuration, which are written like a manual
+    fn parse_hook(parsed_data: &JsonValue, key: &str) -> Vec<Step> {
+        let json = &parsed_data[key];
+        if !json.is_null() && !json.is_object() {
+            error!("There was no valid value for {} in the configuration, it must be an object of steps like a manual.", key);
+            panic!("There was no valid value for {} in the configuration, it must be an object of steps like a manual.", key);
+        }
+        parse_json_to_steps(json)
+    }
+
+    /// Parses a JSON document, then loads its env file and interpolates environment variables, and the variables of
+    /// the vars file if one is given, into it
+    ///
+    /// If `base` is given, the relative paths of the document, including the path of its env file, are resolved
+    /// against it.
+    fn prepare_document(contents: &str, options: &ParseOptions, base: Option<&Path>) -> JsonValue {
+        let mut parsed_data = parse_json_document(contents);
+        let vars = options.vars_file.as_deref().map(load_vars_file).unwrap_or_default();
+        let env_file = options.env_file.clone().or_else(|| {
+            parsed_data["env_file"].as_str().map(|env_file| {
+                let env_file = interpolate_env_str(env_file, &vars, options.strict_env);
+                resolve_path_against(&env_file, base)
+            })
         });
-        config.set_pipelines(parse_pipeline_defs(
-            &config.s_config,
-            &parsed_data,
-            config.get_pipeline_defs(),
-        ));
-        config.set_action_defs({
-            if (parsed_data["actions"]).is_null() {
-                vec![]
+        if let Some(env_file) = env_file {
+            load_env_file(&env_file);
+        }
+        interpolate_env(&mut parsed_data, &vars, options.strict_env);
+        if let Some(base) = base {
+            resolve_document_paths(&mut parsed_data, base);
+        }
+        parsed_data
+    }
+
+    /// Layers the configuration found at `path` over an existing [`TopLevelConfiguration`], keeping everything that the
+    /// file does not set
+    ///
+    /// Only the keys present in the file are applied. Objects, such as the definition of an action or its `manual`, are
+    /// merged key by key, so an override file can change a single step of an action while leaving its other steps and
+    /// settings intact. Any other value, including the `pipelines` and `actions` arrays, replaces the existing one.
+    /// Values that a pipeline or action shares with the level above it are treated as inherited, so changing a
+    /// top-level value also changes it for every pipeline and action that did not set a different value.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::parsing::json_parser;
+    ///
+    /// let base = json_parser::new_top_level("./cider_config.json");
+    /// let overridden = json_parser::overwrite_top_level(base.clone(), "./cider_config.json");
+    ///
+    /// assert_eq!(overridden, base);
+    /// ```
+    ///
+    /// This function will panic when provided with a configuration file that is not found on the host device.
+    pub fn overwrite_top_level(
+        config: TopLevelConfiguration,
+        path: impl AsRef<Path>,
+    ) -> TopLevelConfiguration {
+        let path = path.as_ref();
+        let options = ParseOptions::default();
+        let base = config_base_dir(path, &options);
+        let contents = read_config_file(path);
+        let mut overrides = prepare_document(&contents, &options, base.as_deref());
+        // Resolving paths fills in the default directories, which would replace the existing ones
+        let written = parse_json_document(&contents);
+        for key in PATH_KEYS {
+            if written[*key].is_null() {
+                overrides.remove(key);
+            }
+        }
+        let mut document = config_document(&config);
+        merge_document(&mut document, &overrides);
+        let mut overwritten = build_top_level(&document);
+        keep_locations(&mut overwritten, &config);
+        locate_definitions(&mut overwritten, &contents, Some(&path.display().to_string()));
+        overwritten
+    }
+
+    /// Gives each pipeline and action of `config` the location of the one with the same title within `previous`, so
+    /// that layering a file over a configuration keeps the locations of the definitions it had already
+    fn keep_locations(config: &mut TopLevelConfiguration, previous: &TopLevelConfiguration) {
+        let mut previous_actions = previous.get_all_actions();
+        previous_actions.extend(previous.get_post_run().iter().cloned());
+        let keep = |actions: &[Action]| {
+            let mut actions = actions.to_vec();
+            for action in &mut actions {
+                let location = previous_actions
+                    .iter()
+                    .find(|previous| {
+                        previous.get_pipeline() == action.get_pipeline()
+                            && previous.shared_config.title() == action.shared_config.title()
+                    })
+                    .and_then(|previous| previous.get_location().cloned());
+                action.set_location(location);
+            }
+            actions
+        };
+        config.set_actions(keep(config.get_actions()));
+        config.set_post_run(keep(config.get_post_run()));
+        let mut pipelines = config.get_pipelines().clone();
+        for pipeline in &mut pipelines {
+            let location = previous
+                .get_pipelines()
+                .iter()
+                .find(|previous| previous.shared_config.title() == pipeline.shared_config.title())
+                .and_then(|previous| previous.get_location().cloned());
+            pipeline.set_location(location);
+            let actions = keep(pipeline.pipeline_config.get_actions());
+            pipeline.pipeline_config.set_actions(actions);
+        }
+        config.set_pipelines(pipelines);
+    }
+
+    /// Merges `overrides` into `document`, merging objects key by key and replacing every other value
+    fn merge_document(document: &mut JsonValue, overrides: &JsonValue) {
+        for (key, value) in overrides.entries() {
+            if value.is_object() && document[key].is_object() {
+                merge_document(&mut document[key], value);
             } else {
-                parse_json_vector(&parsed_data["actions"])
+                document[key] = value.clone();
             }
-        });
-        config.set_actions(parse_action_defs(
-            &config.s_config,
-            config.get_action_defs(),
-            &parsed_data,
+        }
+    }
+
+    /// Returns a configuration file document that parses back into `config`
+    ///
+    /// Pipelines and actions leave out the values they share with the level above them, so that they inherit them again.
+    fn config_document(config: &TopLevelConfiguration) -> JsonValue {
+        let mut document = JsonValue::new_object();
+        for (key, value) in config.s_config.to_json().entries() {
+            if !value.is_null() {
+                document[key] = value.clone();
+            }
+        }
+        document["pipelines"] = config.get_pipeline_defs().clone().into();
+        document["actions"] = config.get_action_defs().clone().into();
+        document["post_run"] = config
+            .get_post_run()
+            .iter()
+            .map(|action| action.shared_config.title().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .into();
+        for key in ["before_each", "after_each", "conditions"] {
+            let value = &config.to_json()[key];
+            if !value.is_empty() {
+                document[key] = value.clone();
+            }
+        }
+        for action in config.get_actions().iter().chain(config.get_post_run()) {
+            let title = action.shared_config.title().unwrap_or_default();
+            document[title] = action_document(action, &config.s_config);
+        }
+        for pipeline in config.get_pipelines() {
+            let mut json = inherited_document(&pipeline.shared_config, &config.s_config);
+            let pipeline_config = &pipeline.pipeline_config;
+            let conditions = &pipeline_config.to_json()["conditions"];
+            if !conditions.is_null() {
+                json["conditions"] = conditions.clone();
+            }
+            json["actions"] = pipeline_config.get_action_defs().clone().into();
+            if !pipeline_config.get_requires().is_empty() {
+                json["requires"] = pipeline_config.get_requires().clone().into();
+            }
+            if *pipeline_config.get_allowed_failure() {
+                json["allowed_failure"] = true.into();
+            }
+            if let Some(timeout) = pipeline_config.get_requires_timeout() {
+                json["requires_timeout"] = timeout.as_secs().into();
+            }
+            for action in pipeline_config.get_actions() {
+                let title = action.shared_config.title().unwrap_or_default();
+                json[title] = action_document(action, &pipeline.shared_config);
+            }
+            document[pipeline.shared_config.title().unwrap_or_default()] = json;
+        }
+        document
+    }
+
+    /// Returns the configuration of `action` as it would be written within a configuration file, beneath `parent`
+    ///
+    /// Settings that are left at their defaults are left out, as are the conditions it inherited, which it inherits
+    /// again when the document is parsed.
+    fn action_document(action: &Action, parent: &ShareableConfiguration) -> JsonValue {
+        let mut json = inherited_document(&action.shared_config, parent);
+        let defaults = Action::builder("").build().action_config.to_json();
+        for (key, value) in action.action_config.to_json().entries() {
+            let inherited = key == "inherited_conditions";
+            if key == "manual" || (!inherited && !value.is_null() && *value != defaults[key]) {
+                json[key] = value.clone();
+            }
+        }
+        json
+    }
+
+    /// Returns the shared configuration of a pipeline or action that differs from the configuration it inherits from
+    ///
+    /// Metadata and tags are never inherited, so they are always kept.
+    fn inherited_document(
+        config: &ShareableConfiguration,
+        parent: &ShareableConfiguration,
+    ) -> JsonValue {
+        let parent = parent.to_json();
+        let mut json = JsonValue::new_object();
+        for (key, value) in config.to_json().entries() {
+            let inherited = !matches!(key, "metadata" | "tags") && *value == parent[key];
+            if key != "title" && !value.is_null() && !inherited {
+                json[key] = value.clone();
+            }
+        }
+        json
+    }
+
+    /// Checks that a [`TopLevelConfiguration`] can be run on this device, returning a description of every problem found
+    ///
+    /// Currently, this ensures that the `source_directory` of the configuration and each of its actions, including its
+    /// `post_run` actions, exists and is a directory, that no pipeline or action is listed more than once alongside
+    /// another of the same name, and that the `requires` of every pipeline can be satisfied.
+    ///
+    /// ```
+    /// use cider::parsing::json_parser;
+    /// let config = json_parser::parse_from_str(r#"{ "source_directory": "./does_not_exist", "actions": [] }"#);
+    ///
+    /// assert_eq!(json_parser::validate_config(&config).len(), 1);
+    /// ```
+    pub fn validate_config(config: &TopLevelConfiguration) -> Vec<String> {
+        let top_level_source = config.s_config.get_source();
+        let mut problems = vec![];
+        if let Some(problem) = check_source_directory(top_level_source, "the top-level configuration") {
+            problems.push(problem);
+        }
+        for action in config.get_all_actions().iter().chain(config.get_post_run()) {
+            let source = action.shared_config.get_source();
+            if source == top_level_source {
+                continue;
+            }
+            let location = format!(
+                "action \"{}\"{}",
+                action.shared_config.title().unwrap_or_default(),
+                defined_at(action.get_location())
+            );
+            if let Some(problem) = check_source_directory(source, &location) {
+                problems.push(problem);
+            }
+        }
+        problems.extend(duplicate_problems(
+            config.get_pipelines().iter().map(|pipeline| pipeline.shared_config.title().unwrap_or_default()),
+            "pipeline",
+            "the pipelines of the top level of the configuration",
         ));
-        config
+        problems.extend(duplicate_problems(
+            config.get_actions().iter().map(|action| action.shared_config.title().unwrap_or_default()),
+            "action",
+            "the actions of the top level of the configuration",
+        ));
+        for pipeline in config.get_pipelines() {
+            problems.extend(duplicate_problems(
+                pipeline
+                    .pipeline_config
+                    .get_actions()
+                    .iter()
+                    .map(|action| action.shared_config.title().unwrap_or_default()),
+                "action",
+                &format!(
+                    "the actions of Pipeline: {}",
+                    pipeline.shared_config.title().unwrap_or_default()
+                ),
+            ));
+        }
+        if let Err(err) = crate::utils::executor::check_requires(config) {
+            problems.push(err.to_string());
+        }
+        problems
+    }
+
+    /// Describes where a [`TopLevelConfiguration`] has no actions to run, if it has none at all
+    ///
+    /// Running such a configuration does nothing and succeeds, so this reports the top level and each of its pipelines
+    /// as empty. `post_run` actions are not counted, as they only follow the actions that are run.
+    ///
+    /// ```
+    /// use cider::parsing::json_parser;
+    /// let config = json_parser::parse_from_str(r#"{ "actions": ["Build"], "Build": { "manual": { "build": "echo build" } } }"#);
+    ///
+    /// assert!(json_parser::empty_problems(&config).is_empty());
+    /// ```
+    pub fn empty_problems(config: &TopLevelConfiguration) -> Vec<String> {
+        if !config.get_all_actions().is_empty() {
+            return vec![];
+        }
+        let mut problems = vec!["The top level of the configuration has no actions to run".to_string()];
+        for pipeline in config.get_pipelines() {
+            problems.push(format!(
+                "Pipeline: {}{} has no actions to run",
+                pipeline.shared_config.title().unwrap_or_default(),
+                defined_at(pipeline.get_location())
+            ));
+        }
+        problems
+    }
+
+    /// Describes why `source` cannot be used as the `source_directory` configured at `location`, if it cannot be
+    fn check_source_directory(source: &str, location: &str) -> Option<String> {
+        match fs::metadata(source) {
+            Ok(metadata) if metadata.is_dir() => None,
+            Ok(_) => Some(format!(
+                "source_directory \"{}\" of {} is not a directory",
+                source, location
+            )),
+            Err(_) => Some(format!(
+                "source_directory \"{}\" of {} does not exist",
+                source, location
+            )),
+        }
     }
 
     ///Created strictly for testing purposes.
-    pub fn parse_json_string(filename: &str) -> JsonValue {
-        let contents = fs::read_to_string(filename).unwrap();
+    pub fn parse_json_string(path: impl AsRef<Path>) -> JsonValue {
+        let contents = fs::read_to_string(path).unwrap();
         let parsed_data = json::parse(&contents);
         // println!("{:#?}", parsed_data.as_ref().unwrap().clone());
         parsed_data.unwrap()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_env_interpolation() {
+            env::set_var("CIDER_TEST_REGISTRY", "ghcr.io");
+            env::set_var("CIDER_TEST_TAG", "1.65.0");
+            let reference = "${CIDER_TEST_REGISTRY}/rust:${CIDER_TEST_TAG}";
+            assert_eq!(
+                interpolate_env_str(reference, &HashMap::new(), true),
+                "ghcr.io/rust:1.65.0"
+            );
+        }
+
+        #[test]
+        fn test_env_interpolation_missing_variable() {
+            assert_eq!(
+                interpolate_env_str("rust:${CIDER_TEST_MISSING_TAG}", &HashMap::new(), false),
+                "rust:${CIDER_TEST_MISSING_TAG}"
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_env_interpolation_missing_variable_strict() {
+            interpolate_env_str("rust:${CIDER_TEST_MISSING_TAG}", &HashMap::new(), true);
+        }
+
+        #[test]
+        fn test_env_interpolation_in_config() {
+            env::set_var("CIDER_TEST_IMAGE", "rust:1.65.0");
+            let config = parse_from_str(
+                r#"{ "backend": "docker", "image": "${CIDER_TEST_IMAGE}", "actions": [] }"#,
+            );
+            assert_eq!(config.s_config.get_image(), Some("rust:1.65.0".to_string()));
+        }
+
+        #[test]
+        fn test_format_flag_overrides_extension() {
+            let options = ParseOptions {
+                format: Some(ConfigFormat::Json),
+                ..Default::default()
+            };
+            assert_eq!(resolve_format(Path::new("cider_config"), &options), ConfigFormat::Json);
+            assert_eq!(
+                resolve_format(Path::new("cider_config.json"), &ParseOptions::default()),
+                ConfigFormat::Json
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_unknown_format() {
+            resolve_format(Path::new("cider_config"), &ParseOptions::default());
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_yaml_extension_is_not_a_format() {
+            resolve_format(Path::new("cider_config.yaml"), &ParseOptions::default());
+        }
+
+        #[test]
+        fn test_parse_env_file() {
+            let contents = r#"
+# Registry settings
+export REGISTRY=ghcr.io
+TAG = 1.65.0 # The toolchain version
+GREETING="hello # world"
+EMPTY=
+not a variable
+"#;
+            assert_eq!(
+                parse_env_file(contents),
+                vec![
+                    ("REGISTRY".to_string(), "ghcr.io".to_string()),
+                    ("TAG".to_string(), "1.65.0".to_string()),
+                    ("GREETING".to_string(), "hello # world".to_string()),
+                    ("EMPTY".to_string(), "".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_env_file_in_config() {
+            let env_file = env::temp_dir().join(format!("cider_test_{}.env", std::process::id()));
+            fs::write(
+                &env_file,
+                "CIDER_TEST_ENV_FILE_IMAGE=rust:1.65.0\nCIDER_TEST_ENV_FILE_SET=from_file\n",
+            )
+            .unwrap();
+            env::set_var("CIDER_TEST_ENV_FILE_SET", "from_process");
+            let config = parse_from_str(&format!(
+                r#"{{
+                    "env_file": "{}",
+                    "backend": "docker",
+                    "image": "${{CIDER_TEST_ENV_FILE_IMAGE}}",
+                    "title": "${{CIDER_TEST_ENV_FILE_SET}}",
+                    "actions": []
+                }}"#,
+                env_file.to_str().unwrap()
+            ));
+            fs::remove_file(env_file).unwrap();
+            assert_eq!(config.s_config.get_image(), Some("rust:1.65.0".to_string()));
+            assert_eq!(config.s_config.get_title(), Some("from_process".to_string()));
+        }
+
+        #[test]
+        fn test_vars_file() {
+            env::set_var("CIDER_TEST_VARS_REGION", "from the environment");
+            let vars = env::temp_dir().join(format!("cider_test_vars_{}.json", std::process::id()));
+            fs::write(
+                &vars,
+                r#"{ "CIDER_TEST_VARS_IMAGE": "rust", "CIDER_TEST_VARS_VERSION": 1.65, "CIDER_TEST_VARS_REGION": "eu" }"#,
+            )
+            .unwrap();
+            let options = ParseOptions {
+                vars_file: Some(vars.to_str().unwrap().to_string()),
+                strict_env: true,
+                ..Default::default()
+            };
+            let config = parse_from_str_with_options(
+                r#"{
+                    "backend": "docker",
+                    "image": "${CIDER_TEST_VARS_IMAGE}:${CIDER_TEST_VARS_VERSION}",
+                    "title": "${CIDER_TEST_VARS_REGION}",
+                    "actions": ["deploy"],
+                    "deploy": { "manual": { "d": "echo ${CIDER_TEST_VARS_IMAGE}" } }
+                }"#,
+                &options,
+            );
+            fs::remove_file(vars).unwrap();
+            assert_eq!(config.s_config.get_image(), Some("rust:1.65".to_string()));
+            assert_eq!(config.s_config.title(), Some("from the environment"));
+            let step = &config.get_actions()[0].action_config.get_manual()[0];
+            assert_eq!(step.get_script(), "echo ${CIDER_TEST_VARS_IMAGE}");
+            assert!(env::var_os("CIDER_TEST_VARS_IMAGE").is_none());
+        }
+
+        #[test]
+        #[should_panic(expected = "could not be read")]
+        fn test_missing_vars_file() {
+            let options = ParseOptions {
+                vars_file: Some("./does_not_exist.json".to_string()),
+                ..Default::default()
+            };
+            parse_from_str_with_options(r#"{ "actions": [] }"#, &options);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_missing_env_file() {
+            let options = ParseOptions {
+                env_file: Some("./does_not_exist.env".to_string()),
+                ..Default::default()
+            };
+            parse_from_str_with_options(r#"{ "actions": [] }"#, &options);
+        }
+
+        #[test]
+        fn test_absolute_paths_are_not_resolved() {
+            let absolute = env::temp_dir().join("cider_output");
+            let absolute = absolute.to_str().unwrap();
+            assert_eq!(resolve_path(absolute), absolute);
+            assert_eq!(
+                Path::new(&resolve_path("./dist")),
+                current_dir().unwrap().join("dist")
+            );
+        }
+
+        #[test]
+        fn test_conditions_are_inherited() {
+            let config = parse_from_str(
+                r#"{
+                    "conditions": { "linux": "os:linux" },
+                    "pipelines": ["Release"],
+                    "actions": ["Build"],
+                    "Build": { "manual": { "b": "cargo build" } },
+                    "Release": {
+                        "conditions": { "linux": "os:linux", "ci": "env:CI" },
+                        "actions": ["Publish"],
+                        "Publish": {
+                            "conditions": { "tagged": "env:TAG" },
+                            "manual": { "p": "cargo publish" }
+                        }
+                    }
+                }"#,
+            );
+            let linux = Condition::new("linux".to_string(), "os:linux".to_string());
+            let ci = Condition::new("ci".to_string(), "env:CI".to_string());
+            let tagged = Condition::new("tagged".to_string(), "env:TAG".to_string());
+            assert_eq!(config.get_conditions(), &vec![linux.clone()]);
+            let actions = config.get_all_actions();
+            assert_eq!(actions[0].action_config.get_all_conditions(), Some(vec![linux.clone()]));
+            let publish = &actions[1].action_config;
+            assert_eq!(publish.get_inherited_conditions(), &vec![linux.clone(), ci.clone()]);
+            assert_eq!(publish.get_all_conditions(), Some(vec![linux, ci, tagged]));
+        }
+
+        #[test]
+        fn test_overwrite_top_level_keeps_unset_fields() {
+            let dir = env::temp_dir().join(format!("cider_test_overwrite_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let empty = dir.join("empty.json");
+            fs::write(&empty, "{}").unwrap();
+            for path in ["cider_config.json", "docker_test.json"] {
+                let config = new_top_level(path);
+                assert_eq!(overwrite_top_level(config.clone(), &empty), config, "{}", path);
+            }
+            let config = parse_from_str(
+                r#"{
+                    "tags": { "team": "ci" },
+                    "backend": "docker",
+                    "image": "rust",
+                    "conditions": { "linux": "os:linux" },
+                    "pipelines": ["Release"],
+                    "actions": ["Setup"],
+                    "post_run": ["Notify"],
+                    "before_each": { "separator": "echo ----" },
+                    "after_each": { "reset": { "script": "rm -f state", "allowed_failure": true } },
+                    "Setup": {
+                        "tags": { "team": "ci" },
+                        "backend": "bash",
+                        "manual": { "answer": { "script": "read x", "stdin": "yes", "allowed_failure": true } }
+                    },
+                    "Notify": { "conditions": { "ci": { "condition": "env:CI", "mode": "warn" } }, "manual": { "n": "echo" } },
+                    "Release": {
+                        "requires": ["Setup"],
+                        "allowed_failure": true,
+                        "conditions": { "ci": "env:CI" },
+                        "image": "rust:slim",
+                        "actions": ["Publish"],
+                        "Publish": { "step_timeout": 30, "docker_user": "cider", "manual": { "p": "cargo publish" } }
+                    }
+                }"#,
+            );
+            assert_eq!(overwrite_top_level(config.clone(), &empty), config);
+
+            let base = parse_from_str(
+                r#"{
+                    "title": "Base",
+                    "language": "Rust",
+                    "actions": ["Build", "Lint"],
+                    "Build": { "retries": 2, "manual": { "build": "cargo build", "test": "cargo test" } },
+                    "Lint": { "language": "Clippy", "manual": { "lint": "cargo clippy" } }
+                }"#,
+            );
+            let overrides = dir.join("override.json");
+            fs::write(
+                &overrides,
+                r#"{
+                    "language": "Rust 2021",
+                    "Build": { "manual": { "test": "cargo test --release" } }
+                }"#,
+            )
+            .unwrap();
+            let config = overwrite_top_level(base.clone(), &overrides);
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(config.s_config.title(), Some("Base"));
+            assert_eq!(config.s_config.get_language(), "Rust 2021");
+            assert_eq!(config.s_config.get_source(), base.s_config.get_source());
+            assert_eq!(config.get_action_defs(), base.get_action_defs());
+            let build = config.get_action("Build").unwrap();
+            assert_eq!(build.shared_config.get_language(), "Rust 2021");
+            assert_eq!(*build.action_config.get_retries(), 2);
+            let scripts = build
+                .action_config
+                .get_manual()
+                .iter()
+                .map(|step| step.get_script())
+                .collect::<Vec<_>>();
+            assert_eq!(scripts, ["cargo build", "cargo test --release"]);
+            assert_eq!(config.get_action("Lint"), base.get_action("Lint"));
+        }
+
+        #[test]
+        fn test_paths_relative_to_config_file() {
+            let dir = env::temp_dir().join(format!("cider_test_relative_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let filename = dir.join("cider_config.json");
+            fs::write(
+                &filename,
+                r#"{
+                    "output_directory": "./out",
+                    "actions": ["Build"],
+                    "Build": { "source_directory": "./app", "manual": { "build": "make" } }
+                }"#,
+            )
+            .unwrap();
+
+            let config = new_top_level(&filename);
+            assert_eq!(Path::new(config.s_config.get_source()), dir.join("src"));
+            assert_eq!(Path::new(config.s_config.get_output()), dir.join("out"));
+            assert_eq!(
+                Path::new(config.get_actions()[0].shared_config.get_source()),
+                dir.join("app")
+            );
+
+            let options = ParseOptions {
+                paths_relative_to_cwd: true,
+                ..Default::default()
+            };
+            let config = new_top_level_with_options(&filename, &options);
+            fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(
+                Path::new(config.s_config.get_output()),
+                current_dir().unwrap().join("out")
+            );
+            assert_eq!(
+                Path::new(config.get_actions()[0].shared_config.get_source()),
+                current_dir().unwrap().join("app")
+            );
+        }
+
+        #[test]
+        fn test_validate_source_directory() {
+            let config = parse_from_str(r#"{ "source_directory": "./", "actions": [] }"#);
+            assert!(validate_config(&config).is_empty());
+
+            let config = parse_from_str(r#"{ "source_directory": "./Cargo.toml", "actions": [] }"#);
+            assert!(validate_config(&config)[0].ends_with("of the top-level configuration is not a directory"));
+
+            let config = parse_from_str(
+                r#"{
+                    "source_directory": "./",
+                    "actions": ["Build"],
+                    "Build": { "source_directory": "./does_not_exist", "manual": { "build": "echo build" } }
+                }"#,
+            );
+            let problems = validate_config(&config);
+            assert_eq!(problems.len(), 1);
+            let expected = "of action \"Build\" (defined at line 4, column 21) does not exist";
+            assert!(problems[0].ends_with(expected));
+        }
+
+        #[test]
+        fn test_definitions_are_located() {
+            let dir = env::temp_dir().join(format!("cider_test_locations_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("cider_config.json");
+            let contents = r#"{
+    "actions": ["Build"],
+    "pipelines": ["Release"],
+    "post_run": ["Cleanup"],
+    "Build": { "manual": { "build": "echo \"{ [build] }\"" } },
+    "Cleanup": { "manual": { "clean": "echo clean" } },
+    "Release": {
+        "actions": [
+            "Package",
+            { "title": "Publish", "manual": { "publish": "echo publish" } }
+        ],
+        "Package": { "manual": { "package": "echo package" } }
+    }
+}"#;
+            fs::write(&path, contents).unwrap();
+            let config = new_top_level(&path);
+            let overlay = dir.join("overlay.json");
+            fs::write(&overlay, r#"{ "Build": { "retries": 2 } }"#).unwrap();
+            let overwritten = overwrite_top_level(config.clone(), &overlay);
+            fs::remove_dir_all(&dir).unwrap();
+
+            let location = |action: &Action| action.get_location().unwrap().to_string();
+            let file = path.display().to_string();
+            assert_eq!(location(&config.get_actions()[0]), format!("{}:5:5", file));
+            assert_eq!(location(&config.get_post_run()[0]), format!("{}:6:5", file));
+            let release = &config.get_pipelines()[0];
+            assert_eq!(release.get_location().unwrap().to_string(), format!("{}:7:5", file));
+            let actions = release.pipeline_config.get_actions();
+            assert_eq!(location(&actions[0]), format!("{}:12:9", file));
+            assert_eq!(location(&actions[1]), format!("{}:10:13", file));
+            // Layering a file over the configuration keeps where its actions were defined
+            assert_eq!(location(&overwritten.get_actions()[0]), format!("{}:5:5", file));
+            assert_eq!(overwritten.get_pipelines()[0].get_location(), release.get_location());
+        }
+
+        #[test]
+        fn test_empty_problems() {
+            let config = parse_from_str(r#"{ "actions": [] }"#);
+            assert_eq!(
+                empty_problems(&config),
+                vec!["The top level of the configuration has no actions to run"]
+            );
+
+            let config = parse_from_str(
+                r#"{
+                    "actions": [],
+                    "pipelines": ["Release", "Nightly"],
+                    "Release": { "actions": [] },
+                    "Nightly": { "actions": [] }
+                }"#,
+            );
+            assert_eq!(
+                empty_problems(&config),
+                vec![
+                    "The top level of the configuration has no actions to run",
+                    "Pipeline: Release (defined at line 4, column 21) has no actions to run",
+                    "Pipeline: Nightly (defined at line 5, column 21) has no actions to run",
+                ]
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "The action \"Build\" is listed more than once in the actions of Pipeline: Release")]
+        fn test_duplicate_action_names() {
+            parse_from_str(
+                r#"{
+                    "pipelines": ["Release"],
+                    "Release": {
+                        "actions": ["Build", "Test", "Build"],
+                        "Build": { "manual": { "build": "echo build" } },
+                        "Test": { "manual": { "test": "echo test" } }
+                    }
+                }"#,
+            );
+        }
+
+        #[test]
+        fn test_inline_actions() {
+            let config = parse_from_str(
+                r#"{
+                    "pipelines": ["Release"],
+                    "Release": {
+                        "actions": [
+                            "Build",
+                            { "title": "Test", "manual": { "test": "echo test" } },
+                            "Package"
+                        ],
+                        "Build": { "manual": { "build": "echo build" } },
+                        "Package": { "manual": { "package": "echo package" } }
+                    }
+                }"#,
+            );
+            let pipeline = &config.get_pipelines()[0];
+            assert_eq!(pipeline.pipeline_config.get_action_defs(), &["Build", "Test", "Package"]);
+            let actions = pipeline.pipeline_config.get_actions();
+            assert_eq!(
+                actions.iter().map(|action| action.shared_config.title()).collect::<Vec<_>>(),
+                [Some("Build"), Some("Test"), Some("Package")]
+            );
+            assert_eq!(actions[1].action_config.get_manual()[0].get_script(), "echo test");
+            assert_eq!(actions[1].get_pipeline(), Some("Release"));
+        }
+
+        #[test]
+        fn test_wildcard_actions() {
+            let titles = |actions: &[Action]| {
+                actions
+                    .iter()
+                    .map(|action| action.shared_config.title().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            };
+            let document = |extra: &str| {
+                format!(
+                    r#"{{
+                        "actions": ["lint", "*"],
+                        "pipelines": ["Tests"],
+                        "post_run": ["clean"],
+                        "metadata": {{ "team": "ci" }},
+                        "build": {{ "manual": {{ "build": "cargo build" }} }},
+                        "lint": {{ "manual": {{ "lint": "cargo clippy" }} }},
+                        "clean": {{ "manual": {{ "clean": "cargo clean" }} }},
+                        "Tests": {{
+                            "conditions": {{ "ci": "env:CI" }},
+                            "actions": ["test_*", "smoke"],
+                            "test_unit": {{ "manual": {{ "test": "cargo test --lib" }} }},
+                            "smoke": {{ "manual": {{ "smoke": "./smoke.sh" }} }},
+                            "bench": {{ "manual": {{ "bench": "cargo bench" }} }}{}
+                        }}
+                    }}"#,
+                    extra
+                )
+            };
+            let config = parse_from_str(&document(""));
+            assert_eq!(config.get_action_defs(), &["lint", "build"]);
+            assert_eq!(titles(config.get_actions()), ["lint", "build"]);
+            let tests = config.get_pipeline("Tests").unwrap();
+            assert_eq!(titles(tests.pipeline_config.get_actions()), ["test_unit", "smoke"]);
+
+            let added = r#", "test_doc": { "manual": { "test": "cargo test --doc" } }"#;
+            let config = parse_from_str(&document(added));
+            let tests = config.get_pipeline("Tests").unwrap();
+            assert_eq!(
+                titles(tests.pipeline_config.get_actions()),
+                ["test_unit", "test_doc", "smoke"]
+            );
+            assert_eq!(tests.pipeline_config.get_actions()[1].get_pipeline(), Some("Tests"));
+        }
+
+        #[test]
+        fn test_foreign_documents() {
+            let package_json = json::parse(
+                r#"{ "name": "web", "version": "1.0.0", "scripts": { "build": "vite build" } }"#,
+            )
+            .unwrap();
+            assert_eq!(
+                foreign_document_problem(&package_json).unwrap(),
+                "This doesn't look like a cider config, as none of its keys (name, version, scripts) are CIder settings. Check that the right configuration file was given."
+            );
+            assert!(foreign_document_problem(&json::parse("[1, 2]").unwrap()).is_some());
+            assert!(foreign_document_problem(&json::parse("{}").unwrap()).is_none());
+            let config = json::parse(r#"{ "backend": "bash", "Unlisted": {} }"#).unwrap();
+            assert!(foreign_document_problem(&config).is_none());
+        }
+
+        #[test]
+        #[should_panic(expected = "This doesn't look like a cider config")]
+        fn test_foreign_document_is_rejected() {
+            parse_from_str(r#"{ "name": "web", "dependencies": { "vite": "^5.0.0" } }"#);
+        }
+
+        #[test]
+        fn test_lenient_numbers_and_booleans() {
+            let config = parse_from_str(
+                r#"{
+                    "actions": ["Quoted", "Float", "Fraction"],
+                    "Quoted": {
+                        "retries": " 3 ",
+                        "allowed_failure": "TRUE",
+                        "manual": { "build": { "script": "cargo build", "allowed_failure": "false" } }
+                    },
+                    "Float": { "retries": 2.0, "allowed_failure": "false", "manual": { "test": "cargo test" } },
+                    "Fraction": { "retries": "1.5", "manual": { "doc": "cargo doc" } }
+                }"#,
+            );
+            let actions = config.get_actions();
+            assert_eq!(*actions[0].action_config.get_retries(), 3);
+            assert!(*actions[0].action_config.get_allowed_failure());
+            assert_eq!(actions[0].action_config.get_manual()[0].get_allowed_failure(), Some(false));
+            assert_eq!(*actions[1].action_config.get_retries(), 2);
+            assert!(!*actions[1].action_config.get_allowed_failure());
+            assert_eq!(*actions[2].action_config.get_retries(), 1);
+        }
+
+        #[test]
+        #[should_panic(expected = "There was no valid value for retries in the configuration")]
+        fn test_non_numeric_retries() {
+            parse_from_str(
+                r#"{
+                    "actions": ["Build"],
+                    "Build": { "retries": "three", "manual": { "build": "cargo build" } }
+                }"#,
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "There was no valid value for allowed_failure in the configuration")]
+        fn test_non_boolean_allowed_failure() {
+            parse_from_str(
+                r#"{
+                    "actions": ["Build"],
+                    "Build": { "allowed_failure": "maybe", "manual": { "build": "cargo build" } }
+                }"#,
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "Actions defined inline must have a title")]
+        fn test_inline_action_without_title() {
+            parse_from_str(
+                r#"{
+                    "pipelines": ["Release"],
+                    "Release": { "actions": [{ "manual": { "test": "echo test" } }] }
+                }"#,
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "There was no valid value for docker_workdir in the configuration, it must be an absolute path")]
+        fn test_relative_docker_workdir() {
+            parse_from_str(
+                r#"{
+                    "backend": "docker",
+                    "actions": ["Build"],
+                    "Build": { "docker_workdir": "app", "manual": { "build": "cargo build" } }
+                }"#,
+            );
+        }
+
+        #[test]
+        fn test_redact_patterns_add_to_those_above() {
+            let config = parse_from_str(
+                r#"{
+                    "redact": ["token_\\w+"],
+                    "actions": ["Build"],
+                    "pipelines": ["Release"],
+                    "Build": { "redact": ["\\d{4}-\\d{4}", "token_\\w+"], "manual": { "build": "cargo build" } },
+                    "Release": { "actions": ["Package"], "Package": { "manual": { "zip": "zip" } } }
+                }"#,
+            );
+            let build = &config.get_actions()[0];
+            assert_eq!(build.shared_config.get_redact(), [r"token_\w+", r"\d{4}-\d{4}"]);
+            let package = &config.get_pipelines()[0].pipeline_config.get_actions()[0];
+            assert_eq!(package.shared_config.get_redact(), [r"token_\w+"]);
+        }
+
+        #[test]
+        #[should_panic(expected = "There was no valid value for redact in the configuration, [a-z is not a valid pattern")]
+        fn test_invalid_redact_pattern() {
+            parse_from_str(r#"{ "redact": ["[a-z"], "actions": [] }"#);
+        }
+
+        #[test]
+        #[should_panic(expected = "There was no valid value for pull_policy in the configuration, it must be always, if-not-present or never")]
+        fn test_invalid_pull_policy() {
+            parse_from_str(
+                r#"{
+                    "backend": "docker",
+                    "actions": ["Build"],
+                    "Build": { "pull_policy": "sometimes", "manual": { "build": "cargo build" } }
+                }"#,
+            );
+        }
+
+        #[test]
+        fn test_backend_per_platform() {
+            let config = parse_from_str(
+                r#"{
+                    "backend": { "windows": "batch", "unix": "bash" },
+                    "actions": ["Build", "Package"],
+                    "Build": { "manual": { "build": "cargo build" } },
+                    "Package": { "backend": { "linux": "docker", "default": "bash" }, "manual": { "package": "cargo package" } }
+                }"#,
+            );
+            let expected = if cfg!(windows) { "batch" } else { "bash" };
+            assert_eq!(config.s_config.get_backend(), expected);
+            assert_eq!(config.get_actions()[0].shared_config.get_backend(), expected);
+            let expected = if cfg!(target_os = "linux") { "docker" } else { "bash" };
+            assert_eq!(config.get_actions()[1].shared_config.get_backend(), expected);
+        }
+
+        #[test]
+        fn test_platform_backend_resolution() {
+            let backends = json::parse(r#"{ "windows": "batch", "unix": "bash", "macos": "docker" }"#).unwrap();
+            assert_eq!(resolve_platform_backend(&backends, "windows", "windows").as_deref(), Some("batch"));
+            assert_eq!(resolve_platform_backend(&backends, "linux", "unix").as_deref(), Some("bash"));
+            assert_eq!(resolve_platform_backend(&backends, "macos", "unix").as_deref(), Some("docker"));
+            let backends = json::parse(r#"{ "windows": "batch" }"#).unwrap();
+            assert_eq!(resolve_platform_backend(&backends, "linux", "unix"), None);
+        }
+
+        #[test]
+        fn test_absolute_container_paths() {
+            assert!(is_absolute_container_path("/cider/app"));
+            assert!(is_absolute_container_path("C:\\cider\\app"));
+            assert!(is_absolute_container_path("c:/cider"));
+            assert!(!is_absolute_container_path("cider/app"));
+            assert!(!is_absolute_container_path("./app"));
+            assert!(!is_absolute_container_path("C:app"));
+        }
+
+        #[test]
+        fn test_duplicate_names_found() {
+            assert_eq!(find_duplicates(["Build", "Test", "Build", "Lint", "Test", "Build"]), ["Build", "Test"]);
+
+            let build = Action::builder("Build").step("build", "echo build").build();
+            let mut config = parse_from_str(r#"{ "source_directory": "./", "actions": [] }"#);
+            config.set_actions(vec![build.clone(), build]);
+            assert_eq!(
+                validate_config(&config),
+                ["The action \"Build\" is listed more than once in the actions of the top level of the configuration"]
+            );
+        }
+
+        #[test]
+        fn test_unknown_keys_found() {
+            let json = json::parse(
+                r#"{ "sourc_directory": "./", "backend": "bash", "Build": {}, "manual": {} }"#,
+            )
+            .unwrap();
+            let known_keys = [SHARED_KEYS, ACTION_KEYS].concat();
+            assert_eq!(
+                find_unknown_keys(&json, &known_keys, &["Build".to_string()]),
+                vec!["sourc_directory".to_string()]
+            );
+        }
+
+        #[test]
+        fn test_unlisted_definitions_are_unknown() {
+            let json = json::parse(r#"{ "actions": ["Build"], "Build": {}, "Lint": {} }"#).unwrap();
+            assert_eq!(
+                find_unknown_keys(&json, PIPELINE_KEYS, &["Build".to_string()]),
+                vec!["Lint".to_string()]
+            );
+        }
+
+        #[test]
+        fn test_unknown_step_keys() {
+            let json = json::parse(r#"{ "script": "echo hi", "allowed_faliure": true }"#).unwrap();
+            assert_eq!(
+                find_unknown_keys(&json, STEP_KEYS, &[]),
+                vec!["allowed_faliure".to_string()]
+            );
+        }
+
+        #[test]
+        fn test_ignored_image() {
+            let config = parse_from_str(
+                r#"{
+                    "backend": "docker",
+                    "image": "rust",
+                    "actions": ["Lint", "Build"],
+                    "Lint": { "backend": "bash", "image": "rust:slim", "manual": { "l": "cargo clippy" } },
+                    "Build": { "image": "rust:slim", "manual": { "b": "cargo build" } }
+                }"#,
+            );
+            let lint = &config.get_actions()[0];
+            assert_eq!(lint.shared_config.image(), None);
+            let json = json::parse(r#"{ "backend": "bash", "image": "rust:slim" }"#).unwrap();
+            assert_eq!(
+                ignored_image(&json, &lint.shared_config, "Action: Lint").unwrap(),
+                "The image \"rust:slim\" set in Action: Lint will be ignored, as its backend is bash rather than docker. Set \"backend\": \"docker\" to run its steps within the image, or remove the image."
+            );
+            let build = &config.get_actions()[1];
+            assert_eq!(build.shared_config.image(), Some("rust:slim"));
+            assert_eq!(ignored_image(&json, &build.shared_config, "Action: Build"), None);
+            assert_eq!(ignored_image(&json::object! {}, &lint.shared_config, "Action: Lint"), None);
+        }
+    }
 }
@@ -0,0 +1,521 @@
+use std::cell::Cell;
+use std::slice;
+
+/// What every match of a [`RedactPattern`] is replaced with
+pub const MASK: &str = "***";
+
+/// How many steps matching a pattern may take for each character of a line before the matching gives up
+///
+/// Patterns such as `(a+)+$` can otherwise take exponentially long to find that a line does not match, and trying a
+/// match from every start of a long line would otherwise take quadratically long.
+const STEPS_PER_CHAR: usize = 10_000;
+
+/// A pattern from the `redact` setting of a configuration, matched against the output of steps
+///
+/// Supports the common subset of regular expressions: literal characters, `.`, character classes such as `[a-z0-9_]` and
+/// `[^@]`, the classes `\d`, `\w` and `\s` along with their negations `\D`, `\W` and `\S`, the word boundary `\b`, the
+/// anchors `^` and `$`, groups with `(...)` or `(?:...)`, alternation with `|`, and the quantifiers `*`, `+`, `?`,
+/// `{n}`, `{n,}` and `{n,m}`, any of which can be followed by `?` to match as few times as possible. Patterns are
+/// case-sensitive and are matched against a single line at a time, so `^` and `$` match the start and end of a line.
+///
+/// Matching a line gives up after [`STEPS_PER_CHAR`] steps for each of its characters. The line is then treated as
+/// matching from where the matching gave up to its end, so output is hidden rather than shown when in doubt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactPattern {
+    pattern: String,
+    root: Node,
+}
+
+/// A single piece of a [`RedactPattern`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Matches one character within the class
+    Class(Class),
+    /// Matches the start of a line without consuming anything
+    Start,
+    /// Matches the end of a line without consuming anything
+    End,
+    /// Matches between a word character and a character that is not one, without consuming anything
+    WordBoundary,
+    /// Matches the first of the alternatives that lets the rest of the pattern match
+    Group(Vec<Vec<Node>>),
+    /// Matches `node` at least `min` and at most `max` times, as many times as possible if `greedy` is set
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+/// A set of characters, given as inclusive ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    /// The class of only `c`
+    fn of(c: char) -> Self {
+        Class {
+            ranges: vec![(c, c)],
+            negated: false,
+        }
+    }
+
+    /// The class matched by `\d`, `\w` or `\s`, or by their negations if `negated` is set
+    fn named(name: char, negated: bool) -> Self {
+        let ranges = match name {
+            'd' => vec![('0', '9')],
+            'w' => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+            _ => vec![(' ', ' '), ('\t', '\r')],
+        };
+        Class { ranges, negated }
+    }
+
+    /// Returns whether `c` is within the class
+    fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(low, high)| low <= c && c <= high) != self.negated
+    }
+}
+
+impl RedactPattern {
+    /// Parses `pattern`, returning why it could not be parsed if it is not valid
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::redaction::RedactPattern;
+    ///
+    /// let pattern = RedactPattern::parse(r"ghp_\w{8,}").unwrap();
+    /// assert!(pattern.is_match("token=ghp_abcdef123456"));
+    /// assert!(!pattern.is_match("token=ghp_abc"));
+    /// assert!(RedactPattern::parse("(unclosed").is_err());
+    /// ```
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let alternatives = parser.alternatives()?;
+        if parser.pos < parser.chars.len() {
+            return Err("a group is closed without being opened".to_string());
+        }
+        Ok(RedactPattern {
+            pattern: pattern.to_string(),
+            root: Node::Group(alternatives),
+        })
+    }
+
+    /// Returns the pattern as it was written
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether the pattern matches anything within `line`
+    pub fn is_match(&self, line: &str) -> bool {
+        let chars = line.chars().collect::<Vec<char>>();
+        let steps = budget(&chars);
+        self.find(&chars, 0, &steps).is_some()
+    }
+
+    /// Returns `line` with every match of the pattern replaced with [`MASK`]
+    fn replace(&self, line: &str) -> String {
+        let chars = line.chars().collect::<Vec<char>>();
+        let mut redacted = String::with_capacity(line.len());
+        let mut pos = 0;
+        let steps = budget(&chars);
+        while let Some((start, end)) = self.find(&chars, pos, &steps) {
+            redacted.extend(&chars[pos..start]);
+            redacted.push_str(MASK);
+            pos = end;
+        }
+        redacted.extend(&chars[pos..]);
+        redacted
+    }
+
+    /// Returns the start and end of the first match within `line` that starts at or after `from`, ignoring matches
+    /// that are empty
+    ///
+    /// If `steps` runs out, the line is treated as matching from the start being tried to its end.
+    fn find(&self, line: &[char], from: usize, steps: &Cell<usize>) -> Option<(usize, usize)> {
+        (from..line.len()).find_map(|start| {
+            let mut found = None;
+            matches(slice::from_ref(&self.root), line, start, steps, &mut |end| {
+                if end > start {
+                    found = Some(end);
+                }
+                found.is_some()
+            });
+            if steps.get() == 0 {
+                found = found.or(Some(line.len()));
+            }
+            found.map(|end| (start, end))
+        })
+    }
+}
+
+/// Returns the number of steps that matching a pattern against `line` may take
+fn budget(line: &[char]) -> Cell<usize> {
+    Cell::new((line.len() + 1).saturating_mul(STEPS_PER_CHAR))
+}
+
+/// Replaces every match of `patterns` within `text` with [`MASK`], applying the patterns in order to each line
+///
+/// # Examples:
+/// ```
+/// use cider::redaction::{redact, RedactPattern};
+///
+/// let patterns = [RedactPattern::parse(r"[\w.]+@[\w.]+").unwrap()];
+/// assert_eq!(redact("sent to ci@example.com\ndone", &patterns), "sent to ***\ndone");
+/// ```
+pub fn redact(text: &str, patterns: &[RedactPattern]) -> String {
+    if patterns.is_empty() {
+        return text.to_string();
+    }
+    text.split('\n')
+        .map(|line| {
+            patterns
+                .iter()
+                .fold(line.to_string(), |line, pattern| pattern.replace(&line))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns whether `nodes` match `line` from `pos`, in which case `next` is called with where the match ends and
+/// decides whether it is accepted. Otherwise, the next way the nodes could match is tried.
+///
+/// Each call takes one of `steps`, and once they run out nothing matches.
+fn matches(
+    nodes: &[Node],
+    line: &[char],
+    pos: usize,
+    steps: &Cell<usize>,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    if steps.get() == 0 {
+        return false;
+    }
+    steps.set(steps.get() - 1);
+    let Some((node, rest)) = nodes.split_first() else {
+        return next(pos);
+    };
+    match node {
+        Node::Class(class) => {
+            line.get(pos).is_some_and(|&c| class.matches(c))
+                && matches(rest, line, pos + 1, steps, next)
+        }
+        Node::Start => pos == 0 && matches(rest, line, pos, steps, next),
+        Node::End => pos == line.len() && matches(rest, line, pos, steps, next),
+        Node::WordBoundary => {
+            let is_word = |c: Option<&char>| c.is_some_and(|&c| c.is_alphanumeric() || c == '_');
+            let before = pos.checked_sub(1).and_then(|before| line.get(before));
+            is_word(before) != is_word(line.get(pos))
+                && matches(rest, line, pos, steps, next)
+        }
+        Node::Group(alternatives) => alternatives.iter().any(|alternative| {
+            matches(alternative, line, pos, steps, &mut |end| {
+                matches(rest, line, end, steps, next)
+            })
+        }),
+        Node::Repeat {
+            node: inner,
+            min,
+            max,
+            greedy,
+        } => {
+            if let Node::Class(class) = &**inner {
+                // Repeating a single character needs no backtracking within the repetition, so the
+                // counts it could match are tried in turn rather than recursing once per character
+                let most = line[pos.min(line.len())..]
+                    .iter()
+                    .take(max.unwrap_or(usize::MAX))
+                    .take_while(|&&c| class.matches(c))
+                    .count();
+                let mut attempt = |count: usize| matches(rest, line, pos + count, steps, next);
+                *min <= most
+                    && if *greedy {
+                        (*min..=most).rev().any(&mut attempt)
+                    } else {
+                        (*min..=most).any(&mut attempt)
+                    }
+            } else {
+                repeat(node, 0, rest, line, pos, steps, next)
+            }
+        }
+    }
+}
+
+/// Matches the [`Node::Repeat`] `node`, which has already matched `count` times, followed by `rest`
+fn repeat(
+    node: &Node,
+    count: usize,
+    rest: &[Node],
+    line: &[char],
+    pos: usize,
+    steps: &Cell<usize>,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    let Node::Repeat {
+        node: inner,
+        min,
+        max,
+        greedy,
+    } = node
+    else {
+        unreachable!("Only repetitions are repeated");
+    };
+    let again = |next: &mut dyn FnMut(usize) -> bool| {
+        max.is_none_or(|max| count < max)
+            && matches(slice::from_ref(&**inner), line, pos, steps, &mut |end| {
+                // An empty match could repeat forever, so it only counts towards the minimum
+                (end != pos || count < *min)
+                    && repeat(node, count + 1, rest, line, end, steps, next)
+            })
+    };
+    let stop = |next: &mut dyn FnMut(usize) -> bool| {
+        count >= *min && matches(rest, line, pos, steps, next)
+    };
+    // A greedy repetition tries to match once more before it tries to stop, and a lazy one the
+    // other way around
+    let order = if *greedy { [true, false] } else { [false, true] };
+    order
+        .into_iter()
+        .any(|more| if more { again(next) } else { stop(next) })
+}
+
+/// Parses a [`RedactPattern`] from its characters
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    /// Consumes the next character if it is `c`
+    fn eat(&mut self, c: char) -> bool {
+        let next = self.chars.get(self.pos) == Some(&c);
+        if next {
+            self.pos += 1;
+        }
+        next
+    }
+
+    /// Parses alternatives separated by `|`, up to the end of the pattern or the `)` that closes the current group
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.eat('|') {
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    /// Parses nodes up to the next `|`, the `)` that closes the current group or the end of the pattern
+    fn sequence(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = vec![];
+        while let Some(&c) = self.chars.get(self.pos) {
+            if c == '|' || c == ')' {
+                break;
+            }
+            self.pos += 1;
+            let node = match c {
+                '(' => {
+                    if self.chars[self.pos..].starts_with(&['?', ':']) {
+                        self.pos += 2;
+                    }
+                    let alternatives = self.alternatives()?;
+                    if !self.eat(')') {
+                        return Err("a group is not closed".to_string());
+                    }
+                    Node::Group(alternatives)
+                }
+                '[' => Node::Class(self.class()?),
+                '.' => Node::Class(Class {
+                    ranges: vec![('\n', '\n')],
+                    negated: true,
+                }),
+                '^' => Node::Start,
+                '$' => Node::End,
+                '\\' => match self.escape()? {
+                    Some(class) => Node::Class(class),
+                    None => Node::WordBoundary,
+                },
+                '*' | '+' | '?' => return Err(format!("{} does not follow anything to repeat", c)),
+                c => Node::Class(Class::of(c)),
+            };
+            nodes.push(self.quantified(node)?);
+        }
+        Ok(nodes)
+    }
+
+    /// Wraps `node` in a [`Node::Repeat`] if a quantifier follows it
+    fn quantified(&mut self, node: Node) -> Result<Node, String> {
+        let (min, max) = match self.chars.get(self.pos) {
+            Some('{') => match self.counted() {
+                Some(counts) => counts,
+                None => return Ok(node),
+            },
+            Some(&c @ ('*' | '+' | '?')) => {
+                self.pos += 1;
+                match c {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(node),
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(format!(
+                "{{{},{}}} repeats fewer times at most than at least",
+                min,
+                max.unwrap()
+            ));
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// Parses a `{n}`, `{n,}` or `{n,m}` quantifier, consuming it, or returns None without consuming anything if the
+    /// `{` does not start one, in which case it is a literal character
+    fn counted(&mut self) -> Option<(usize, Option<usize>)> {
+        let rest = self.chars[self.pos + 1..].iter().collect::<String>();
+        let inner = &rest[..rest.find('}')?];
+        let (min, max) = match inner.split_once(',') {
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+            None => (inner.parse().ok()?, Some(inner.parse().ok()?)),
+        };
+        self.pos += inner.chars().count() + 2;
+        Some((min, max))
+    }
+
+    /// Parses the escape after a `\`, returning the class it matches, or None for the word boundary `\b`
+    fn escape(&mut self) -> Result<Option<Class>, String> {
+        let Some(&c) = self.chars.get(self.pos) else {
+            return Err("the pattern ends with a lone \\".to_string());
+        };
+        self.pos += 1;
+        Ok(Some(match c {
+            'b' => return Ok(None),
+            'd' | 'w' | 's' => Class::named(c, false),
+            'D' | 'W' | 'S' => Class::named(c.to_ascii_lowercase(), true),
+            'n' => Class::of('\n'),
+            't' => Class::of('\t'),
+            'r' => Class::of('\r'),
+            c if c.is_alphanumeric() => return Err(format!("\\{} is not a supported escape", c)),
+            c => Class::of(c),
+        }))
+    }
+
+    /// Parses a character class after its `[`, up to and including its `]`
+    fn class(&mut self) -> Result<Class, String> {
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut first = true;
+        loop {
+            let Some(&c) = self.chars.get(self.pos) else {
+                return Err("a character class is not closed".to_string());
+            };
+            self.pos += 1;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let low = if c == '\\' {
+                match self.escape()? {
+                    Some(class) if class.ranges.len() == 1 && !class.negated => class.ranges[0].0,
+                    Some(class) if !class.negated => {
+                        ranges.extend(class.ranges);
+                        continue;
+                    }
+                    _ => {
+                        let reason = "\\b, \\D, \\W and \\S cannot be used within a class";
+                        return Err(reason.to_string());
+                    }
+                }
+            } else {
+                c
+            };
+            let high = match self.chars[self.pos..] {
+                ['-', high, ..] if high != ']' => {
+                    self.pos += 2;
+                    high
+                }
+                _ => low,
+            };
+            if high < low {
+                return Err(format!("the range {}-{} is out of order", low, high));
+            }
+            ranges.push((low, high));
+        }
+        Ok(Class { ranges, negated })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<RedactPattern> {
+        patterns.iter().map(|pattern| RedactPattern::parse(pattern).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_redact_replaces_every_match() {
+        let patterns = patterns(&[r"gh[ps]_[A-Za-z0-9]{6,}", r"[\w.+-]+@[\w-]+(\.[\w-]+)+"]);
+        let output =
+            "pushing with ghp_a1B2c3D4e5\nnotify dev+ci@example.co.uk and ops@example.com\nghp_no";
+        assert_eq!(
+            redact(output, &patterns),
+            "pushing with ***\nnotify *** and ***\nghp_no"
+        );
+    }
+
+    #[test]
+    fn test_pattern_syntax() {
+        let matched =
+            |pattern: &str, line: &str| RedactPattern::parse(pattern).unwrap().is_match(line);
+        assert!(matched(r"^\d+$", "12345"));
+        assert!(!matched(r"^\d+$", "123a"));
+        assert!(matched(r"\bkey\b", "the key is"));
+        assert!(!matched(r"\bkey\b", "monkeys"));
+        assert!(matched("colou?r", "color"));
+        assert!(matched("(?:cat|dog)s", "dogs"));
+        assert!(matched("[^ ]{3}", "abc"));
+        assert!(matched(r"a\.b", "a.b"));
+        assert!(!matched(r"a\.b", "axb"));
+        assert!(matched("a{2}", "caab"));
+        assert!(matched("a{", "a{"));
+        assert_eq!(redact("<a><b>", &patterns(&["<.+?>"])), "******");
+        assert_eq!(redact("<a><b>", &patterns(&["<.+>"])), "***");
+        assert_eq!(redact("abab-ab", &patterns(&["(ab)+"])), "***-***");
+    }
+
+    #[test]
+    fn test_pathological_pattern_gives_up() {
+        let patterns = patterns(&["(a+)+$", "(x+x+)+y"]);
+        let line = format!("ok {}b", "a".repeat(40));
+        let started = std::time::Instant::now();
+        assert_eq!(redact(&line, &patterns[..1]), "ok ***");
+        assert!(patterns[1].is_match(&"x".repeat(40)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(redact("abab", &patterns[..1]), "abab");
+
+        // Ordinary patterns do not give up on long lines
+        let email = RedactPattern::parse(r"[\w.+-]+@[\w-]+(\.[\w-]+)+").unwrap();
+        assert!(!email.is_match(&"a".repeat(2_000)));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in ["(a", "a)", "[a-", "*a", r"\q", "[z-a]", "a{3,1}", "x\\"] {
+            assert!(RedactPattern::parse(pattern).is_err(), "{} should not parse", pattern);
+        }
+    }
+}
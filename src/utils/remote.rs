@@ -0,0 +1,147 @@
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// The environment variable holding a token that is sent as a bearer token when fetching a configuration
+pub const TOKEN_VAR: &str = "CIDER_CONFIG_TOKEN";
+
+/// The environment variable holding the `user:password` that is sent with basic authentication when fetching a
+/// configuration
+pub const BASIC_AUTH_VAR: &str = "CIDER_CONFIG_BASIC_AUTH";
+
+/// Fetches the contents of the configuration at `url` over HTTP or HTTPS
+///
+/// The configuration is fetched with `curl`, following redirects. If [`TOKEN_VAR`] is set, its value is sent in an
+/// `Authorization: Bearer` header, and if [`BASIC_AUTH_VAR`] is set, its value is sent with basic authentication. The
+/// credentials are handed to `curl` through its standard input, so they never appear within its arguments.
+///
+/// Returns an error if the URL is not an HTTP or HTTPS URL, if the URL or either credential holds a control character
+/// such as a newline, if `curl` could not be run, or if the configuration could not be fetched, such as when the
+/// server cannot be reached or responds with an error status.
+pub fn fetch_config(url: &str) -> io::Result<String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not an http:// or https:// URL", url),
+        ));
+    }
+    let config = curl_config(url, env::var(TOKEN_VAR).ok(), env::var(BASIC_AUTH_VAR).ok())?;
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("curl could not be run to fetch {}: {}", url, err),
+            )
+        })?;
+    child
+        .stdin
+        .take()
+        .expect("The standard input of curl was not piped.")
+        .write_all(config.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "The configuration could not be fetched from {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("The configuration fetched from {} is not valid UTF-8", url),
+        )
+    })
+}
+
+/// Returns the `curl` config file that fetches `url`, sending `token` as a bearer token and `basic_auth` with basic
+/// authentication if they are given
+///
+/// Returns an error if any of them holds a control character, which could otherwise end the line it is written on and
+/// add settings of its own to the config.
+fn curl_config(
+    url: &str,
+    token: Option<String>,
+    basic_auth: Option<String>,
+) -> io::Result<String> {
+    let mut config = format!("url = {}\n", quote(url, "The URL")?);
+    if let Some(token) = token {
+        let header = format!("Authorization: Bearer {}", token);
+        config += &format!("header = {}\n", quote(&header, TOKEN_VAR)?);
+    }
+    if let Some(basic_auth) = basic_auth {
+        config += &format!("user = {}\n", quote(&basic_auth, BASIC_AUTH_VAR)?);
+    }
+    Ok(config)
+}
+
+/// Quotes `value` for a `curl` config file, returning an error naming `name` if it holds a control character
+///
+/// The error leaves out `value` itself, as it may be a credential.
+fn quote(value: &str, name: &str) -> io::Result<String> {
+    if value.chars().any(char::is_control) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} holds a control character, such as a newline, and cannot be passed to curl", name),
+        ));
+    }
+    Ok(format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_config() {
+        assert_eq!(
+            curl_config("https://example.com/cider.json", None, None).unwrap(),
+            "url = \"https://example.com/cider.json\"\n"
+        );
+        assert_eq!(
+            curl_config(
+                "https://example.com/cider.json",
+                Some("abc\"123".to_string()),
+                Some("cider:p\\ss".to_string())
+            )
+            .unwrap(),
+            concat!(
+                "url = \"https://example.com/cider.json\"\n",
+                "header = \"Authorization: Bearer abc\\\"123\"\n",
+                "user = \"cider:p\\\\ss\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_curl_config_rejects_control_characters() {
+        let err = curl_config(
+            "https://example.com/cider.json",
+            Some("abc\nurl = \"https://attacker.example\"".to_string()),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains(TOKEN_VAR));
+        assert!(!err.to_string().contains("attacker"));
+
+        let basic_auth = Some("cider:p\r\nss".to_string());
+        let err = curl_config("https://example.com/cider.json", None, basic_auth).unwrap_err();
+        assert!(err.to_string().contains(BASIC_AUTH_VAR));
+    }
+
+    #[test]
+    fn test_fetch_config_errors() {
+        let err = fetch_config("ftp://example.com/cider.json").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // Nothing listens on the discard port of the loopback address, so the connection is refused straight away
+        let err = fetch_config("http://127.0.0.1:9/cider.json").unwrap_err();
+        assert!(err.to_string().contains("http://127.0.0.1:9/cider.json"));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::utils::events::{Event, EventKind, EventSender};
+use crate::utils::results::RunReport;
+use std::fmt;
+use std::sync::Arc;
+
+/// Receives the results of a run as it progresses, such as to send them to a dashboard or a database
+///
+/// Every method does nothing by default, so a reporter only implements the ones it needs. The methods are called from
+/// whichever thread runs the action the event is about, so a reporter that keeps state should lock it.
+///
+/// # Examples:
+/// ```
+/// use cider::events::{Event, EventKind};
+/// use cider::reporters::{Reporter, Reporters};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Debug, Default)]
+/// struct StartedActions(Mutex<Vec<String>>);
+///
+/// impl Reporter for StartedActions {
+///     fn on_action_start(&self, event: &Event) {
+///         self.0.lock().unwrap().push(event.action.clone().unwrap_or_default());
+///     }
+/// }
+///
+/// let started = Arc::new(StartedActions::default());
+/// let mut reporters = Reporters::default();
+/// reporters.attach(started.clone());
+/// reporters.event(Event::new(EventKind::ActionStarted, None, Some("Build".to_string()), None));
+///
+/// assert_eq!(*started.0.lock().unwrap(), ["Build"]);
+/// ```
+pub trait Reporter: fmt::Debug + Send + Sync {
+    /// Called with every [`Event`] of the run, before the method specific to the event is called
+    fn on_event(&self, _event: &Event) {}
+
+    /// Called when an action starts running
+    fn on_action_start(&self, _event: &Event) {}
+
+    /// Called when a step of an action finishes running, whether or not it succeeded
+    fn on_step_finish(&self, _event: &Event) {}
+
+    /// Called when an action finishes running, or is skipped
+    fn on_action_finish(&self, _event: &Event) {}
+
+    /// Called with the results of every action once [`crate::executor::exec_config`] has run them all, before any
+    /// `post_run` action runs. It is not called if the run could not be completed, such as when it was cancelled.
+    fn on_run_finish(&self, _report: &RunReport) {}
+}
+
+/// Sends every [`Event`] it is given through the channel, such as to the writer behind `--events`
+impl Reporter for EventSender {
+    fn on_event(&self, event: &Event) {
+        self.send(event.clone());
+    }
+}
+
+/// The [`Reporter`]s attached to a run, each of which is called in the order it was attached
+#[derive(Debug, Clone, Default)]
+pub struct Reporters(Vec<Arc<dyn Reporter>>);
+
+impl Reporters {
+    /// Attaches `reporter`, so that it is called after every reporter attached before it
+    pub fn attach(&mut self, reporter: Arc<dyn Reporter>) {
+        self.0.push(reporter);
+    }
+
+    /// Returns whether no [`Reporter`] is attached
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Calls every [`Reporter`] with `event`, first through [`Reporter::on_event`] and then through the method specific
+    /// to its kind, if there is one
+    pub fn event(&self, event: Event) {
+        for reporter in &self.0 {
+            reporter.on_event(&event);
+            match event.kind {
+                EventKind::ActionStarted => reporter.on_action_start(&event),
+                EventKind::StepFinished { .. } => reporter.on_step_finish(&event),
+                EventKind::ActionFinished { .. } => reporter.on_action_finish(&event),
+                _ => {}
+            }
+        }
+    }
+
+    /// Calls every [`Reporter`] with the results of a run that has finished
+    pub fn run_finished(&self, report: &RunReport) {
+        for reporter in &self.0 {
+            reporter.on_run_finish(report);
+        }
+    }
+}
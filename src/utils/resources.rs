@@ -0,0 +1,123 @@
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+
+/// The resources a step used while it ran, including every process it started and waited for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The most memory the step held at once, in bytes
+    pub peak_memory_bytes: u64,
+    /// The CPU time the step spent, both running its own code and within the kernel on its behalf
+    pub cpu_time: Duration,
+}
+
+/// Returns whether the resources of steps can be measured on the current platform, see [`wait`]
+pub fn supported() -> bool {
+    cfg!(unix)
+}
+
+/// Waits for `child` to exit, blocking until it does if `block` is set, returning its exit status along with the
+/// resources it used
+///
+/// Returns None if `block` is not set and `child` has not exited yet. On unix, the resources are measured with
+/// `wait4`, which reaps the process in place of [`Child::wait`], so `child` must not be waited on again. Elsewhere, the
+/// resources cannot be measured yet, so the process is waited on as usual and no [`ResourceUsage`] is returned.
+pub fn wait(
+    child: &mut Child,
+    block: bool,
+) -> io::Result<Option<(ExitStatus, Option<ResourceUsage>)>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        let pid = child.id() as libc::pid_t;
+        let options = if block { 0 } else { libc::WNOHANG };
+        let mut status = 0;
+        loop {
+            // SAFETY: rusage is plain data, for which all zeroes is a valid value
+            let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+            // SAFETY: both pointers are valid for the duration of the call, and the process is a child of CIder
+            let reaped = unsafe { libc::wait4(pid, &mut status, options, &mut usage) };
+            match reaped {
+                0 => return Ok(None),
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+                _ => return Ok(Some((ExitStatus::from_raw(status), Some(usage_of(&usage))))),
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let status = if block {
+            Some(child.wait()?)
+        } else {
+            child.try_wait()?
+        };
+        Ok(status.map(|status| (status, None)))
+    }
+}
+
+/// Converts the `rusage` of a process into a [`ResourceUsage`]
+#[cfg(unix)]
+fn usage_of(usage: &libc::rusage) -> ResourceUsage {
+    let time = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec.max(0) as u64)
+            + Duration::from_micros(time.tv_usec.max(0) as u64)
+    };
+    // Linux and the BSDs report the peak resident set size in kilobytes, while macOS reports it in bytes
+    let peak = usage.ru_maxrss.max(0) as u64;
+    ResourceUsage {
+        peak_memory_bytes: if cfg!(target_os = "macos") { peak } else { peak * 1024 },
+        cpu_time: time(usage.ru_utime) + time(usage.ru_stime),
+    }
+}
+
+/// Formats a number of bytes for people to read, such as `1.5 MiB`
+///
+/// # Examples:
+/// ```
+/// use cider::resources::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 B");
+/// assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MiB");
+/// ```
+pub fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in ["KiB", "MiB", "GiB", "TiB"] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_wait_measures_resources() {
+        let mut child = Command::new("sh")
+            .args(["-c", "i=0; while [ $i -lt 20000 ]; do i=$((i + 1)); done; exit 3"])
+            .spawn()
+            .unwrap();
+        let (status, usage) = wait(&mut child, true).unwrap().unwrap();
+        let usage = usage.unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(usage.peak_memory_bytes > 0);
+        assert!(usage.cpu_time > Duration::ZERO);
+        assert!(supported());
+    }
+}
@@ -0,0 +1,917 @@
+use crate::utils::config::Action;
+use crate::utils::resources::{format_bytes, ResourceUsage};
+use chrono::{SecondsFormat, Utc};
+use json::JsonValue;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Describes whether something run by CIder succeeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Everything ran successfully
+    Success,
+    /// Something failed, but it was allowed to fail
+    AllowedFailure,
+    /// Something failed, and it was not allowed to
+    Failure,
+    /// Nothing was run, as a condition was not met
+    Skipped,
+    /// Nothing was run, as the action is disabled by `skip`
+    Disabled,
+}
+
+impl Status {
+    /// Creates the [`Status`] of something that either succeeded or failed, and may or may not be allowed to fail
+    pub fn new(succeeded: bool, allowed_failure: bool) -> Self {
+        if succeeded {
+            Status::Success
+        } else if allowed_failure {
+            Status::AllowedFailure
+        } else {
+            Status::Failure
+        }
+    }
+
+    /// Returns whether the [`Status`] should fail the run as a whole
+    pub fn is_fatal(&self) -> bool {
+        *self == Status::Failure
+    }
+
+    /// Returns the [`Status`] of something made up of two parts with the statuses `self` and `other`, which is the worse
+    /// of the two
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::Status;
+    ///
+    /// assert_eq!(Status::Success.and(Status::AllowedFailure), Status::AllowedFailure);
+    /// assert_eq!(Status::Failure.and(Status::AllowedFailure), Status::Failure);
+    /// ```
+    pub fn and(self, other: Status) -> Status {
+        let severity = |status: Status| match status {
+            Status::Skipped | Status::Disabled => 0,
+            Status::Success => 1,
+            Status::AllowedFailure => 2,
+            Status::Failure => 3,
+        };
+        if severity(other) > severity(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Success => write!(f, "passed"),
+            Status::AllowedFailure => write!(f, "failed (allowed)"),
+            Status::Failure => write!(f, "failed"),
+            Status::Skipped => write!(f, "skipped"),
+            Status::Disabled => write!(f, "skipped (disabled)"),
+        }
+    }
+}
+
+/// Holds the results of every [`Action`] run by CIder
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunReport {
+    /// The result of each action, in the order the actions were run
+    pub actions: Vec<ActionResult>,
+    /// The titles of the pipelines that are allowed to fail without failing the run as a whole
+    pub allowed_failure_pipelines: Vec<String>,
+}
+
+impl RunReport {
+    /// Returns the [`Status`] of a pipeline, which has failed if any of its actions failed without being allowed to
+    pub fn pipeline_status(&self, pipeline: &str) -> Status {
+        let succeeded = !self
+            .actions
+            .iter()
+            .filter(|result| result.pipeline.as_deref() == Some(pipeline))
+            .any(|result| result.status.is_fatal());
+        Status::new(
+            succeeded,
+            self.allowed_failure_pipelines
+                .iter()
+                .any(|allowed| allowed == pipeline),
+        )
+    }
+
+    /// Returns whether the run as a whole succeeded
+    ///
+    /// A run succeeds when no top-level action and no pipeline has a fatal [`Status`].
+    pub fn succeeded(&self) -> bool {
+        self.by_pipeline().iter().all(|group| match group.pipeline {
+            Some(pipeline) => !self.pipeline_status(pipeline).is_fatal(),
+            None => group.actions.iter().all(|result| !result.status.is_fatal()),
+        })
+    }
+
+    /// Returns a human-readable summary of the [`Status`] of every pipeline and action, followed by how many actions
+    /// ended with each [`Status`]
+    pub fn summary(&self) -> Vec<String> {
+        let mut summary = vec![];
+        for group in self.by_pipeline() {
+            let indent = match group.pipeline {
+                Some(pipeline) => {
+                    summary.push(format!("pipeline {} {}", pipeline, self.pipeline_status(pipeline)));
+                    "    "
+                }
+                None => "",
+            };
+            for result in group.actions {
+                summary.push(format!("{}action {} {}", indent, result.name, result.status));
+            }
+        }
+        summary.push(self.counts());
+        summary.push(format!(
+            "run {}",
+            if self.succeeded() { "passed" } else { "failed" }
+        ));
+        summary
+    }
+
+    /// Returns how many actions ended with each [`Status`], leaving out those no action ended with
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::RunReport;
+    ///
+    /// assert_eq!(RunReport::default().counts(), "actions 0");
+    /// ```
+    pub fn counts(&self) -> String {
+        let mut counts = format!("actions {}", self.actions.len());
+        let statuses = [
+            Status::Success,
+            Status::Failure,
+            Status::AllowedFailure,
+            Status::Skipped,
+            Status::Disabled,
+        ];
+        for status in statuses {
+            let count = self.actions.iter().filter(|result| result.status == status).count();
+            if count > 0 {
+                counts += &format!(", {} {}", count, status);
+            }
+        }
+        counts
+    }
+
+    /// Returns a compact JSON summary of a run that took `total`, for tools that only need its outcome
+    ///
+    /// The summary holds how many actions `passed`, `failed`, were `allowed_failures`, and were `skipped`, where
+    /// disabled actions count as skipped, along with `duration_ms`, whether the run `succeeded`, and `failed_actions`.
+    /// Failed actions of a pipeline are named the way `requires` names them, such as `Release.Publish`. Actions that
+    /// failed within a pipeline that is allowed to fail count as allowed failures, as they do not fail the run.
+    pub fn summary_json(&self, total: Duration) -> JsonValue {
+        let status = |result: &ActionResult| {
+            let allowed = result
+                .pipeline
+                .as_ref()
+                .is_some_and(|pipeline| self.allowed_failure_pipelines.contains(pipeline));
+            if allowed && result.status == Status::Failure {
+                Status::AllowedFailure
+            } else {
+                result.status
+            }
+        };
+        let count = |statuses: &[Status]| {
+            self.actions
+                .iter()
+                .filter(|result| statuses.contains(&status(result)))
+                .count()
+        };
+        let failed_actions = self
+            .actions
+            .iter()
+            .filter(|result| status(result) == Status::Failure)
+            .map(|result| match &result.pipeline {
+                Some(pipeline) => format!("{}.{}", pipeline, result.name),
+                None => result.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        json::object! {
+            "passed": count(&[Status::Success]),
+            "failed": count(&[Status::Failure]),
+            "allowed_failures": count(&[Status::AllowedFailure]),
+            "skipped": count(&[Status::Skipped, Status::Disabled]),
+            "duration_ms": total.as_millis() as u64,
+            "succeeded": self.succeeded(),
+            "failed_actions": failed_actions,
+        }
+    }
+
+    /// Returns a human-readable explanation of why each action ran or was skipped
+    pub fn explain(&self) -> Vec<String> {
+        let mut explanation = vec![];
+        for result in &self.actions {
+            explanation.push(match &result.pipeline {
+                Some(pipeline) => format!(
+                    "action {} of pipeline {} {}",
+                    result.name, pipeline, result.status
+                ),
+                None => format!("action {} {}", result.name, result.status),
+            });
+            if result.reasons.is_empty() {
+                explanation.push("    no conditions configured".to_string());
+            }
+            for reason in &result.reasons {
+                explanation.push(format!("    {}", reason));
+            }
+        }
+        explanation
+    }
+
+    /// Returns a human-readable table of where the time of a run that took `total` went
+    ///
+    /// Lists the time spent on each backend, the `slowest` steps that took the longest, and the time spent pulling,
+    /// building, and cleaning up docker images. If the resources of steps were profiled, the steps that used the most
+    /// memory and the most CPU time are listed as well.
+    pub fn stats(&self, total: Duration, slowest: usize) -> Vec<String> {
+        let mut rows = vec![("total runtime".to_string(), format_duration(total))];
+
+        let mut backends: Vec<(&str, Duration, usize)> = vec![];
+        for result in &self.actions {
+            match backends.iter_mut().find(|backend| backend.0 == result.backend) {
+                Some(backend) => {
+                    backend.1 += result.duration;
+                    backend.2 += 1;
+                }
+                None => backends.push((&result.backend, result.duration, 1)),
+            }
+        }
+        for (backend, duration, actions) in backends {
+            rows.push((
+                format!("backend {}", backend),
+                format!("{} ({} actions)", format_duration(duration), actions),
+            ));
+        }
+
+        let mut steps = self
+            .actions
+            .iter()
+            .flat_map(|result| {
+                result
+                    .timings
+                    .iter()
+                    .filter(|timing| timing.kind == TimingKind::Step)
+                    .map(move |timing| (result, timing))
+            })
+            .collect::<Vec<_>>();
+        steps.sort_by_key(|step| std::cmp::Reverse(step.1.duration));
+        let step_name = |result: &ActionResult, step: &str| match &result.pipeline {
+            Some(pipeline) => format!("{}/{}/{}", pipeline, result.name, step),
+            None => format!("{}/{}", result.name, step),
+        };
+        for (rank, (result, timing)) in steps.into_iter().take(slowest).enumerate() {
+            rows.push((
+                format!("slowest step {}", rank + 1),
+                format!("{} {}", format_duration(timing.duration), step_name(result, &timing.name)),
+            ));
+        }
+
+        let measured = self
+            .actions
+            .iter()
+            .flat_map(|result| {
+                result
+                    .steps
+                    .iter()
+                    .filter_map(move |step| Some((result, &step.name, step.resources?)))
+            })
+            .collect::<Vec<_>>();
+        if let Some((result, step, usage)) = measured.iter().max_by_key(|m| m.2.peak_memory_bytes) {
+            rows.push((
+                "peak memory".to_string(),
+                format!("{} {}", format_bytes(usage.peak_memory_bytes), step_name(result, step)),
+            ));
+        }
+        if let Some((result, step, usage)) = measured.iter().max_by_key(|m| m.2.cpu_time) {
+            rows.push((
+                "most cpu time".to_string(),
+                format!("{} {}", format_duration(usage.cpu_time), step_name(result, step)),
+            ));
+        }
+
+        for kind in [TimingKind::DockerPull, TimingKind::DockerBuild, TimingKind::DockerClean] {
+            let timings = self
+                .actions
+                .iter()
+                .flat_map(|result| &result.timings)
+                .filter(|timing| timing.kind == kind)
+                .collect::<Vec<_>>();
+            if !timings.is_empty() {
+                let duration = timings.iter().map(|timing| timing.duration).sum();
+                rows.push((kind.to_string(), format_duration(duration)));
+            }
+        }
+
+        let width = rows.iter().map(|row| row.0.len()).max().unwrap_or_default();
+        rows.into_iter()
+            .map(|(label, value)| format!("{:width$}  {}", label, value, width = width))
+            .collect()
+    }
+
+    /// Returns the output of each action in the shape [`exec_actions`](crate::executor::exec_actions) used to return,
+    /// where the banner of each step is followed by its output
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::RunReport;
+    ///
+    /// assert!(RunReport::default().to_legacy_strings().is_empty());
+    /// ```
+    pub fn to_legacy_strings(&self) -> Vec<Vec<String>> {
+        self.actions.iter().map(|result| result.output.clone()).collect()
+    }
+
+    /// Returns the results grouped by the pipeline their actions belong to
+    ///
+    /// Pipelines appear in the order their first action was run. Top-level actions are grouped under a pipeline of None.
+    pub fn by_pipeline(&self) -> Vec<PipelineResults<'_>> {
+        let mut pipelines: Vec<PipelineResults> = vec![];
+        for result in &self.actions {
+            match pipelines
+                .iter_mut()
+                .find(|pipeline| pipeline.pipeline == result.pipeline.as_deref())
+            {
+                Some(pipeline) => pipeline.actions.push(result),
+                None => pipelines.push(PipelineResults {
+                    pipeline: result.pipeline.as_deref(),
+                    actions: vec![result],
+                }),
+            }
+        }
+        pipelines
+    }
+
+    /// Returns the name of the file the output of each action is written to by `--split-output`, in the same order as
+    /// [`RunReport::actions`]
+    ///
+    /// Each name is the title of the action with anything that is not safe within a filename replaced, followed by
+    /// `.log`. Titles that would share a file, including those that differ only by case, have an index appended.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::RunReport;
+    ///
+    /// assert!(RunReport::default().log_filenames().is_empty());
+    /// ```
+    pub fn log_filenames(&self) -> Vec<String> {
+        let mut taken: Vec<String> = vec![];
+        for result in &self.actions {
+            let stem = sanitize_filename(&result.name);
+            let mut filename = format!("{}.log", stem);
+            let mut index = 2;
+            while taken.iter().any(|other| other.eq_ignore_ascii_case(&filename)) {
+                filename = format!("{}-{}.log", stem, index);
+                index += 1;
+            }
+            taken.push(filename);
+        }
+        taken
+    }
+}
+
+/// Holds the results of the [`Action`]s belonging to a single pipeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineResults<'a> {
+    /// The title of the pipeline, or None for actions defined at the top level
+    pub pipeline: Option<&'a str>,
+    /// The results of the pipeline's actions
+    pub actions: Vec<&'a ActionResult>,
+}
+
+/// Holds the result of running a single [`Action`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionResult {
+    /// The title of the action
+    pub name: String,
+    /// The title of the pipeline the action belongs to, or None if it was defined at the top level
+    pub pipeline: Option<String>,
+    /// Everything output while running the action's steps
+    pub output: Vec<String>,
+    /// Whether the action succeeded
+    pub status: Status,
+    /// Why the action was run or skipped, such as the outcome of each of its conditions
+    pub reasons: Vec<String>,
+    /// The backend the action was run with
+    pub backend: String,
+    /// The directory the results of the action are written to, which is the `output` of the action
+    pub output_dir: String,
+    /// How long the action took to run
+    pub duration: Duration,
+    /// How long each part of the action took to run, in the order they were run
+    pub timings: Vec<Timing>,
+    /// The result of each step of the action, in the order the steps are defined. Steps of a docker action run while its
+    /// image is built, so they have no results of their own
+    pub steps: Vec<StepResult>,
+}
+
+impl ActionResult {
+    /// Creates a new [`ActionResult`] for the given [`Action`]
+    pub fn new(action: &Action, output: Vec<String>, status: Status) -> Self {
+        ActionResult {
+            name: action.shared_config.title().unwrap_or_default().to_string(),
+            pipeline: action.get_pipeline().map(str::to_string),
+            output,
+            status,
+            reasons: vec![],
+            backend: action.shared_config.get_backend().to_string(),
+            output_dir: action.shared_config.get_output().to_string(),
+            duration: Duration::default(),
+            timings: vec![],
+            steps: vec![],
+        }
+    }
+
+    /// Records why the action was run or skipped
+    pub fn with_reasons(mut self, reasons: Vec<String>) -> Self {
+        self.reasons = reasons;
+        self
+    }
+
+    /// Records how long the action, and each part of it, took to run
+    pub fn with_timings(mut self, duration: Duration, timings: Vec<Timing>) -> Self {
+        self.duration = duration;
+        self.timings = timings;
+        self
+    }
+
+    /// Records the result of each step of the action
+    pub fn with_steps(mut self, steps: Vec<StepResult>) -> Self {
+        self.steps = steps;
+        self
+    }
+}
+
+/// Holds the result of running a single step of an [`Action`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepResult {
+    /// The name of the step
+    pub name: String,
+    /// Whether the step succeeded
+    pub status: Status,
+    /// Everything the step wrote to its standard output
+    pub stdout: String,
+    /// Everything the step wrote to its standard error, which is empty if it was merged into its standard output
+    pub stderr: String,
+    /// The code the step exited with, or None if it was killed by a signal
+    pub exit_code: Option<i32>,
+    /// How long the step took to run
+    pub duration: Duration,
+    /// The peak memory and CPU time of the step, or None if resources were not profiled or cannot be measured on the
+    /// current platform
+    pub resources: Option<ResourceUsage>,
+}
+
+/// Describes which part of an [`Action`] a [`Timing`] measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimingKind {
+    /// A step of the action's manual
+    Step,
+    /// Pulling the base image of a docker action
+    DockerPull,
+    /// Building the image of a docker action
+    DockerBuild,
+    /// Removing the previous image of a docker action
+    DockerClean,
+}
+
+impl fmt::Display for TimingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimingKind::Step => write!(f, "step"),
+            TimingKind::DockerPull => write!(f, "docker pull"),
+            TimingKind::DockerBuild => write!(f, "docker build"),
+            TimingKind::DockerClean => write!(f, "docker clean"),
+        }
+    }
+}
+
+/// Holds how long part of an [`Action`] took to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    /// The name of the step, or a description of the part of the action that was timed
+    pub name: String,
+    /// Which part of the action was timed
+    pub kind: TimingKind,
+    /// How long it took to run
+    pub duration: Duration,
+    /// The command that was run, for the steps of an action
+    pub command: Option<CommandLine>,
+}
+
+impl Timing {
+    /// Creates a new [`Timing`]
+    pub fn new(name: impl Into<String>, kind: TimingKind, duration: Duration) -> Self {
+        Timing {
+            name: name.into(),
+            kind,
+            duration,
+            command: None,
+        }
+    }
+
+    /// Records the command that was run
+    pub fn with_command(mut self, command: CommandLine) -> Self {
+        self.command = Some(command);
+        self
+    }
+}
+
+/// The exact command a step was run with, so that a failing step can be reproduced by hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandLine {
+    /// The program that was run, such as `sh`
+    pub program: String,
+    /// The arguments the program was given
+    pub args: Vec<String>,
+    /// The directory the program was run within
+    pub working_dir: String,
+}
+
+impl CommandLine {
+    /// Returns the [`CommandLine`] as a JSON object
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            "program": self.program.clone(),
+            "args": self.args.clone(),
+            "working_dir": self.working_dir.clone(),
+        }
+    }
+}
+
+/// Displays the command as a single line that can be pasted into a POSIX shell
+///
+/// # Examples:
+/// ```
+/// use cider::results::CommandLine;
+///
+/// let command = CommandLine {
+///     program: "sh".to_string(),
+///     args: vec!["-c".to_string(), "echo 'hi'".to_string()],
+///     working_dir: "/tmp/my project".to_string(),
+/// };
+/// assert_eq!(command.to_string(), r#"cd '/tmp/my project' && sh -c 'echo '\''hi'\'''"#);
+/// ```
+impl fmt::Display for CommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cd {} && {}", shell_quote(&self.working_dir), shell_quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", shell_quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes a run as a whole: when it ran, what it ran against, and which CIder ran it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunMetadata {
+    /// When the run started, as an RFC 3339 timestamp
+    pub started: String,
+    /// When the run finished, as an RFC 3339 timestamp, or None while it is still running
+    pub finished: Option<String>,
+    /// The commit checked out when the run started, or None if it was not started within a git repository
+    pub commit: Option<String>,
+    /// The operating system CIder ran on, such as `linux`
+    pub os: String,
+    /// The version of CIder that ran
+    pub version: String,
+}
+
+impl RunMetadata {
+    /// Creates the [`RunMetadata`] of a run starting now within the current directory
+    pub fn capture() -> Self {
+        Self::capture_in(Path::new("."))
+    }
+
+    /// Creates the [`RunMetadata`] of a run starting now within `dir`
+    ///
+    /// The commit is found with `git rev-parse HEAD`, and left as None if `dir` is not within a git repository or git
+    /// could not be run.
+    pub fn capture_in(dir: &Path) -> Self {
+        RunMetadata {
+            started: timestamp(),
+            finished: None,
+            commit: git_commit(dir),
+            os: std::env::consts::OS.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Records that the run finished now
+    pub fn finish(&mut self) {
+        self.finished = Some(timestamp());
+    }
+
+    /// Returns the [`RunMetadata`] as a JSON object
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::RunMetadata;
+    ///
+    /// let mut metadata = RunMetadata::capture();
+    /// assert!(metadata.to_json()["finished"].is_null());
+    ///
+    /// metadata.finish();
+    /// let json = metadata.to_json();
+    ///
+    /// assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    /// assert_eq!(json["os"], std::env::consts::OS);
+    /// assert!(json["finished"].is_string());
+    /// ```
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            "started": self.started.clone(),
+            "finished": self.finished.clone(),
+            "commit": self.commit.clone(),
+            "os": self.os.clone(),
+            "version": self.version.clone(),
+        }
+    }
+
+    /// Returns a human-readable summary of the [`RunMetadata`]
+    pub fn summary(&self) -> Vec<String> {
+        vec![
+            format!("cider {} on {}", self.version, self.os),
+            format!("commit {}", self.commit.as_deref().unwrap_or("unknown")),
+            format!(
+                "started {}, finished {}",
+                self.started,
+                self.finished.as_deref().unwrap_or("unknown")
+            ),
+        ]
+    }
+}
+
+/// Returns the current time as an RFC 3339 timestamp
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Returns the commit checked out within the git repository `dir` is within, if any
+fn git_commit(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// The standard output of every step that has run so far, which `output_contains` conditions look up by step name
+///
+/// Clones of a [`StepOutputs`] share the same outputs, so a step recorded through one can be looked up through them all.
+/// When more than one step shares a name, the output of the step that ran last is kept.
+#[derive(Debug, Clone, Default)]
+pub struct StepOutputs(Arc<Mutex<HashMap<String, String>>>);
+
+impl StepOutputs {
+    /// Records that the step named `step` wrote `output` to its standard output
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::results::StepOutputs;
+    ///
+    /// let outputs = StepOutputs::default();
+    /// outputs.record("build", "Compiling cider");
+    ///
+    /// assert_eq!(outputs.get("build").as_deref(), Some("Compiling cider"));
+    /// assert_eq!(outputs.get("test"), None);
+    /// ```
+    pub fn record(&self, step: &str, output: &str) {
+        self.0
+            .lock()
+            .expect("A step panicked while recording its output.")
+            .insert(step.to_string(), output.to_string());
+    }
+
+    /// Returns the standard output of the step named `step`, or None if no step of that name has run
+    pub fn get(&self, step: &str) -> Option<String> {
+        self.0
+            .lock()
+            .expect("A step panicked while recording its output.")
+            .get(step)
+            .cloned()
+    }
+
+    /// Forgets the output of every step, such as before a new run starts
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .expect("A step panicked while recording its output.")
+            .clear();
+    }
+}
+
+/// Quotes `value` for a POSIX shell, unless it only holds characters that never need quoting
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Replaces every character of `name` that is not safe within a filename on every platform with an underscore
+///
+/// Leading dots are replaced as well, so the file is never hidden and never refers to a parent directory.
+pub(crate) fn sanitize_filename(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let leading_dot = c == '.' && name.chars().take(index).all(|c| c == '.');
+            if (c.is_alphanumeric() || "-_.".contains(c)) && !leading_dot {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if sanitized.is_empty() {
+        "action".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Formats a duration in seconds, such as `1.25s`
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, pipeline: Option<&str>) -> ActionResult {
+        ActionResult {
+            name: name.to_string(),
+            pipeline: pipeline.map(str::to_string),
+            output: vec![],
+            status: Status::Success,
+            reasons: vec![],
+            backend: "bash".to_string(),
+            output_dir: "./dist/cider/".to_string(),
+            duration: Duration::default(),
+            timings: vec![],
+            steps: vec![],
+        }
+    }
+
+    #[test]
+    fn test_run_metadata_outside_of_git() {
+        let dir = std::env::temp_dir().join(format!("cider_metadata_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let within_git = Command::new("git")
+            .args(["rev-parse", "--is-inside-work-tree"])
+            .current_dir(&dir)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        let metadata = RunMetadata::capture_in(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        if !within_git {
+            assert_eq!(metadata.commit, None);
+            assert!(metadata.to_json()["commit"].is_null());
+            assert_eq!(metadata.summary()[1], "commit unknown");
+        }
+        assert_eq!(metadata.finished, None);
+    }
+
+    #[test]
+    fn test_log_filenames() {
+        let report = RunReport {
+            actions: vec![
+                result("Build", None),
+                result("build", Some("Release")),
+                result("Build-2", None),
+                result("../Deploy: prod", None),
+                result("", None),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            report.log_filenames(),
+            ["Build.log", "build-2.log", "Build-2-2.log", "___Deploy__prod.log", "action.log"]
+        );
+    }
+
+    #[test]
+    fn test_group_by_pipeline() {
+        let report = RunReport {
+            actions: vec![
+                result("top", None),
+                result("build", Some("Build_Pipeline")),
+                result("test", Some("Build_Pipeline")),
+                result("lint", Some("Lint_Pipeline")),
+            ],
+            ..Default::default()
+        };
+        let grouped = report.by_pipeline();
+        assert_eq!(
+            grouped
+                .iter()
+                .map(|pipeline| (pipeline.pipeline, pipeline.actions.len()))
+                .collect::<Vec<_>>(),
+            vec![(None, 1), (Some("Build_Pipeline"), 2), (Some("Lint_Pipeline"), 1)]
+        );
+    }
+
+    #[test]
+    fn test_summary_json() {
+        let status = |mut result: ActionResult, status: Status| {
+            result.status = status;
+            result
+        };
+        let report = RunReport {
+            actions: vec![
+                result("build", None),
+                status(result("lint", None), Status::AllowedFailure),
+                status(result("test", None), Status::Failure),
+                status(result("docs", None), Status::Skipped),
+                status(result("bench", None), Status::Disabled),
+                result("package", Some("Release")),
+                status(result("publish", Some("Release")), Status::Failure),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            report.summary_json(Duration::from_millis(1500)),
+            json::object! {
+                "passed": 2,
+                "failed": 2,
+                "allowed_failures": 1,
+                "skipped": 2,
+                "duration_ms": 1500,
+                "succeeded": false,
+                "failed_actions": ["test", "Release.publish"],
+            }
+        );
+        assert_eq!(RunReport::default().summary_json(Duration::ZERO)["succeeded"], true);
+
+        let report = RunReport {
+            actions: vec![
+                result("build", None),
+                status(result("publish", Some("Nightly")), Status::Failure),
+            ],
+            allowed_failure_pipelines: vec!["Nightly".to_string()],
+        };
+        let summary = report.summary_json(Duration::ZERO);
+        assert_eq!(summary["failed"], 0);
+        assert_eq!(summary["allowed_failures"], 1);
+        assert_eq!(summary["succeeded"], true);
+        assert!(summary["failed_actions"].is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut build = result("build", Some("Build_Pipeline"));
+        build = build.with_timings(
+            Duration::from_secs(3),
+            vec![
+                Timing::new("compile", TimingKind::Step, Duration::from_secs(2)),
+                Timing::new("link", TimingKind::Step, Duration::from_secs(1)),
+            ],
+        );
+        let mut image = result("image", None);
+        image.backend = "docker".to_string();
+        image = image.with_timings(
+            Duration::from_secs(4),
+            vec![
+                Timing::new("docker pull", TimingKind::DockerPull, Duration::from_secs(1)),
+                Timing::new("docker build", TimingKind::DockerBuild, Duration::from_millis(2500)),
+            ],
+        );
+        let report = RunReport {
+            actions: vec![build, image],
+            ..Default::default()
+        };
+        assert_eq!(
+            report.stats(Duration::from_secs(7), 1),
+            vec![
+                "total runtime   7.00s",
+                "backend bash    3.00s (1 actions)",
+                "backend docker  4.00s (1 actions)",
+                "slowest step 1  2.00s Build_Pipeline/build/compile",
+                "docker pull     1.00s",
+                "docker build    2.50s",
+            ]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crate::utils::config::{Action, ShareableConfiguration, TopLevelConfiguration};
+use crate::utils::ignore::{self, IgnorePattern};
+use log::{info, warn};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A throwaway directory that a configuration is run within, so that running it cannot touch the files it was given
+///
+/// Steps run within the working directory CIder was started in, so creating a [`Sandbox`] copies the working directory
+/// into a new directory within [`env::temp_dir`], along with the source directory of the configuration and of every
+/// action that is not already within it, and points the configuration at the copies. The output of the configuration is
+/// written within the sandbox as well. The sandbox is removed once it is dropped, unless it was created to be kept.
+#[derive(Debug)]
+pub struct Sandbox {
+    root: PathBuf,
+    keep: bool,
+    working_dir: PathBuf,
+    /// Every source directory outside of the working directory that has been copied, along with where it was copied to
+    sources: Vec<(String, String)>,
+}
+
+impl Sandbox {
+    /// Copies `working_dir` and the sources of `config` into a new [`Sandbox`], and points `config` at the copies
+    ///
+    /// `.git` directories are never copied, and neither is anything ignored by the `.gitignore` of a directory that is
+    /// configured to `use_gitignore`, where the working directory follows the top level of the configuration. If `keep`
+    /// is set, the sandbox is left in place once it is dropped.
+    pub fn create(
+        config: &mut TopLevelConfiguration,
+        working_dir: &Path,
+        keep: bool,
+    ) -> io::Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let root = env::temp_dir().join(format!("cider-sandbox-{}-{}", process::id(), nanos));
+        fs::create_dir_all(&root)?;
+        info!("Sandbox created at {:#?}", root);
+        let sandboxed_working_dir = root.join("workdir");
+        let patterns = if config.s_config.get_use_gitignore() {
+            ignore::read_gitignore(working_dir)?
+        } else {
+            vec![]
+        };
+        copy_dir(working_dir, &sandboxed_working_dir, &patterns, "", &root)?;
+        let mut sandbox = Sandbox {
+            root,
+            keep,
+            working_dir: working_dir.to_path_buf(),
+            sources: vec![],
+        };
+
+        sandbox.enter(&mut config.s_config)?;
+        let mut actions = config.get_actions().clone();
+        sandbox.enter_actions(&mut actions)?;
+        config.set_actions(actions);
+        let mut post_run = config.get_post_run().clone();
+        sandbox.enter_actions(&mut post_run)?;
+        config.set_post_run(post_run);
+        let mut pipelines = config.get_pipelines().clone();
+        for pipeline in &mut pipelines {
+            sandbox.enter(&mut pipeline.shared_config)?;
+            let mut actions = pipeline.pipeline_config.get_actions().clone();
+            sandbox.enter_actions(&mut actions)?;
+            pipeline.pipeline_config.set_actions(actions);
+        }
+        config.set_pipelines(pipelines);
+        Ok(sandbox)
+    }
+
+    /// Returns the directory the sandbox is within
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the copy of the working directory, which steps should be run within
+    pub fn working_dir(&self) -> PathBuf {
+        self.root.join("workdir")
+    }
+
+    /// Points the source and output directories of each of `actions` into the sandbox
+    fn enter_actions(&mut self, actions: &mut [Action]) -> io::Result<()> {
+        for action in actions {
+            self.enter(&mut action.shared_config)?;
+        }
+        Ok(())
+    }
+
+    /// Points the source and output directories of `config` into the sandbox, copying its source if it is outside of the
+    /// working directory and has not been copied yet
+    fn enter(&mut self, config: &mut ShareableConfiguration) -> io::Result<()> {
+        let source = config.get_source().to_string();
+        let copy = if let Ok(within) = Path::new(&source).strip_prefix(&self.working_dir) {
+            self.working_dir()
+                .join(within)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            match self
+                .sources
+                .iter()
+                .find(|(original, _)| *original == source)
+            {
+                Some((_, copy)) => copy.clone(),
+                None => {
+                    let copy = self.root.join(format!("source-{}", self.sources.len()));
+                    let patterns = if config.get_use_gitignore() {
+                        ignore::read_gitignore(Path::new(&source))?
+                    } else {
+                        vec![]
+                    };
+                    copy_dir(Path::new(&source), &copy, &patterns, "", &self.root)?;
+                    let copy = copy.to_string_lossy().into_owned();
+                    self.sources.push((source, copy.clone()));
+                    copy
+                }
+            }
+        };
+        config.set_source(copy);
+        config.set_output(self.root.join("output").to_string_lossy().into_owned());
+        Ok(())
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if self.keep {
+            println!("The sandbox was kept at {}", self.root.display());
+            return;
+        }
+        match fs::remove_dir_all(&self.root) {
+            Ok(()) => info!("Sandbox removed from {:#?}", self.root),
+            Err(err) => warn!(
+                "The sandbox at {:#?} could not be removed: {}",
+                self.root, err
+            ),
+        }
+    }
+}
+
+/// Copies the contents of the directory `from` into `to`, skipping `.git`, anything matching `patterns`, and `skip`
+///
+/// `relative` is the path of `from` relative to the directory the patterns were read from.
+fn copy_dir(
+    from: &Path,
+    to: &Path,
+    patterns: &[IgnorePattern],
+    relative: &str,
+    skip: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let name = file_name.to_string_lossy();
+        let relative = if relative.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let file_type = entry.file_type()?;
+        if name == ".git"
+            || path == skip
+            || ignore::is_ignored(patterns, &relative, file_type.is_dir())
+        {
+            continue;
+        }
+        let target = to.join(&file_name);
+        if file_type.is_dir() {
+            copy_dir(&path, &target, patterns, &relative, skip)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Recreates the symbolic link at `from` at `to`, pointing at the same place
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+/// Copies what the symbolic link at `from` points at to `to`, as creating symbolic links may not be permitted
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        warn!(
+            "Skipping the symbolic link to a directory {:#?} while copying into the sandbox",
+            from
+        );
+        return Ok(());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsing::json_parser;
+
+    #[test]
+    fn test_sandbox_copies_sources() {
+        let temp = env::temp_dir().join(format!("cider_sandbox_test_{}", process::id()));
+        let working_dir = temp.join("project");
+        let outside = temp.join("outside");
+        fs::create_dir_all(working_dir.join("src")).unwrap();
+        fs::create_dir_all(working_dir.join(".git")).unwrap();
+        fs::create_dir_all(working_dir.join("target")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(working_dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(working_dir.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        fs::write(working_dir.join("target/cider"), "").unwrap();
+        fs::write(working_dir.join(".gitignore"), "target/\n").unwrap();
+        fs::write(outside.join("setup.sh"), "echo setup").unwrap();
+        let mut config = json_parser::parse_from_str(&format!(
+            r#"{{
+                "source_directory": "{}",
+                "use_gitignore": true,
+                "actions": ["Build", "Setup"],
+                "Build": {{ "manual": {{ "build": "echo build" }} }},
+                "Setup": {{ "source_directory": "{}", "manual": {{ "setup": "sh setup.sh" }} }}
+            }}"#,
+            working_dir.join("src").display(),
+            outside.display()
+        ));
+
+        let sandbox = Sandbox::create(&mut config, &working_dir, false).unwrap();
+        let root = sandbox.root().to_path_buf();
+        let copy = sandbox.working_dir();
+        assert_eq!(
+            config.s_config.get_source(),
+            copy.join("src").to_str().unwrap()
+        );
+        assert_eq!(
+            config.get_actions()[0].shared_config.get_source(),
+            config.s_config.get_source()
+        );
+        let outside_copy = Path::new(config.get_actions()[1].shared_config.get_source());
+        assert!(outside_copy.starts_with(&root));
+        assert!(outside_copy.join("setup.sh").exists());
+        assert!(config
+            .s_config
+            .get_output()
+            .starts_with(root.to_str().unwrap()));
+        assert_eq!(
+            fs::read_to_string(copy.join("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert!(copy.join(".gitignore").exists());
+        assert!(!copy.join(".git").exists());
+        assert!(!copy.join("target").exists());
+
+        drop(sandbox);
+        assert!(!root.exists());
+        assert!(working_dir.join("src/main.rs").exists());
+        fs::remove_dir_all(temp).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::process::Command;
+
+/// The size the terminal of a step reports, in rows and columns
+pub const SIZE: (u16, u16) = (24, 80);
+
+/// Returns whether steps can be run within a pseudo-terminal on the current platform, see [`open`]
+pub fn supported() -> bool {
+    cfg!(unix)
+}
+
+/// The end of a pseudo-terminal that CIder reads what a step wrote to its terminal from
+///
+/// Reading stops once every process that held the terminal has closed it.
+#[derive(Debug)]
+pub struct TerminalReader(File);
+
+impl Read for TerminalReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.read(buf) {
+            // Linux fails reads with EIO rather than returning end of file once the terminal has been closed
+            #[cfg(unix)]
+            Err(err) if err.raw_os_error() == Some(libc::EIO) => Ok(0),
+            read => read,
+        }
+    }
+}
+
+/// Opens a new pseudo-terminal of [`SIZE`], returning the end to read from along with the terminal itself, which a step
+/// is given as its standard output and error
+///
+/// The terminal does not translate `\n` into `\r\n`, so its output reads the same as the output of a pipe. Fails on
+/// platforms that are not [`supported`].
+pub fn open() -> io::Result<(TerminalReader, File)> {
+    #[cfg(unix)]
+    {
+        use std::ffi::CStr;
+        use std::fs::OpenOptions;
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::os::unix::io::{AsRawFd, FromRawFd};
+        use std::sync::Mutex;
+
+        // ptsname returns a pointer to a static buffer, so only one terminal can be named at a time
+        static NAMING: Mutex<()> = Mutex::new(());
+
+        let check = |result: libc::c_int| {
+            if result < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(result)
+            }
+        };
+        // SAFETY: posix_openpt has no preconditions, and the descriptor it returns is owned by the File from here on
+        let reader = unsafe {
+            File::from_raw_fd(check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?)
+        };
+        // SAFETY: the descriptor is a valid pseudo-terminal for as long as reader is alive
+        check(unsafe { libc::grantpt(reader.as_raw_fd()) })?;
+        // SAFETY: as with grantpt, the descriptor is a valid pseudo-terminal for as long as reader is alive
+        check(unsafe { libc::unlockpt(reader.as_raw_fd()) })?;
+        let path = {
+            let _naming = NAMING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            // SAFETY: ptsname returns either null or a nul-terminated string, which is copied before it can change
+            let name = unsafe { libc::ptsname(reader.as_raw_fd()) };
+            if name.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: name was checked to be non-null above, so it points to the nul-terminated string ptsname keeps,
+            // which NAMING stops any other thread from overwriting until it has been copied
+            unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+        };
+        let terminal = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(path)?;
+        let fd = terminal.as_raw_fd();
+        // SAFETY: termios and winsize are plain data, for which all zeroes is a valid value, and both pointers are valid
+        // for the duration of the calls
+        unsafe {
+            let mut settings: libc::termios = std::mem::zeroed();
+            check(libc::tcgetattr(fd, &mut settings))?;
+            settings.c_oflag &= !libc::ONLCR;
+            check(libc::tcsetattr(fd, libc::TCSANOW, &settings))?;
+            let size = libc::winsize {
+                ws_row: SIZE.0,
+                ws_col: SIZE.1,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            };
+            check(libc::ioctl(fd, libc::TIOCSWINSZ as _, &size))?;
+        }
+        Ok((TerminalReader(reader), terminal))
+    }
+    #[cfg(not(unix))]
+    {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pseudo-terminals are not supported on this platform",
+        ))
+    }
+}
+
+/// Makes the terminal `cmd` is given as its standard output the controlling terminal of the process, such as for
+/// tools that open `/dev/tty`
+///
+/// The process is placed within a session of its own, which also places it within a process group of its own, so
+/// every process it starts can be killed along with it.
+pub fn control(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: setsid and ioctl are async-signal-safe, so they can be called between forking and running the command
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() < 0 || libc::ioctl(1, libc::TIOCSCTTY as _, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_steps_see_a_terminal() {
+        let (mut reader, terminal) = open().unwrap();
+        let mut cmd = Command::new("sh");
+        let script = "if [ -t 1 ]; then echo terminal; fi; stty size < /dev/tty; echo error >&2";
+        cmd.args(["-c", script])
+            .stdin(Stdio::null())
+            .stdout(terminal.try_clone().unwrap())
+            .stderr(terminal);
+        control(&mut cmd);
+        let status = cmd.status().unwrap();
+        // Dropping the command closes the last copy of the terminal held by CIder, so that reading finishes
+        drop(cmd);
+        let mut output = String::new();
+        reader.read_to_string(&mut output).unwrap();
+        assert!(status.success());
+        assert_eq!(output, "terminal\n24 80\nerror\n");
+    }
+}
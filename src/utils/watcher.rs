@@ -1,41 +1,460 @@
-// use std::path::PathBuf;
-
-// /// Contains path information needed to watch directory.
-// ///
-// /// Watcher is a struct design to hold a path, as well as an enum that contains the necessary information/tools required
-// /// in order to watch a directory for changes being made to it.
-// ///
-// #[derive(Debug, Clone)]
-// pub struct Watcher {
-//     mode: WatchMode,
-//     watching: bool,
-//     watch_dir: PathBuf,
-// }
-
-// impl Watcher {
-//     /// Creates a new Watcher struct.
-//     ///
-//     /// The point of a watcher struct as see at [`Watcher`] is to contain path information and perform actions based on its
-//     /// [`WatchMode`]
-//     ///`
-//     ///
-//     pub fn new(mode: Option<WatchMode>, watching: bool, watch_dir: PathBuf) -> Self {
-//         Watcher {
-//             mode: match mode {
-//                 Some(mode) => mode,
-//                 None => WatchMode::Default,
-//             },
-//             watching,
-//             watch_dir,
-//         }
-//     }
-// }
-
-// /// Will be used to define multiple types of watchers with differing functionality
-// #[derive(Debug, Clone)]
-// pub enum WatchMode {
-//     /// The default mode for Watcher structs
-//     ///
-//     ///
-//     Default,
-// }
+use crate::utils::ignore::{self, IgnorePattern};
+use log::info;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the watched directory is checked for changes, unless [`Watcher::set_interval`] is used
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(2000);
+
+/// How long no file may change for after a change before it is acted on, unless [`Watcher::set_debounce`] is used
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often a heartbeat is printed while waiting for changes
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Names of directories that are never watched, as they usually hold build output or dependencies
+const IGNORED_DIRECTORIES: &[&str] = &["target", "node_modules", "bin", "obj"];
+
+/// Contains path information needed to watch directory.
+///
+/// Watcher is a struct design to hold a path, as well as an enum that contains the necessary information/tools required
+/// in order to watch a directory for changes being made to it.
+///
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    mode: WatchMode,
+    watch_dir: PathBuf,
+    modified_times: HashMap<PathBuf, SystemTime>,
+    /// The modification time and content hash of every watched file as of the last check, only used in
+    /// [`WatchMode::Hash`]
+    hashes: Option<HashMap<PathBuf, (SystemTime, u64)>>,
+    baseline: SystemTime,
+    newest_modified: Option<SystemTime>,
+    ignore_patterns: Vec<IgnorePattern>,
+    runs_completed: usize,
+    last_heartbeat: Instant,
+    interval: Duration,
+    debounce: Duration,
+}
+
+impl Watcher {
+    /// Creates a new Watcher struct.
+    ///
+    /// The point of a watcher struct as see at [`Watcher`] is to contain path information and perform actions based on its
+    /// [`WatchMode`]
+    ///
+    /// Only files modified after the [`Watcher`] is created are treated as changes, see [`Watcher::set_since`] to treat
+    /// earlier modifications as changes as well.
+    ///
+    /// # Examples:
+    /// ```
+    /// use cider::watcher::Watcher;
+    /// use std::path::PathBuf;
+    ///
+    /// let watcher = Watcher::new(None, PathBuf::from("./src"));
+    /// ```
+    pub fn new(mode: Option<WatchMode>, watch_dir: PathBuf) -> Self {
+        Watcher {
+            mode: mode.unwrap_or(WatchMode::Default),
+            watch_dir,
+            modified_times: HashMap::new(),
+            hashes: None,
+            baseline: SystemTime::now(),
+            newest_modified: None,
+            ignore_patterns: vec![],
+            runs_completed: 0,
+            last_heartbeat: Instant::now(),
+            interval: DEFAULT_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+        }
+    }
+
+    /// Returns the [`WatchMode`] of the [`Watcher`]
+    pub fn get_mode(&self) -> &WatchMode {
+        &self.mode
+    }
+
+    /// Treats files modified within `since` of the [`Watcher`] being created as changed on the next check
+    pub fn set_since(&mut self, since: Duration) {
+        self.baseline = SystemTime::now()
+            .checked_sub(since)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        info!("New watch baseline set: {:#?}", self.baseline);
+    }
+
+    /// Skips files and directories matching `patterns`, given relative to the watched directory, in addition to the
+    /// directories that are never watched
+    pub fn set_ignore_patterns(&mut self, patterns: Vec<IgnorePattern>) {
+        info!("New watch ignore patterns set: {:#?}", patterns);
+        self.ignore_patterns = patterns;
+    }
+
+    /// Sets how often the watched directory is checked for changes, see [`Watcher::wait`]
+    pub fn set_interval(&mut self, interval: Duration) {
+        info!("New watch interval set: {:#?}", interval);
+        self.interval = interval;
+    }
+
+    /// Returns how often the watched directory is checked for changes
+    pub fn get_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Sets how long no file may change for after a change before it is acted on, see [`Watcher::settle`]
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        info!("New watch debounce set: {:#?}", debounce);
+        self.debounce = debounce;
+    }
+
+    /// Returns how long no file may change for after a change before it is acted on
+    pub fn get_debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Sleeps until the watched directory should next be checked for changes
+    pub fn wait(&self) {
+        thread::sleep(self.interval);
+    }
+
+    /// Waits, after a change was detected, until no file has changed for the debounce period of the [`Watcher`], so
+    /// that a burst of changes, such as saving several files at once, is acted on once rather than partway through
+    ///
+    /// The watched directory is checked at the interval of the [`Watcher`] while waiting, or more often if the debounce
+    /// period is shorter than it.
+    pub fn settle(&mut self) -> std::io::Result<()> {
+        let mut quiet_since = Instant::now();
+        while quiet_since.elapsed() < self.debounce {
+            thread::sleep(self.interval.min(self.debounce - quiet_since.elapsed()));
+            if self.poll()? {
+                info!("Another change was detected, waiting for {:#?} again", self.debounce);
+                quiet_since = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the current state of the watched directory, so that only changes made afterwards are detected
+    pub fn set_baseline(&mut self) -> std::io::Result<()> {
+        self.poll()?;
+        Ok(())
+    }
+
+    /// Checks the watched directory, returning whether any file has changed since the last check
+    pub fn poll(&mut self) -> std::io::Result<bool> {
+        let watch_dir = self.watch_dir.clone();
+        // Files removed since the last check are forgotten, rather than keeping their last modification time
+        self.modified_times.clear();
+        self.get_files_modified_times(&watch_dir)?;
+        self.newest_modified = self.get_newest_modified();
+        let modified_since_baseline = match self.newest_modified {
+            Some(newest_modified) if newest_modified > self.baseline => {
+                self.baseline = newest_modified;
+                true
+            }
+            _ => false,
+        };
+        let changed = match self.mode {
+            WatchMode::Default => modified_since_baseline,
+            // The first check has no hashes to compare against, so it falls back to modification times
+            WatchMode::Hash => {
+                let first_check = self.hashes.is_none();
+                self.hashes_changed()? || first_check && modified_since_baseline
+            }
+        };
+        if !changed {
+            info!(
+                "File in watched directory most recently changed {:#?} ago.",
+                self.last_change_elapsed()
+            );
+        }
+        Ok(changed)
+    }
+
+    /// Announces that a change was detected and the configuration is about to run again
+    pub fn announce_change(&self) {
+        let banner = format!(
+            "==> Change detected in {}, re-running (run {})",
+            self.watch_dir.display(),
+            self.runs_completed + 1
+        );
+        info!("{}", banner);
+        println!("{}", banner);
+    }
+
+    /// Records that a run triggered by the [`Watcher`] has completed
+    pub fn record_run(&mut self) {
+        self.runs_completed += 1;
+        self.last_heartbeat = Instant::now();
+    }
+
+    /// Returns the number of runs that have completed while watching
+    pub fn get_runs_completed(&self) -> usize {
+        self.runs_completed
+    }
+
+    /// Returns a line describing what the [`Watcher`] is currently doing
+    pub fn status(&self) -> String {
+        format!(
+            "Watching {} files in {}, last change {}s ago, {} runs completed",
+            self.modified_times.len(),
+            self.watch_dir.display(),
+            self.last_change_elapsed().as_secs(),
+            self.runs_completed
+        )
+    }
+
+    /// Prints the status of the [`Watcher`] if no heartbeat or run has happened recently
+    pub fn heartbeat(&mut self) {
+        if self.last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            let status = self.status();
+            info!("{}", status);
+            println!("{}", status);
+            self.last_heartbeat = Instant::now();
+        }
+    }
+
+    /// Returns how long ago the newest change to a watched file was made
+    fn last_change_elapsed(&self) -> Duration {
+        self.newest_modified
+            .and_then(|modified| modified.elapsed().ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether `path` matches one of the ignore patterns of the [`Watcher`]
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.ignore_patterns.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(&self.watch_dir).unwrap_or(path);
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        ignore::is_ignored(&self.ignore_patterns, &relative, is_dir)
+    }
+
+    /// Hashes the contents of every watched file, returning whether any of them changed, appeared or disappeared since
+    /// the last check
+    ///
+    /// Files whose modification time has not changed are not read again. The first check only records the hashes.
+    fn hashes_changed(&mut self) -> io::Result<bool> {
+        let previous = self.hashes.take();
+        let mut hashes = HashMap::new();
+        for (path, modified) in &self.modified_times {
+            let hash = match previous.as_ref().and_then(|previous| previous.get(path)) {
+                Some((previous_modified, hash)) if previous_modified == modified => *hash,
+                _ => match hash_file(path) {
+                    Ok(Some(hash)) => hash,
+                    Ok(None) => continue,
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err),
+                },
+            };
+            hashes.insert(path.clone(), (*modified, hash));
+        }
+        let changed = previous.as_ref().is_some_and(|previous| {
+            let changed = hashes
+                .iter()
+                .filter(|(path, (_, hash))| previous.get(*path).map(|entry| entry.1) != Some(*hash))
+                .chain(previous.iter().filter(|(path, _)| !hashes.contains_key(*path)))
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>();
+            if !changed.is_empty() {
+                info!("The contents of {:#?} changed", changed);
+            }
+            !changed.is_empty()
+        });
+        self.hashes = Some(hashes);
+        Ok(changed)
+    }
+
+    fn get_newest_modified(&self) -> Option<SystemTime> {
+        let newest = self.modified_times.iter().max_by_key(|entry| entry.1);
+        if let Some((path, modified)) = newest {
+            info!(
+                "The file with the newest changes is {:#?}, changed at {:#?}",
+                path, modified
+            );
+        }
+        newest.map(|entry| *entry.1)
+    }
+
+    fn get_files_modified_times(&mut self, path: &Path) -> std::io::Result<()> {
+        info!("Getting modification times for files within {:#?}", path);
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            if Path::new(&file_name).extension().and_then(OsStr::to_str) == Some("class")
+                || file_name == "package-lock.json"
+            {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if self.is_ignored(&entry.path(), metadata.is_dir()) {
+                continue;
+            }
+            self.modified_times.insert(entry.path(), metadata.modified()?);
+            if metadata.is_dir()
+                && !IGNORED_DIRECTORIES
+                    .iter()
+                    .any(|ignored| file_name == OsStr::new(ignored))
+            {
+                self.get_files_modified_times(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hashes the contents of the file at `path`, or returns [`None`] if `path` is not a file
+pub(crate) fn hash_file(path: &Path) -> io::Result<Option<u64>> {
+    if !fs::metadata(path)?.is_file() {
+        return Ok(None);
+    }
+    let mut hasher = DefaultHasher::new();
+    fs::read(path)?.hash(&mut hasher);
+    Ok(Some(hasher.finish()))
+}
+
+/// Will be used to define multiple types of watchers with differing functionality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchMode {
+    /// The default mode for Watcher structs
+    ///
+    /// Detects changes by comparing the modification times of files.
+    Default,
+    /// Detects changes by comparing the contents of files, so that a file which is touched without being changed does
+    /// not trigger a run
+    ///
+    /// Files are hashed on every check, which is slower than comparing modification times, although files whose
+    /// modification time has not changed are not read again.
+    Hash,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_first_poll_detects_files() {
+        let mut watcher = Watcher::new(None, PathBuf::from("./src"));
+        watcher.set_since(Duration::from_secs(u32::MAX as u64));
+        assert!(watcher.poll().unwrap());
+        assert!(watcher.status().starts_with("Watching "));
+    }
+
+    #[test]
+    fn test_cold_start_ignores_existing_files() {
+        let mut watcher = Watcher::new(None, PathBuf::from("./src"));
+        assert!(!watcher.poll().unwrap());
+    }
+
+    #[test]
+    fn test_change_after_start_is_detected() {
+        let watch_dir = std::env::temp_dir().join(format!("cider_watch_{}", std::process::id()));
+        fs::create_dir_all(&watch_dir).unwrap();
+        let mut watcher = Watcher::new(None, watch_dir.clone());
+        watcher.set_since(Duration::from_secs(1));
+        fs::write(watch_dir.join("main.rs"), "fn main() {}").unwrap();
+        assert!(watcher.poll().unwrap());
+        assert!(!watcher.poll().unwrap());
+        fs::remove_dir_all(watch_dir).unwrap();
+    }
+
+    #[test]
+    fn test_ignored_changes_are_not_detected() {
+        let watch_dir = std::env::temp_dir().join(format!("cider_watch_ignore_{}", std::process::id()));
+        fs::create_dir_all(watch_dir.join("build")).unwrap();
+        let mut watcher = Watcher::new(None, watch_dir.clone());
+        watcher.set_since(Duration::from_secs(1));
+        watcher.set_ignore_patterns(
+            ["build/", "*.log"]
+                .iter()
+                .filter_map(|line| IgnorePattern::parse(line))
+                .collect(),
+        );
+        fs::write(watch_dir.join("build").join("output.o"), "").unwrap();
+        fs::write(watch_dir.join("run.log"), "").unwrap();
+        let changed = watcher.poll().unwrap();
+        let watched = watcher.modified_times.len();
+        fs::remove_dir_all(watch_dir).unwrap();
+        assert!(!changed);
+        assert_eq!(watched, 0);
+    }
+
+    #[test]
+    fn test_hash_mode_ignores_touched_files() {
+        let watch_dir = std::env::temp_dir().join(format!("cider_watch_hash_{}", std::process::id()));
+        fs::create_dir_all(&watch_dir).unwrap();
+        let file = watch_dir.join("main.rs");
+        fs::write(&file, "fn main() {}").unwrap();
+        let mut watcher = Watcher::new(Some(WatchMode::Hash), watch_dir.clone());
+        watcher.set_baseline().unwrap();
+        let touched = SystemTime::now() + Duration::from_secs(5);
+        File::options().write(true).open(&file).unwrap().set_modified(touched).unwrap();
+        let after_touch = watcher.poll().unwrap();
+        fs::write(&file, "fn main() { println!() }").unwrap();
+        let after_edit = watcher.poll().unwrap();
+        fs::remove_file(&file).unwrap();
+        let after_removal = watcher.poll().unwrap();
+        fs::remove_dir_all(watch_dir).unwrap();
+        assert!(!after_touch);
+        assert!(after_edit);
+        assert!(after_removal);
+    }
+
+    #[test]
+    fn test_settle_waits_for_changes_to_stop() {
+        let watch_dir = std::env::temp_dir().join(format!("cider_watch_settle_{}", std::process::id()));
+        fs::create_dir_all(&watch_dir).unwrap();
+        let file = watch_dir.join("main.rs");
+        let mut watcher = Watcher::new(None, watch_dir.clone());
+        watcher.set_baseline().unwrap();
+        watcher.set_interval(Duration::from_millis(10));
+        watcher.set_debounce(Duration::from_millis(200));
+        assert_eq!(watcher.get_interval(), Duration::from_millis(10));
+        let modified = SystemTime::now() + Duration::from_secs(5);
+        fs::write(&file, "fn main() {}").unwrap();
+        File::options().write(true).open(&file).unwrap().set_modified(modified).unwrap();
+        assert!(watcher.poll().unwrap());
+        let writer = {
+            let file = file.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                let modified = modified + Duration::from_secs(5);
+                File::options().write(true).open(&file).unwrap().set_modified(modified).unwrap();
+            })
+        };
+        let started = Instant::now();
+        watcher.settle().unwrap();
+        writer.join().unwrap();
+        let changed_after = watcher.poll().unwrap();
+        fs::remove_dir_all(watch_dir).unwrap();
+        // The change made while settling restarts the quiet period, and is not detected again
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert!(!changed_after);
+    }
+
+    #[test]
+    fn test_no_change_after_baseline() {
+        let mut watcher = Watcher::new(None, PathBuf::from("./src"));
+        watcher.set_baseline().unwrap();
+        assert!(!watcher.poll().unwrap());
+    }
+
+    #[test]
+    fn test_record_run() {
+        let mut watcher = Watcher::new(None, PathBuf::from("./src"));
+        watcher.record_run();
+        assert_eq!(watcher.get_runs_completed(), 1);
+        assert!(watcher.status().ends_with("1 runs completed"));
+    }
+}